@@ -0,0 +1,511 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::application_status::{self, ApplicationStatus, StageDuration, StatusChange};
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::reminder::{FollowUp, Reminder};
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+const SELECT_APPLICATIONS: &str = "SELECT id, date_applied, last_updated, company_id, \
+     job_posting_id, contact_id, hide, status FROM applied_to";
+
+/// A record of applying to a company, stored in the `applied_to` table.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Application {
+    pub id: Option<i32>,
+    /// Defaults to the day the application is added to the database.
+    pub date_applied: Option<NaiveDate>,
+    pub last_updated: Option<DateTime<Utc>>,
+    pub company_id: Option<i32>,
+    pub job_posting_id: Option<i32>,
+    /// The contact who referred us or is handling the application.
+    pub contact_id: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+    /// Only written when the application is added. Afterwards the status is
+    /// moved along with `change_status` so every step is validated and
+    /// recorded in the history.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub status: ApplicationStatus,
+}
+
+impl Application {
+    pub fn new(
+        company_id: i32,
+        job_posting_id: Option<i32>,
+        contact_id: Option<i32>,
+    ) -> Application {
+        Application {
+            id: None,
+            date_applied: None,
+            last_updated: None,
+            company_id: Some(company_id),
+            job_posting_id,
+            contact_id,
+            hide: false,
+            status: ApplicationStatus::default(),
+        }
+    }
+
+    pub(crate) fn get_by_company(
+        conn: &Connection,
+        company_id: i32,
+    ) -> Result<Vec<Application>, JobSearchError> {
+        entity::query(
+            conn,
+            &format!(
+                "{} WHERE company_id = (?1) AND {} ORDER BY {}",
+                SELECT_APPLICATIONS,
+                Visibility::Visible.condition(Application::TABLE),
+                Application::ORDER_BY
+            ),
+            params![company_id],
+        )
+    }
+
+    /// Both ends of the range are inclusive.
+    pub(crate) fn get_applied_between(
+        conn: &Connection,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Application>, JobSearchError> {
+        entity::query(
+            conn,
+            &format!(
+                "{} WHERE date(date_applied) BETWEEN (?1) AND (?2) AND {} ORDER BY {}",
+                SELECT_APPLICATIONS,
+                Visibility::Visible.condition(Application::TABLE),
+                Application::ORDER_BY
+            ),
+            params![from, to],
+        )
+    }
+
+    /// Moves the application to `status`, recording the change in its
+    /// history. Fails with `InvalidTransition` if the pipeline does not allow
+    /// the move.
+    pub(crate) fn change_status(
+        &mut self,
+        conn: &Connection,
+        status: ApplicationStatus,
+    ) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "application")?;
+
+        // Check against the stored status in case this copy is stale
+        let current = Application::get_by_id(conn, id)?.status;
+
+        if !current.can_transition_to(status) {
+            return Err(JobSearchError::InvalidTransition {
+                from: current,
+                to: status,
+            });
+        }
+
+        // The history row is written by the applied_to_status_update trigger
+        let _ = conn.execute(
+            "UPDATE applied_to SET status = (?1) WHERE id = (?2)",
+            params![status, id],
+        )?;
+
+        self.status = status;
+        self.last_updated = Application::get_by_id(conn, id)?.last_updated;
+
+        Ok(())
+    }
+}
+
+impl Entity for Application {
+    const TABLE: &'static str = "applied_to";
+    const NAME: &'static str = "application";
+    const SELECT: &'static str = SELECT_APPLICATIONS;
+    const ORDER_BY: &'static str = "date_applied, id";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("applied_to.date_applied"),
+        company: Some("applied_to.company_id = ?"),
+        status: Some("applied_to.status = ?"),
+        contact_type: Some("applied_to.contact_id IN (SELECT id FROM contacts WHERE type_id = ?)"),
+        text: &[],
+        name: None,
+        tag: Some(
+            "applied_to.id IN (SELECT application_tags.application_id FROM application_tags \
+             JOIN tags ON tags.id = application_tags.tag_id WHERE tags.name = ?)",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Application, JobSearchError> {
+        let application = Application {
+            id: row.get(0)?,
+            date_applied: row.get(1)?,
+            last_updated: timestamp::get(row, 2)?,
+            company_id: row.get(3)?,
+            job_posting_id: row.get(4)?,
+            contact_id: row.get(5)?,
+            hide: row.get::<_, Option<bool>>(6)?.unwrap_or(false),
+            status: row.get(7)?,
+        };
+
+        Ok(application)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        // Same as the column default when no date is set
+        let _ = conn.execute(
+            "INSERT INTO applied_to (date_applied, company_id, job_posting_id, contact_id, hide, status, last_updated) \
+             VALUES (COALESCE(?1, CURRENT_DATE), ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                self.date_applied,
+                self.company_id,
+                self.job_posting_id,
+                self.contact_id,
+                self.hide,
+                self.status,
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        let id = conn.last_insert_rowid() as i32;
+
+        let application = Application::get_by_id(conn, id)?;
+
+        self.id = application.id;
+        self.date_applied = application.date_applied;
+        self.last_updated = application.last_updated;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "application")?;
+
+        let _ = conn.execute(
+            "UPDATE applied_to SET date_applied = COALESCE(?1, date_applied), company_id = (?2), \
+             job_posting_id = (?3), contact_id = (?4), hide = (?5) WHERE id = (?6)",
+            params![
+                self.date_applied,
+                self.company_id,
+                self.job_posting_id,
+                self.contact_id,
+                self.hide,
+                id
+            ],
+        )?;
+
+        let application = Application::get_by_id(conn, id)?;
+
+        self.date_applied = application.date_applied;
+        self.last_updated = application.last_updated;
+        self.status = application.status;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Application>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Application> {
+    pub fn change_status(
+        &self,
+        application: &mut Application,
+        status: ApplicationStatus,
+    ) -> Result<(), JobSearchError> {
+        application.change_status(self.conn(), status)
+    }
+
+    /// Moves the application to `status` and adds a reminder to follow up
+    /// on it. Neither is kept if the other fails.
+    pub fn change_status_with_follow_up(
+        &self,
+        application: &mut Application,
+        status: ApplicationStatus,
+        follow_up: &FollowUp,
+    ) -> Result<Reminder, JobSearchError> {
+        let conn = self.conn();
+
+        with_savepoint(conn, || {
+            application.change_status(conn, status)?;
+
+            Reminder::add_application_follow_up(
+                conn,
+                application,
+                follow_up,
+                Local::now().date_naive(),
+            )
+        })
+    }
+
+    /// Every status the application has been in, oldest first.
+    pub fn status_history(&self, application_id: i32) -> Result<Vec<StatusChange>, JobSearchError> {
+        application_status::get_history(self.conn(), application_id)
+    }
+
+    /// How long the application spent in each stage, oldest first.
+    pub fn stage_durations(
+        &self,
+        application_id: i32,
+    ) -> Result<Vec<StageDuration>, JobSearchError> {
+        application_status::get_stage_durations(self.conn(), application_id, Utc::now())
+    }
+
+    pub fn get_by_company(&self, company_id: i32) -> Result<Vec<Application>, JobSearchError> {
+        Application::get_by_company(self.conn(), company_id)
+    }
+
+    pub fn get_applied_between(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Application>, JobSearchError> {
+        Application::get_applied_between(self.conn(), from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::company::Company;
+    use crate::create_in_memory_db;
+    use crate::job_posting::JobPosting;
+
+    fn create_company(conn: &Connection, name: &str) -> i32 {
+        let mut company = Company::new(name.to_string(), None, None, None);
+        company.add_to_db(conn).unwrap();
+
+        company.id.unwrap()
+    }
+
+    fn create_application(conn: &Connection, company_id: i32, date: &str) -> Application {
+        let mut application = Application::new(company_id, None, None);
+        application.date_applied = Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap());
+        application.add_to_db(conn).unwrap();
+
+        application
+    }
+
+    #[test]
+    fn test_add_to_db() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+
+        let mut job_posting = JobPosting::new("https://example.com/job".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+
+        let mut application = Application::new(company_id, job_posting.id, None);
+        application.add_to_db(&conn).unwrap();
+
+        assert_ne!(application.id, None);
+        // CURRENT_DATE is in UTC
+        assert_eq!(application.date_applied, Some(Utc::now().date_naive()));
+        assert_eq!(application.last_updated, None);
+    }
+
+    #[test]
+    fn test_add_to_db_with_unknown_company() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut application = Application::new(42, None, None);
+
+        assert!(matches!(
+            application.add_to_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_to_db_with_unknown_contact() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+
+        let mut application = Application::new(company_id, None, Some(42));
+
+        assert!(matches!(
+            application.add_to_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_db() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = create_application(&conn, company_id, "2020-05-01");
+
+        application.hide = true;
+        application.update_db(&conn).unwrap();
+
+        assert_ne!(application.last_updated, None);
+        assert!(
+            Application::get_by_id(&conn, application.id.unwrap())
+                .unwrap()
+                .hide
+        );
+    }
+
+    #[test]
+    fn test_delete_from_db() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = create_application(&conn, company_id, "2020-05-01");
+        let id = application.id.unwrap();
+
+        application.delete_from_db(&conn).unwrap();
+
+        assert_eq!(application.id, None);
+        assert!(matches!(
+            Application::get_by_id(&conn, id),
+            Err(JobSearchError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_by_company() {
+        let conn = create_in_memory_db().unwrap();
+        let google = create_company(&conn, "google");
+        let amazon = create_company(&conn, "amazon");
+
+        create_application(&conn, google, "2020-05-01");
+        create_application(&conn, google, "2020-05-02");
+        create_application(&conn, amazon, "2020-05-03");
+
+        let applications = Application::get_by_company(&conn, google).unwrap();
+
+        assert_eq!(applications.len(), 2);
+        assert_eq!(Application::get_all(&conn).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_get_applied_between() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+
+        create_application(&conn, company_id, "2020-04-30");
+        create_application(&conn, company_id, "2020-05-01");
+        create_application(&conn, company_id, "2020-05-31");
+        create_application(&conn, company_id, "2020-06-01");
+
+        let applications = Application::get_applied_between(
+            &conn,
+            NaiveDate::from_ymd_opt(2020, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2020, 5, 31).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(applications.len(), 2);
+    }
+
+    #[test]
+    fn test_change_status() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = create_application(&conn, company_id, "2020-05-01");
+
+        application
+            .change_status(&conn, ApplicationStatus::Screening)
+            .unwrap();
+        application
+            .change_status(&conn, ApplicationStatus::Offer)
+            .unwrap();
+
+        let history = application_status::get_history(&conn, application.id.unwrap()).unwrap();
+        let statuses: Vec<ApplicationStatus> = history.iter().map(|change| change.status).collect();
+
+        assert_eq!(application.status, ApplicationStatus::Offer);
+        assert_eq!(
+            statuses,
+            vec![
+                ApplicationStatus::Applied,
+                ApplicationStatus::Screening,
+                ApplicationStatus::Offer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_status_invalid_transition() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = create_application(&conn, company_id, "2020-05-01");
+
+        application
+            .change_status(&conn, ApplicationStatus::Rejected)
+            .unwrap();
+        let result = application.change_status(&conn, ApplicationStatus::Interviewing);
+
+        assert!(matches!(
+            result,
+            Err(JobSearchError::InvalidTransition {
+                from: ApplicationStatus::Rejected,
+                to: ApplicationStatus::Interviewing
+            })
+        ));
+        assert_eq!(
+            Application::get_by_id(&conn, application.id.unwrap())
+                .unwrap()
+                .status,
+            ApplicationStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn test_update_db_keeps_status() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = create_application(&conn, company_id, "2020-05-01");
+
+        application.status = ApplicationStatus::Accepted;
+        application.update_db(&conn).unwrap();
+
+        assert_eq!(application.status, ApplicationStatus::Applied);
+    }
+
+    #[test]
+    fn test_stage_durations() {
+        let conn = create_in_memory_db().unwrap();
+        let company_id = create_company(&conn, "google");
+        let mut application = Application::new(company_id, None, None);
+        application.status = ApplicationStatus::Saved;
+        application.add_to_db(&conn).unwrap();
+        let id = application.id.unwrap();
+
+        conn.execute(
+            "UPDATE application_status_history SET changed_date = '2020-05-01 10:00:00' WHERE application_id = (?1)",
+            params![id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO application_status_history (application_id, status, changed_date) \
+             VALUES (?1, 'applied', '2020-05-03 10:00:00')",
+            params![id],
+        )
+        .unwrap();
+
+        let now = "2020-05-10T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let durations = application_status::get_stage_durations(&conn, id, now).unwrap();
+
+        assert_eq!(durations.len(), 2);
+        assert_eq!(durations[0].status, ApplicationStatus::Saved);
+        assert_eq!(durations[0].duration, chrono::Duration::days(2));
+        assert_eq!(durations[1].status, ApplicationStatus::Applied);
+        assert_eq!(durations[1].duration, chrono::Duration::days(7));
+    }
+}
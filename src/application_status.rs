@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, ToSql, NO_PARAMS};
+
+use crate::errors::JobSearchError;
+use crate::timestamp;
+
+/// Where an application stands in the hiring pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ApplicationStatus {
+    Saved,
+    #[default]
+    Applied,
+    Screening,
+    Interviewing,
+    Offer,
+    Accepted,
+    Rejected,
+    Withdrawn,
+    Ghosted,
+}
+
+use ApplicationStatus::*;
+
+impl ApplicationStatus {
+    pub const ALL: [ApplicationStatus; 9] = [
+        Saved,
+        Applied,
+        Screening,
+        Interviewing,
+        Offer,
+        Accepted,
+        Rejected,
+        Withdrawn,
+        Ghosted,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Saved => "saved",
+            Applied => "applied",
+            Screening => "screening",
+            Interviewing => "interviewing",
+            Offer => "offer",
+            Accepted => "accepted",
+            Rejected => "rejected",
+            Withdrawn => "withdrawn",
+            Ghosted => "ghosted",
+        }
+    }
+
+    /// The statuses an application in this status may move to.
+    ///
+    /// Stages can be skipped (a referral may go straight to interviewing),
+    /// but never revisited, except that a company that ghosted us can pick
+    /// the process back up. Accepted, rejected and withdrawn are final.
+    pub fn next(self) -> &'static [ApplicationStatus] {
+        match self {
+            Saved => &[Applied, Withdrawn],
+            Applied => &[Screening, Interviewing, Offer, Rejected, Withdrawn, Ghosted],
+            Screening => &[Interviewing, Offer, Rejected, Withdrawn, Ghosted],
+            Interviewing => &[Offer, Rejected, Withdrawn, Ghosted],
+            Offer => &[Accepted, Rejected, Withdrawn, Ghosted],
+            Ghosted => &[Screening, Interviewing, Offer, Rejected, Withdrawn],
+            Accepted | Rejected | Withdrawn => &[],
+        }
+    }
+
+    pub fn can_transition_to(self, status: ApplicationStatus) -> bool {
+        self.next().contains(&status)
+    }
+
+    pub fn is_final(self) -> bool {
+        self.next().is_empty()
+    }
+}
+
+impl fmt::Display for ApplicationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApplicationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ApplicationStatus, String> {
+        ApplicationStatus::ALL
+            .iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown application status: {}", s))
+    }
+}
+
+impl ToSql for ApplicationStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ApplicationStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err: String| FromSqlError::Other(err.into()))
+    }
+}
+
+/// One entry of an application's status history.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusChange {
+    pub status: ApplicationStatus,
+    pub changed_date: DateTime<Utc>,
+}
+
+/// How long an application spent in one stage of the pipeline. The current
+/// stage is measured up to now.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StageDuration {
+    pub status: ApplicationStatus,
+    /// Serialized as a number of seconds.
+    #[cfg_attr(feature = "serde", serde(with = "seconds"))]
+    pub duration: Duration,
+}
+
+#[cfg(feature = "serde")]
+mod seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::seconds)
+    }
+}
+
+pub(crate) fn get_history(
+    conn: &Connection,
+    application_id: i32,
+) -> Result<Vec<StatusChange>, JobSearchError> {
+    let mut stmt = conn.prepare(
+        "SELECT status, changed_date FROM application_status_history \
+         WHERE application_id = (?1) ORDER BY datetime(changed_date), id",
+    )?;
+
+    let history_iter = stmt.query_map(params![application_id], |row| {
+        Ok(StatusChange {
+            status: row.get(0)?,
+            changed_date: timestamp::get_required(row, 1)?,
+        })
+    })?;
+
+    let mut history = Vec::new();
+    for change in history_iter {
+        history.push(change?);
+    }
+
+    Ok(history)
+}
+
+/// The history of every application, by the application's id, read in one
+/// query.
+pub(crate) fn get_all_histories(
+    conn: &Connection,
+) -> Result<HashMap<i32, Vec<StatusChange>>, JobSearchError> {
+    let mut stmt = conn.prepare(
+        "SELECT application_id, status, changed_date FROM application_status_history \
+         ORDER BY application_id, datetime(changed_date), id",
+    )?;
+
+    let history_iter = stmt.query_map(NO_PARAMS, |row| {
+        let change = StatusChange {
+            status: row.get(1)?,
+            changed_date: timestamp::get_required(row, 2)?,
+        };
+
+        Ok((row.get(0)?, change))
+    })?;
+
+    let mut histories: HashMap<i32, Vec<StatusChange>> = HashMap::new();
+    for change in history_iter {
+        let (application_id, change) = change?;
+        histories.entry(application_id).or_default().push(change);
+    }
+
+    Ok(histories)
+}
+
+pub(crate) fn get_stage_durations(
+    conn: &Connection,
+    application_id: i32,
+    now: DateTime<Utc>,
+) -> Result<Vec<StageDuration>, JobSearchError> {
+    let history = get_history(conn, application_id)?;
+
+    let durations = history
+        .iter()
+        .enumerate()
+        .map(|(index, change)| {
+            let end = history
+                .get(index + 1)
+                .map(|next| next.changed_date)
+                .unwrap_or(now);
+
+            StageDuration {
+                status: change.status,
+                duration: end - change.changed_date,
+            }
+        })
+        .collect();
+
+    Ok(durations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_names() {
+        for status in ApplicationStatus::ALL.iter() {
+            assert_eq!(status.as_str().parse::<ApplicationStatus>(), Ok(*status));
+        }
+
+        assert_eq!("Offer".parse::<ApplicationStatus>(), Ok(Offer));
+        assert!("hired".parse::<ApplicationStatus>().is_err());
+    }
+
+    #[test]
+    fn test_can_transition_to() {
+        let test_cases = vec![
+            (Saved, Applied, true),
+            (Saved, Offer, false),
+            (Applied, Interviewing, true),
+            (Interviewing, Screening, false),
+            (Offer, Accepted, true),
+            (Ghosted, Interviewing, true),
+            (Accepted, Withdrawn, false),
+            (Rejected, Applied, false),
+            (Applied, Applied, false),
+        ];
+
+        for (from, to, expected) in test_cases {
+            assert_eq!(from.can_transition_to(to), expected, "{} -> {}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_is_final() {
+        assert!(Accepted.is_final());
+        assert!(Rejected.is_final());
+        assert!(Withdrawn.is_final());
+        assert!(!Ghosted.is_final());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let change = StatusChange {
+            status: Interviewing,
+            changed_date: "2020-05-14T21:16:39Z".parse().unwrap(),
+        };
+        let json = serde_json::to_value(&change).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({"status": "interviewing", "changed_date": "2020-05-14T21:16:39Z"})
+        );
+        assert_eq!(
+            serde_json::from_value::<StatusChange>(json).unwrap(),
+            change
+        );
+
+        let stage = StageDuration {
+            status: Applied,
+            duration: Duration::hours(2),
+        };
+        assert_eq!(serde_json::to_value(&stage).unwrap()["duration"], 7200);
+    }
+}
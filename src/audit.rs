@@ -0,0 +1,494 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::{self, FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection, Row};
+use serde_json::{Map, Value};
+
+use crate::database::Table;
+use crate::entity::Entity;
+use crate::errors::{not_found, JobSearchError};
+use crate::timestamp;
+
+const SELECT_ENTRIES: &str = "SELECT id, table_name, record_id, action, old_values, new_values, \
+     changed_date FROM audit_log";
+
+/// What a change in the audit log did to a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AuditAction {
+    Insert,
+    Update,
+    Hide,
+    Unhide,
+    Delete,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 5] = [
+        AuditAction::Insert,
+        AuditAction::Update,
+        AuditAction::Hide,
+        AuditAction::Unhide,
+        AuditAction::Delete,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Insert => "insert",
+            AuditAction::Update => "update",
+            AuditAction::Hide => "hide",
+            AuditAction::Unhide => "unhide",
+            AuditAction::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<AuditAction, String> {
+        AuditAction::ALL
+            .iter()
+            .find(|action| action.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown audit action: {}", s))
+    }
+}
+
+impl FromSql for AuditAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err: String| FromSqlError::Other(err.into()))
+    }
+}
+
+/// One insert, update, hide or delete of a record, written by the database
+/// as it happens. The values are the columns of the record by name, apart
+/// from `last_updated`, which `changed_date` stands in for.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditEntry {
+    pub id: i32,
+    /// The table of the record, e.g. `job_postings`.
+    pub table: String,
+    pub record_id: i32,
+    pub action: AuditAction,
+    /// The record before the change, `None` for an insert.
+    pub old_values: Option<Map<String, Value>>,
+    /// The record after the change, `None` for a delete.
+    pub new_values: Option<Map<String, Value>>,
+    pub changed_date: DateTime<Utc>,
+}
+
+/// A field whose value differs between two versions of a record. Fields
+/// a version does not have are `null`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl AuditEntry {
+    /// The fields the change touched. Every field of an inserted or deleted
+    /// record counts as changed.
+    pub fn changes(&self) -> Vec<FieldChange> {
+        diff_values(self.old_values.as_ref(), self.new_values.as_ref())
+    }
+
+    /// The version of the record the change left, or for a delete, the one
+    /// it removed.
+    pub fn values(&self) -> Option<&Map<String, Value>> {
+        match self.action {
+            AuditAction::Delete => self.old_values.as_ref(),
+            _ => self.new_values.as_ref(),
+        }
+    }
+
+    fn new_from_row(row: &Row) -> Result<AuditEntry, JobSearchError> {
+        let entry = AuditEntry {
+            id: row.get(0)?,
+            table: row.get(1)?,
+            record_id: row.get(2)?,
+            action: row.get(3)?,
+            old_values: values(row, 4)?,
+            new_values: values(row, 5)?,
+            changed_date: timestamp::get_required(row, 6)?,
+        };
+
+        Ok(entry)
+    }
+}
+
+// The JSON object the triggers wrote into a column
+fn values(row: &Row, idx: usize) -> Result<Option<Map<String, Value>>, JobSearchError> {
+    let text: Option<String> = row.get(idx)?;
+
+    text.map(|text| {
+        serde_json::from_str(&text)
+            .map_err(|err| JobSearchError::SQLError(FromSqlError::Other(err.into())))
+    })
+    .transpose()
+}
+
+fn diff_values(
+    old: Option<&Map<String, Value>>,
+    new: Option<&Map<String, Value>>,
+) -> Vec<FieldChange> {
+    let empty = Map::new();
+    let old = old.unwrap_or(&empty);
+    let new = new.unwrap_or(&empty);
+
+    let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old = old.get(field).cloned().unwrap_or(Value::Null);
+            let new = new.get(field).cloned().unwrap_or(Value::Null);
+
+            if old == new {
+                None
+            } else {
+                Some(FieldChange {
+                    field: field.clone(),
+                    old,
+                    new,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Every change to the record of `table` with the given id, oldest first.
+pub(crate) fn get_history(
+    conn: &Connection,
+    table: &str,
+    record_id: i32,
+) -> Result<Vec<AuditEntry>, JobSearchError> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE table_name = (?1) AND record_id = (?2) ORDER BY id",
+        SELECT_ENTRIES
+    ))?;
+
+    let entries_iter = stmt.query_map(params![table, record_id], |row| {
+        Ok(AuditEntry::new_from_row(row))
+    })?;
+
+    let mut entries = Vec::new();
+    for entry in entries_iter {
+        entries.push(entry??);
+    }
+
+    Ok(entries)
+}
+
+pub(crate) fn get_entry(conn: &Connection, id: i32) -> Result<AuditEntry, JobSearchError> {
+    conn.query_row(
+        &format!("{} WHERE id = (?1)", SELECT_ENTRIES),
+        params![id],
+        |row| Ok(AuditEntry::new_from_row(row)),
+    )
+    .map_err(|err| not_found(err, "audit entry", id))?
+}
+
+// The audit entry with the id, which has to be about the record
+fn get_record_entry(
+    conn: &Connection,
+    table: &str,
+    record_id: i32,
+    id: i32,
+) -> Result<AuditEntry, JobSearchError> {
+    let entry = get_entry(conn, id)?;
+
+    if entry.table != table || entry.record_id != record_id {
+        return Err(JobSearchError::InvalidInput(format!(
+            "audit entry {} is not about {} {}",
+            id, table, record_id
+        )));
+    }
+
+    Ok(entry)
+}
+
+/// The fields that differ between the versions of a record left by the
+/// audit entries `from` and `to`. Both have to be about that record.
+pub(crate) fn diff(
+    conn: &Connection,
+    table: &str,
+    record_id: i32,
+    from: i32,
+    to: i32,
+) -> Result<Vec<FieldChange>, JobSearchError> {
+    let from = get_record_entry(conn, table, record_id, from)?;
+    let to = get_record_entry(conn, table, record_id, to)?;
+
+    Ok(diff_values(from.values(), to.values()))
+}
+
+// JSON as the triggers wrote it, back into a column
+fn column_value(value: &Value) -> types::Value {
+    match value {
+        Value::Null => types::Value::Null,
+        Value::Bool(value) => types::Value::Integer(*value as i64),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => types::Value::Integer(number),
+            None => types::Value::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => types::Value::Text(text.clone()),
+        value => types::Value::Text(value.to_string()),
+    }
+}
+
+/// Writes the record back as the audit entry `entry_id` left it, adding it
+/// again with its old id if it was deleted since. The restore is a change
+/// of its own, so it is logged like any other.
+pub(crate) fn restore(
+    conn: &Connection,
+    table: &str,
+    record_id: i32,
+    entry_id: i32,
+) -> Result<(), JobSearchError> {
+    let entry = get_record_entry(conn, table, record_id, entry_id)?;
+    let values = entry.values().ok_or_else(|| {
+        JobSearchError::InvalidInput(format!("audit entry {} has no values", entry_id))
+    })?;
+
+    let columns: Vec<String> = values
+        .keys()
+        .map(|column| format!("\"{}\"", column.replace('"', "\"\"")))
+        .collect();
+    let placeholders: Vec<String> = (1..=columns.len())
+        .map(|index| format!("?{}", index))
+        .collect();
+    let updates: Vec<String> = columns
+        .iter()
+        .map(|column| format!("{0} = excluded.{0}", column))
+        .collect();
+    let values: Vec<types::Value> = values.values().map(column_value).collect();
+
+    let _ = conn.execute(
+        &format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT (id) DO UPDATE SET {}",
+            table,
+            columns.join(", "),
+            placeholders.join(", "),
+            updates.join(", ")
+        ),
+        &values,
+    )?;
+
+    Ok(())
+}
+
+impl<'a, T: Entity> Table<'a, T> {
+    /// Every insert, update, hide and delete of the record, oldest first.
+    /// Changes made before the audit log was added to the database are not
+    /// known.
+    pub fn history(&self, id: i32) -> Result<Vec<AuditEntry>, JobSearchError> {
+        get_history(self.conn(), T::TABLE, id)
+    }
+
+    /// What changed in the record between the version left by the audit
+    /// entry `from` and the one left by `to`. Fails with `InvalidInput` if
+    /// either entry is about another record.
+    pub fn diff(&self, id: i32, from: i32, to: i32) -> Result<Vec<FieldChange>, JobSearchError> {
+        diff(self.conn(), T::TABLE, id, from, to)
+    }
+
+    /// Puts the record back the way the audit entry `entry` left it, such
+    /// as before an accidental edit, or as it was when it was deleted.
+    /// Fails with `InvalidInput` if the entry is about another record.
+    pub fn restore(&self, id: i32, entry: i32) -> Result<T, JobSearchError> {
+        restore(self.conn(), T::TABLE, id, entry)?;
+
+        self.get_by_id(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::company::Company;
+    use crate::create_in_memory_db;
+    use crate::job_posting::JobPosting;
+
+    fn actions(entries: &[AuditEntry]) -> Vec<AuditAction> {
+        entries.iter().map(|entry| entry.action).collect()
+    }
+
+    #[test]
+    fn test_history() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let id = company.id.unwrap();
+
+        company.website = Some("https://mozilla.org".to_string());
+        company.update_db(&conn).unwrap();
+        // Writing the record back unchanged is not a change
+        company.update_db(&conn).unwrap();
+        company.archive(&conn).unwrap();
+        company.unarchive(&conn).unwrap();
+        company.delete_from_db(&conn).unwrap();
+
+        let history = get_history(&conn, Company::TABLE, id).unwrap();
+
+        assert_eq!(
+            actions(&history),
+            vec![
+                AuditAction::Insert,
+                AuditAction::Update,
+                AuditAction::Hide,
+                AuditAction::Unhide,
+                AuditAction::Delete
+            ]
+        );
+        assert_eq!(history[0].old_values, None);
+        assert_eq!(history[0].new_values.as_ref().unwrap()["name"], "Mozilla");
+        assert!(!history[0]
+            .new_values
+            .as_ref()
+            .unwrap()
+            .contains_key("last_updated"));
+        assert_eq!(
+            history[1].changes(),
+            vec![FieldChange {
+                field: "website".to_string(),
+                old: Value::Null,
+                new: json!("https://mozilla.org"),
+            }]
+        );
+        assert_eq!(history[4].new_values, None);
+        assert!(history[0].changed_date <= history[4].changed_date);
+    }
+
+    #[test]
+    fn test_diff() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut job_posting = JobPosting::new("https://example.com/sre".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+        let id = job_posting.id.unwrap();
+
+        job_posting.description = Some("Remote".to_string());
+        job_posting.update_db(&conn).unwrap();
+        job_posting.description = Some("On site".to_string());
+        job_posting.update_db(&conn).unwrap();
+
+        let history = get_history(&conn, JobPosting::TABLE, id).unwrap();
+        let (first, last) = (history[0].id, history[2].id);
+
+        // When the description changed
+        let changed: Vec<i32> = history
+            .iter()
+            .filter(|entry| {
+                entry
+                    .changes()
+                    .iter()
+                    .any(|change| change.field == "description")
+            })
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(changed, vec![history[1].id, last]);
+
+        assert_eq!(
+            diff(&conn, JobPosting::TABLE, id, first, last).unwrap(),
+            vec![FieldChange {
+                field: "description".to_string(),
+                old: Value::Null,
+                new: json!("On site"),
+            }]
+        );
+        assert!(diff(&conn, JobPosting::TABLE, id, last, last)
+            .unwrap()
+            .is_empty());
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let other = get_history(&conn, Company::TABLE, company.id.unwrap()).unwrap()[0].id;
+
+        assert!(matches!(
+            diff(&conn, JobPosting::TABLE, id, first, other),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            diff(&conn, JobPosting::TABLE, id, first, 999),
+            Err(JobSearchError::NotFound { .. })
+        ));
+
+        // A delete is compared as the version it removed
+        job_posting.delete_from_db(&conn).unwrap();
+        let deleted = get_history(&conn, JobPosting::TABLE, id).unwrap()[3].id;
+        assert!(diff(&conn, JobPosting::TABLE, id, last, deleted)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_restore() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new(
+            "Mozilla".to_string(),
+            None,
+            Some("https://mozilla.org".to_string()),
+            None,
+        );
+        company.add_to_db(&conn).unwrap();
+        let id = company.id.unwrap();
+
+        company.website = None;
+        company.phone = Some("555-0100".to_string());
+        company.update_db(&conn).unwrap();
+
+        let history = get_history(&conn, Company::TABLE, id).unwrap();
+        restore(&conn, Company::TABLE, id, history[0].id).unwrap();
+
+        let restored = Company::get_by_id(&conn, id).unwrap();
+        assert_eq!(restored.name, "Mozilla");
+        assert_eq!(restored.website.as_deref(), Some("https://mozilla.org"));
+        assert_eq!(restored.phone, None);
+
+        let history = get_history(&conn, Company::TABLE, id).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].action, AuditAction::Update);
+
+        // A deleted record comes back with its id
+        let mut company = restored;
+        company.delete_from_db(&conn).unwrap();
+        let deleted = get_history(&conn, Company::TABLE, id).unwrap()[3].id;
+        restore(&conn, Company::TABLE, id, deleted).unwrap();
+
+        let restored = Company::get_by_id(&conn, id).unwrap();
+        assert_eq!(restored.website.as_deref(), Some("https://mozilla.org"));
+        assert_eq!(
+            get_history(&conn, Company::TABLE, id).unwrap()[4].action,
+            AuditAction::Insert
+        );
+
+        let mut job_posting = JobPosting::new("https://example.com/sre".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+        let other = get_history(&conn, JobPosting::TABLE, job_posting.id.unwrap()).unwrap()[0].id;
+        assert!(matches!(
+            restore(&conn, Company::TABLE, id, other),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,1681 @@
+mod output;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use job_search::{
+    timestamp, Application, ApplicationStatus, Company, Contact, ContactType, CsvImportOptions,
+    CsvTable, Cursor, Database, Entity, Filter, FollowUp, IcsImportOptions, IcsOptions, Interview,
+    InterviewType, JobPosting, JobSearchError, Note, NoteTarget, Order, Recurrence, Reminder,
+    SortBy, Table, Tag, TagMatch, Taggable, Visibility,
+};
+
+use crate::output::{
+    print_agenda, print_csv_import, print_follow_up, print_ics_import, print_list, print_one,
+    print_page, print_snapshot_import,
+};
+
+/// Keeps track of the companies, postings, contacts, applications and
+/// interviews of a job search.
+#[derive(Debug, Parser)]
+#[command(name = "job-search", version)]
+struct Cli {
+    /// The database file. It is created if it does not exist yet.
+    #[arg(
+        long,
+        global = true,
+        env = "JOB_SEARCH_DB",
+        default_value = "job_search.db"
+    )]
+    db: PathBuf,
+
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Add, list and edit companies
+    #[command(subcommand)]
+    Company(CompanyCommand),
+
+    /// Add and list job postings
+    #[command(subcommand)]
+    Posting(PostingCommand),
+
+    /// Add and list contacts
+    #[command(subcommand)]
+    Contact(ContactCommand),
+
+    /// Record an application to a company
+    Apply {
+        company_id: i32,
+        #[arg(long)]
+        posting: Option<i32>,
+        #[arg(long)]
+        contact: Option<i32>,
+        /// When the application was sent, as YYYY-MM-DD. Defaults to today.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Schedule and list interviews
+    #[command(subcommand)]
+    Interview(InterviewCommand),
+
+    /// List applications by status, show the history of one, or move it to
+    /// a new status
+    Status {
+        application_id: Option<i32>,
+        status: Option<ApplicationStatus>,
+        /// When moving the application, add a reminder to follow up on it
+        /// DAYS from today
+        #[arg(long, value_name = "DAYS", env = "JOB_SEARCH_FOLLOW_UP_DAYS")]
+        follow_up: Option<i64>,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+
+    /// Add, list and complete reminders
+    #[command(subcommand)]
+    Remind(RemindCommand),
+
+    /// Show the reminders that are overdue, due today and due later this
+    /// week
+    Agenda,
+
+    /// Write down, read and search notes about a record
+    #[command(subcommand)]
+    Note(NoteCommand),
+
+    /// Tag companies, postings, contacts and applications, and manage the
+    /// tags
+    #[command(subcommand)]
+    Tag(TagCommand),
+
+    /// Move interviews to and from calendar apps
+    #[command(subcommand)]
+    Ics(IcsCommand),
+
+    /// Export a table to a spreadsheet, or import one
+    #[command(subcommand)]
+    Csv(CsvCommand),
+
+    /// Copy the whole database to a JSON file, or merge one into it
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+
+    /// Search the text of companies, postings, contacts, interviews and
+    /// notes, e.g. "kubernetes AND remote"
+    Search {
+        query: String,
+        /// Show at most this many results
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+    },
+
+    /// Show every change to a record, or what changed between two versions
+    /// of it
+    History {
+        record: RecordKind,
+        id: i32,
+        /// Compare the record as the changes FROM and TO left it, by their
+        /// ids in the history
+        #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+        diff: Option<Vec<i32>>,
+        /// Put the record back the way the change ENTRY left it, showing
+        /// what that changes
+        #[arg(long, value_name = "ENTRY", conflicts_with = "diff")]
+        restore: Option<i32>,
+    },
+}
+
+/// The kinds of records with a history.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RecordKind {
+    Company,
+    Posting,
+    Contact,
+    Application,
+    Interview,
+    Reminder,
+    Note,
+    Tag,
+    ContactType,
+    InterviewType,
+}
+
+/// Lists leave out hidden records unless asked for them.
+#[derive(Debug, Args)]
+struct ListArgs {
+    /// Include hidden records
+    #[arg(long)]
+    all: bool,
+    /// Only show hidden records
+    #[arg(long, conflicts_with = "all")]
+    hidden: bool,
+    /// Only show records containing TEXT
+    #[arg(long)]
+    text: Option<String>,
+    /// Only show records tagged with TAG. Given more than once, records
+    /// with any of the tags are shown.
+    #[arg(long)]
+    tag: Vec<String>,
+    /// Only show records with every --tag
+    #[arg(long, requires = "tag")]
+    all_tags: bool,
+    /// Show at most this many records
+    #[arg(long)]
+    limit: Option<u32>,
+    /// Continue a list that was cut off by --limit
+    #[arg(long, value_name = "CURSOR")]
+    after: Option<Cursor>,
+}
+
+impl ListArgs {
+    fn visibility(&self) -> Visibility {
+        if self.all {
+            Visibility::IncludeHidden
+        } else if self.hidden {
+            Visibility::OnlyHidden
+        } else {
+            Visibility::Visible
+        }
+    }
+
+    fn filter(&self) -> Filter {
+        let mut filter = Filter::new().visibility(self.visibility());
+
+        if let Some(ref text) = self.text {
+            filter = filter.text(text);
+        }
+        if !self.tag.is_empty() {
+            let tag_match = if self.all_tags {
+                TagMatch::All
+            } else {
+                TagMatch::Any
+            };
+            filter = filter.tagged(&self.tag, tag_match);
+        }
+        if let Some(limit) = self.limit {
+            filter = filter.limit(limit);
+        }
+        if let Some(cursor) = self.after {
+            filter = filter.after(cursor);
+        }
+
+        filter
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum CompanyCommand {
+    Add {
+        name: String,
+        #[arg(long)]
+        address: Option<String>,
+        #[arg(long)]
+        website: Option<String>,
+        #[arg(long)]
+        phone: Option<String>,
+    },
+    List(ListArgs),
+    /// Change the given fields of a company
+    Edit {
+        id: i32,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        address: Option<String>,
+        #[arg(long)]
+        website: Option<String>,
+        #[arg(long)]
+        phone: Option<String>,
+    },
+    Hide {
+        id: i32,
+        /// Also hide the company's applications, interviews and postings
+        #[arg(long)]
+        cascade: bool,
+    },
+    Unhide {
+        id: i32,
+        /// Also show the applications, interviews and postings that hiding
+        /// it with --cascade hid
+        #[arg(long)]
+        cascade: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PostingCommand {
+    Add {
+        link: String,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    List(ListArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum ContactCommand {
+    Add {
+        name: String,
+        /// The kind of contact, e.g. recruiter. Created if it does not exist.
+        #[arg(long = "type")]
+        contact_type: String,
+        #[arg(long)]
+        email: Option<String>,
+        #[arg(long)]
+        phone: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    List(ListArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum InterviewCommand {
+    Schedule {
+        company_id: i32,
+        /// When the interview takes place, as "YYYY-MM-DD HH:MM" local time
+        #[arg(long, value_parser = parse_local_time)]
+        at: DateTime<Utc>,
+        /// The kind of interview, e.g. phone. Created if it does not exist.
+        #[arg(long = "type")]
+        interview_type: String,
+        #[arg(long)]
+        application: Option<i32>,
+        #[arg(long)]
+        contact: Option<i32>,
+        #[arg(long)]
+        posting: Option<i32>,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    List {
+        /// Only show interviews in the next DAYS days that are not cancelled
+        #[arg(long, value_name = "DAYS")]
+        upcoming: Option<i64>,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Mark an interview as having taken place
+    Complete {
+        id: i32,
+        /// Add a reminder to follow up on the interview DAYS from today
+        #[arg(long, value_name = "DAYS", env = "JOB_SEARCH_FOLLOW_UP_DAYS")]
+        follow_up: Option<i64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RemindCommand {
+    Add {
+        title: String,
+        /// When the reminder is due, as YYYY-MM-DD
+        #[arg(long)]
+        due: NaiveDate,
+        /// Add the next reminder when this one is done: daily, weekly or
+        /// monthly
+        #[arg(long, value_name = "RECURRENCE")]
+        every: Option<Recurrence>,
+        #[arg(long)]
+        application: Option<i32>,
+        #[arg(long)]
+        contact: Option<i32>,
+        #[arg(long)]
+        company: Option<i32>,
+        #[arg(long)]
+        interview: Option<i32>,
+    },
+    List(ListArgs),
+    /// Mark a reminder as done. Recurring reminders are followed by the
+    /// next one.
+    Done {
+        id: i32,
+    },
+}
+
+/// The one record a note is about.
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct NoteTargetArgs {
+    #[arg(long)]
+    company: Option<i32>,
+    #[arg(long)]
+    posting: Option<i32>,
+    #[arg(long)]
+    contact: Option<i32>,
+    #[arg(long)]
+    application: Option<i32>,
+    #[arg(long)]
+    interview: Option<i32>,
+}
+
+impl NoteTargetArgs {
+    fn target(&self) -> NoteTarget {
+        self.company
+            .map(NoteTarget::Company)
+            .or(self.posting.map(NoteTarget::JobPosting))
+            .or(self.contact.map(NoteTarget::Contact))
+            .or(self.application.map(NoteTarget::Application))
+            .or(self.interview.map(NoteTarget::Interview))
+            // clap requires one of them
+            .expect("a note needs a record to be about")
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum NoteCommand {
+    /// Add a note, keeping the earlier ones
+    Add {
+        body: String,
+        #[command(flatten)]
+        about: NoteTargetArgs,
+    },
+    /// Show the notes about a record, oldest first
+    List {
+        #[command(flatten)]
+        about: NoteTargetArgs,
+    },
+    /// Change the text of a note
+    Edit { id: i32, body: String },
+    /// Find notes by their text or by the name of what they are about
+    Search { query: String },
+}
+
+/// The one record to tag.
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct TagTargetArgs {
+    #[arg(long)]
+    company: Option<i32>,
+    #[arg(long)]
+    posting: Option<i32>,
+    #[arg(long)]
+    contact: Option<i32>,
+    #[arg(long)]
+    application: Option<i32>,
+}
+
+/// The kinds of records that have tags, with the id of one.
+#[derive(Debug, Clone, Copy)]
+enum TagTarget {
+    Company(i32),
+    JobPosting(i32),
+    Contact(i32),
+    Application(i32),
+}
+
+impl TagTargetArgs {
+    fn target(&self) -> TagTarget {
+        self.company
+            .map(TagTarget::Company)
+            .or(self.posting.map(TagTarget::JobPosting))
+            .or(self.contact.map(TagTarget::Contact))
+            .or(self.application.map(TagTarget::Application))
+            // clap requires one of them
+            .expect("a record to tag")
+    }
+}
+
+/// What `tag add`, `tag remove` and `tag show` do to the record.
+enum TagAction {
+    Add(String),
+    Remove(String),
+    Show,
+}
+
+#[derive(Debug, Subcommand)]
+enum TagCommand {
+    /// Tag a record, creating the tag if it is new
+    Add {
+        name: String,
+        #[command(flatten)]
+        record: TagTargetArgs,
+    },
+    /// Take a tag off a record
+    Remove {
+        name: String,
+        #[command(flatten)]
+        record: TagTargetArgs,
+    },
+    /// Show the tags on a record
+    Show {
+        #[command(flatten)]
+        record: TagTargetArgs,
+    },
+    /// List every tag with how many records carry it
+    List,
+    Rename {
+        name: String,
+        new_name: String,
+    },
+    /// Move every record tagged FROM over to INTO and delete FROM
+    Merge {
+        from: String,
+        into: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum IcsCommand {
+    /// Write the scheduled interviews as an .ics file
+    Export {
+        /// Where to write the file. Printed when left out.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Add a to-do to follow up on each open application, DAYS after
+        /// its last status change
+        #[arg(long, value_name = "DAYS")]
+        follow_ups: Option<i64>,
+    },
+    /// Add the events of an .ics file as interviews, matching them to
+    /// companies and contacts by name or email
+    Import {
+        file: PathBuf,
+        /// The interview type of events that do not name one. It is created
+        /// if it does not exist yet.
+        #[arg(long, value_name = "TYPE")]
+        default_type: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CsvCommand {
+    /// Write every record of a table as CSV, e.g. `csv export companies`
+    Export {
+        table: CsvTable,
+        /// Where to write the file. Printed when left out.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Add the rows of a CSV file to a table. Companies and postings that
+    /// already exist, by name or link, are updated.
+    Import {
+        table: CsvTable,
+        file: PathBuf,
+        /// Read a header of the file as a column of the table, e.g.
+        /// --map "Company Name=name"
+        #[arg(long = "map", value_name = "HEADER=COLUMN", value_parser = parse_mapping)]
+        columns: Vec<(String, String)>,
+        /// Check the file and show what would change, without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SnapshotCommand {
+    /// Write every record as JSON
+    Export {
+        /// Where to write the file. Printed when left out.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Merge an exported snapshot into the database. Records that exist in
+    /// both are updated if the snapshot's copy is newer.
+    Import { file: PathBuf },
+}
+
+fn parse_mapping(value: &str) -> Result<(String, String), String> {
+    match value.rsplit_once('=') {
+        Some((header, column)) => Ok((header.to_string(), column.to_string())),
+        None => Err("expected HEADER=COLUMN".to_string()),
+    }
+}
+
+fn parse_local_time(value: &str) -> Result<DateTime<Utc>, String> {
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .map_err(|err| format!("expected YYYY-MM-DD HH:MM: {}", err))?;
+
+    timestamp::from_local(naive, &Local).map_err(|err| err.to_string())
+}
+
+fn days(days: i64) -> Result<Duration, JobSearchError> {
+    Duration::try_days(days)
+        .ok_or_else(|| JobSearchError::InvalidInput(format!("{} days is out of range", days)))
+}
+
+fn follow_up(days: Option<i64>) -> Result<Option<FollowUp>, JobSearchError> {
+    days.map(|days| Ok(FollowUp::new(self::days(days)?)))
+        .transpose()
+}
+
+fn contact_type_id(db: &Database, name: &str) -> Result<i32, JobSearchError> {
+    if let Some(contact_type) = db.contact_types().get_by_name(name)? {
+        return Ok(contact_type.id.unwrap_or_default());
+    }
+
+    let mut contact_type = ContactType::new(name.to_string());
+    db.contact_types().add(&mut contact_type)?;
+
+    Ok(contact_type.id.unwrap_or_default())
+}
+
+fn interview_type_id(db: &Database, name: &str) -> Result<i32, JobSearchError> {
+    if let Some(interview_type) = db.interview_types().get_by_name(name)? {
+        return Ok(interview_type.id.unwrap_or_default());
+    }
+
+    let mut interview_type = InterviewType::new(name.to_string());
+    db.interview_types().add(&mut interview_type)?;
+
+    Ok(interview_type.id.unwrap_or_default())
+}
+
+fn run<W: Write>(
+    command: Command,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        Command::Company(command) => run_company(command, db, as_json, out),
+        Command::Posting(command) => run_posting(command, db, as_json, out),
+        Command::Contact(command) => run_contact(command, db, as_json, out),
+        Command::Apply {
+            company_id,
+            posting,
+            contact,
+            date,
+        } => {
+            let mut application = Application::new(company_id, posting, contact);
+            application.date_applied = date;
+            db.applications().add(&mut application)?;
+
+            Ok(print_one(out, &application, as_json)?)
+        }
+        Command::Interview(command) => run_interview(command, db, as_json, out),
+        Command::Status {
+            application_id: None,
+            list,
+            ..
+        } => Ok(print_page(
+            out,
+            &db.applications()
+                .list(&list.filter().sort_by(SortBy::Date, Order::Ascending))?,
+            as_json,
+        )?),
+        Command::Status {
+            application_id: Some(id),
+            status: None,
+            ..
+        } => Ok(print_list(
+            out,
+            &db.applications().status_history(id)?,
+            as_json,
+        )?),
+        Command::Status {
+            application_id: Some(id),
+            status: Some(status),
+            follow_up: days,
+            ..
+        } => {
+            let mut application = db.applications().get_by_id(id)?;
+
+            match follow_up(days)? {
+                Some(follow_up) => {
+                    let reminder = db.applications().change_status_with_follow_up(
+                        &mut application,
+                        status,
+                        &follow_up,
+                    )?;
+
+                    print_one(out, &application, as_json)?;
+                    print_follow_up(out, &reminder, as_json)?;
+                }
+                None => {
+                    db.applications().change_status(&mut application, status)?;
+
+                    print_one(out, &application, as_json)?;
+                }
+            }
+
+            Ok(())
+        }
+        Command::Remind(command) => run_remind(command, db, as_json, out),
+        Command::Agenda => {
+            let today = Local::now().date_naive();
+            let (due_today, later): (Vec<Reminder>, Vec<Reminder>) = db
+                .reminders()
+                .due_this_week()?
+                .into_iter()
+                .partition(|reminder| reminder.due_date == today);
+
+            Ok(print_agenda(
+                out,
+                &db.reminders().overdue()?,
+                &due_today,
+                &later,
+                as_json,
+            )?)
+        }
+        Command::Note(command) => run_note(command, db, as_json, out),
+        Command::Tag(command) => run_tag(command, db, as_json, out),
+        Command::Ics(command) => run_ics(command, db, as_json, out),
+        Command::Csv(command) => run_csv(command, db, as_json, out),
+        Command::Snapshot(command) => run_snapshot(command, db, as_json, out),
+        Command::Search { query, limit } => {
+            Ok(print_list(out, &db.search_limit(&query, limit)?, as_json)?)
+        }
+        Command::History {
+            record,
+            id,
+            diff,
+            restore,
+        } => {
+            let view = match (diff, restore) {
+                (Some(ids), _) => HistoryView::Diff(ids[0], ids[1]),
+                (None, Some(entry)) => HistoryView::Restore(entry),
+                (None, None) => HistoryView::All,
+            };
+
+            match record {
+                RecordKind::Company => run_history(db.companies(), id, view, as_json, out),
+                RecordKind::Posting => run_history(db.job_postings(), id, view, as_json, out),
+                RecordKind::Contact => run_history(db.contacts(), id, view, as_json, out),
+                RecordKind::Application => run_history(db.applications(), id, view, as_json, out),
+                RecordKind::Interview => run_history(db.interviews(), id, view, as_json, out),
+                RecordKind::Reminder => run_history(db.reminders(), id, view, as_json, out),
+                RecordKind::Note => run_history(db.notes(), id, view, as_json, out),
+                RecordKind::Tag => run_history(db.tags(), id, view, as_json, out),
+                RecordKind::ContactType => run_history(db.contact_types(), id, view, as_json, out),
+                RecordKind::InterviewType => {
+                    run_history(db.interview_types(), id, view, as_json, out)
+                }
+            }
+        }
+    }
+}
+
+/// What `history` shows of a record.
+enum HistoryView {
+    All,
+    Diff(i32, i32),
+    Restore(i32),
+}
+
+fn run_history<T: Entity, W: Write>(
+    table: Table<T>,
+    id: i32,
+    view: HistoryView,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match view {
+        HistoryView::All => print_list(out, &table.history(id)?, as_json)?,
+        HistoryView::Diff(from, to) => print_list(out, &table.diff(id, from, to)?, as_json)?,
+        HistoryView::Restore(entry) => {
+            // Shown as what changes from the latest version
+            let changes = match table.history(id)?.last() {
+                Some(latest) => table.diff(id, latest.id, entry)?,
+                None => Vec::new(),
+            };
+            table.restore(id, entry)?;
+
+            print_list(out, &changes, as_json)?
+        }
+    }
+
+    Ok(())
+}
+
+fn run_company<W: Write>(
+    command: CompanyCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        CompanyCommand::Add {
+            name,
+            address,
+            website,
+            phone,
+        } => {
+            let mut company = Company::new(name, address, website, phone);
+            db.companies().add(&mut company)?;
+
+            print_one(out, &company, as_json)?;
+        }
+        CompanyCommand::List(list) => {
+            print_page(out, &db.companies().list(&list.filter())?, as_json)?
+        }
+        CompanyCommand::Edit {
+            id,
+            name,
+            address,
+            website,
+            phone,
+        } => {
+            let mut company = db.companies().get_by_id(id)?;
+
+            if let Some(name) = name {
+                company.name = name;
+            }
+            if address.is_some() {
+                company.address = address;
+            }
+            if website.is_some() {
+                company.website = website;
+            }
+            if phone.is_some() {
+                company.phone = phone;
+            }
+
+            db.companies().update(&mut company)?;
+
+            print_one(out, &company, as_json)?;
+        }
+        CompanyCommand::Hide { id, cascade } => {
+            let mut company = db.companies().get_by_id(id)?;
+
+            if cascade {
+                db.companies().archive_with_related(&mut company)?;
+            } else {
+                db.companies().archive(&mut company)?;
+            }
+
+            print_one(out, &company, as_json)?;
+        }
+        CompanyCommand::Unhide { id, cascade } => {
+            let mut company = db.companies().get_by_id(id)?;
+
+            if cascade {
+                db.companies().unarchive_with_related(&mut company)?;
+            } else {
+                db.companies().unarchive(&mut company)?;
+            }
+
+            print_one(out, &company, as_json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_posting<W: Write>(
+    command: PostingCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        PostingCommand::Add { link, description } => {
+            let mut job_posting = JobPosting::new(link);
+            job_posting.description = description;
+            db.job_postings().add(&mut job_posting)?;
+
+            print_one(out, &job_posting, as_json)?;
+        }
+        PostingCommand::List(list) => {
+            print_page(out, &db.job_postings().list(&list.filter())?, as_json)?
+        }
+    }
+
+    Ok(())
+}
+
+fn run_contact<W: Write>(
+    command: ContactCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        ContactCommand::Add {
+            name,
+            contact_type,
+            email,
+            phone,
+            description,
+        } => {
+            let type_id = contact_type_id(db, &contact_type)?;
+
+            let mut contact = Contact::new(name, email, phone, description, type_id);
+            db.contacts().add(&mut contact)?;
+
+            print_one(out, &contact, as_json)?;
+        }
+        ContactCommand::List(list) => {
+            print_page(out, &db.contacts().list(&list.filter())?, as_json)?
+        }
+    }
+
+    Ok(())
+}
+
+fn run_interview<W: Write>(
+    command: InterviewCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        InterviewCommand::Schedule {
+            company_id,
+            at,
+            interview_type,
+            application,
+            contact,
+            posting,
+            description,
+        } => {
+            let type_id = interview_type_id(db, &interview_type)?;
+
+            let mut interview = Interview::new(type_id, company_id, Some(at));
+            interview.application_id = application;
+            interview.contact_id = contact;
+            interview.job_posting_id = posting;
+            interview.description = description;
+            db.interviews().add(&mut interview)?;
+
+            print_one(out, &interview, as_json)?;
+        }
+        InterviewCommand::List {
+            upcoming: None,
+            list,
+        } => print_page(
+            out,
+            &db.interviews()
+                .list(&list.filter().sort_by(SortBy::Date, Order::Ascending))?,
+            as_json,
+        )?,
+        InterviewCommand::List {
+            upcoming: Some(days),
+            ..
+        } => print_list(out, &db.interviews().get_upcoming(days)?, as_json)?,
+        InterviewCommand::Complete {
+            id,
+            follow_up: days,
+        } => {
+            let mut interview = db.interviews().get_by_id(id)?;
+
+            match follow_up(days)? {
+                Some(follow_up) => {
+                    let reminder = db
+                        .interviews()
+                        .complete_with_follow_up(&mut interview, &follow_up)?;
+
+                    print_one(out, &interview, as_json)?;
+                    print_follow_up(out, &reminder, as_json)?;
+                }
+                None => {
+                    db.interviews().complete(&mut interview)?;
+
+                    print_one(out, &interview, as_json)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_remind<W: Write>(
+    command: RemindCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        RemindCommand::Add {
+            title,
+            due,
+            every,
+            application,
+            contact,
+            company,
+            interview,
+        } => {
+            let mut reminder = Reminder::new(title, due);
+            reminder.recurrence = every;
+            reminder.application_id = application;
+            reminder.contact_id = contact;
+            reminder.company_id = company;
+            reminder.interview_id = interview;
+            db.reminders().add(&mut reminder)?;
+
+            print_one(out, &reminder, as_json)?;
+        }
+        RemindCommand::List(list) => print_page(
+            out,
+            &db.reminders()
+                .list(&list.filter().sort_by(SortBy::Date, Order::Ascending))?,
+            as_json,
+        )?,
+        RemindCommand::Done { id } => {
+            let mut reminder = db.reminders().get_by_id(id)?;
+
+            match db.reminders().complete(&mut reminder)? {
+                Some(next) => print_list(out, &[reminder, next], as_json)?,
+                None => print_one(out, &reminder, as_json)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_note<W: Write>(
+    command: NoteCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        NoteCommand::Add { body, about } => {
+            let mut note = Note::new(about.target(), body);
+            db.notes().add(&mut note)?;
+
+            print_one(out, &note, as_json)?;
+        }
+        NoteCommand::List { about } => {
+            print_list(out, &db.notes().get_by_target(about.target())?, as_json)?
+        }
+        NoteCommand::Edit { id, body } => {
+            let mut note = db.notes().get_by_id(id)?;
+            note.body = body;
+            db.notes().update(&mut note)?;
+
+            print_one(out, &note, as_json)?;
+        }
+        NoteCommand::Search { query } => print_list(out, &db.notes().search(&query)?, as_json)?,
+    }
+
+    Ok(())
+}
+
+fn run_tag<W: Write>(
+    command: TagCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        TagCommand::Add { name, record } => {
+            run_tag_target(db, record.target(), TagAction::Add(name), as_json, out)?
+        }
+        TagCommand::Remove { name, record } => {
+            run_tag_target(db, record.target(), TagAction::Remove(name), as_json, out)?
+        }
+        TagCommand::Show { record } => {
+            run_tag_target(db, record.target(), TagAction::Show, as_json, out)?
+        }
+        TagCommand::List => print_list(out, &db.tags().counts()?, as_json)?,
+        TagCommand::Rename { name, new_name } => {
+            let mut tag = tag_by_name(db, &name)?;
+            db.tags().rename(&mut tag, &new_name)?;
+
+            print_one(out, &tag, as_json)?;
+        }
+        TagCommand::Merge { from, into } => {
+            let mut from = tag_by_name(db, &from)?;
+            let into = tag_by_name(db, &into)?;
+            db.tags().merge(&mut from, &into)?;
+
+            print_one(out, &into, as_json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_tag_target<W: Write>(
+    db: &Database,
+    target: TagTarget,
+    action: TagAction,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match target {
+        TagTarget::Company(id) => run_tag_action(db.companies(), id, action, as_json, out),
+        TagTarget::JobPosting(id) => run_tag_action(db.job_postings(), id, action, as_json, out),
+        TagTarget::Contact(id) => run_tag_action(db.contacts(), id, action, as_json, out),
+        TagTarget::Application(id) => run_tag_action(db.applications(), id, action, as_json, out),
+    }
+}
+
+fn run_tag_action<T: Taggable, W: Write>(
+    table: Table<T>,
+    id: i32,
+    action: TagAction,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match action {
+        TagAction::Add(name) => print_one(out, &table.tag(id, &name)?, as_json)?,
+        TagAction::Remove(name) => {
+            if !table.untag(id, &name)? {
+                return Err(JobSearchError::InvalidInput(format!(
+                    "the record is not tagged {}",
+                    name
+                )));
+            }
+
+            print_list(out, &table.tags(id)?, as_json)?;
+        }
+        TagAction::Show => print_list(out, &table.tags(id)?, as_json)?,
+    }
+
+    Ok(())
+}
+
+fn tag_by_name(db: &Database, name: &str) -> Result<Tag, JobSearchError> {
+    db.tags()
+        .get_by_name(name)?
+        .ok_or_else(|| JobSearchError::InvalidInput(format!("no tag named {}", name)))
+}
+
+fn run_ics<W: Write>(
+    command: IcsCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        IcsCommand::Export { output, follow_ups } => {
+            let options = IcsOptions {
+                follow_up_after: follow_ups.map(days).transpose()?,
+            };
+
+            match output {
+                Some(path) => db.export_ics(&mut BufWriter::new(File::create(path)?), &options)?,
+                None => db.export_ics(out, &options)?,
+            }
+        }
+        IcsCommand::Import { file, default_type } => {
+            let options = IcsImportOptions {
+                default_interview_type_id: default_type
+                    .map(|name| interview_type_id(db, &name))
+                    .transpose()?,
+            };
+
+            let report = db.import_ics(&mut File::open(file)?, &options)?;
+            print_ics_import(out, &report, as_json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_csv<W: Write>(
+    command: CsvCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        CsvCommand::Export { table, output } => match output {
+            Some(path) => db.export_csv(table, &mut BufWriter::new(File::create(path)?))?,
+            None => db.export_csv(table, out)?,
+        },
+        CsvCommand::Import {
+            table,
+            file,
+            columns,
+            dry_run,
+        } => {
+            let options = CsvImportOptions {
+                columns: columns.into_iter().collect(),
+                dry_run,
+            };
+
+            let report = db.import_csv(table, &mut File::open(file)?, &options)?;
+            print_csv_import(out, &report, dry_run, as_json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_snapshot<W: Write>(
+    command: SnapshotCommand,
+    db: &Database,
+    as_json: bool,
+    out: &mut W,
+) -> Result<(), JobSearchError> {
+    match command {
+        SnapshotCommand::Export { output } => match output {
+            Some(path) => db.export_snapshot(&mut BufWriter::new(File::create(path)?))?,
+            None => db.export_snapshot(out)?,
+        },
+        SnapshotCommand::Import { file } => {
+            let report = db.import_snapshot(&mut File::open(file)?)?;
+            print_snapshot_import(out, &report, as_json)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = Database::open_or_create(&cli.db)
+        .and_then(|db| run(cli.command, &db, cli.json, &mut io::stdout().lock()));
+
+    if let Err(err) = result {
+        eprintln!("job-search: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn run_args(db: &Database, args: &[&str]) -> Result<String, JobSearchError> {
+        let cli = Cli::try_parse_from(["job-search"].iter().chain(args)).unwrap();
+
+        let mut out = Vec::new();
+        run(cli.command, db, cli.json, &mut out)?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_company_commands() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(
+            &db,
+            &["company", "edit", "1", "--website", "https://google.com"],
+        )
+        .unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+        run_args(&db, &["company", "hide", "1", "--cascade"]).unwrap();
+
+        let output = run_args(&db, &["company", "list", "--json"]).unwrap();
+        assert_eq!(output.trim(), "[]");
+
+        let output = run_args(&db, &["company", "list", "--hidden", "--json"]).unwrap();
+        let companies: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(companies[0]["name"], "google");
+        assert_eq!(companies[0]["website"], "https://google.com");
+        assert_eq!(companies[0]["hide"], true);
+        assert_eq!(db.applications().count().unwrap(), 0);
+
+        run_args(&db, &["company", "unhide", "1"]).unwrap();
+
+        assert_eq!(db.companies().count().unwrap(), 1);
+        assert_eq!(db.applications().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_application_pipeline() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["contact", "add", "Marcus", "--type", "recruiter"]).unwrap();
+        run_args(
+            &db,
+            &["apply", "1", "--contact", "1", "--date", "2020-05-01"],
+        )
+        .unwrap();
+        run_args(
+            &db,
+            &[
+                "interview",
+                "schedule",
+                "1",
+                "--at",
+                "2020-05-08 10:00",
+                "--type",
+                "phone",
+                "--application",
+                "1",
+            ],
+        )
+        .unwrap();
+        run_args(&db, &["status", "1", "interviewing"]).unwrap();
+
+        let output = run_args(&db, &["status", "1"]).unwrap();
+
+        assert!(output.starts_with("STATUS"));
+        assert!(output.contains("applied"));
+        assert!(output.contains("interviewing"));
+        assert_eq!(db.contact_types().count().unwrap(), 1);
+        assert_eq!(db.interviews().get_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let db = Database::open_in_memory().unwrap();
+
+        let result = run_args(&db, &["company", "edit", "42", "--name", "google"]);
+        assert!(matches!(result, Err(JobSearchError::NotFound { .. })));
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+
+        let result = run_args(&db, &["status", "1", "saved"]);
+        assert!(matches!(
+            result,
+            Err(JobSearchError::InvalidTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_list_pages() {
+        let db = Database::open_in_memory().unwrap();
+
+        for name in &["google", "mozilla", "gitlab"] {
+            run_args(&db, &["company", "add", name]).unwrap();
+        }
+
+        let output = run_args(&db, &["company", "list", "--limit", "2"]).unwrap();
+        assert!(output.ends_with("More: --after 2\n"));
+
+        let output = run_args(&db, &["company", "list", "--after", "2", "--json"]).unwrap();
+        let companies: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(companies.as_array().unwrap().len(), 1);
+        assert_eq!(companies[0]["name"], "gitlab");
+
+        let output = run_args(&db, &["company", "list", "--text", "G", "--json"]).unwrap();
+        let companies: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(companies.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_ics_export() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+        run_args(
+            &db,
+            &[
+                "interview",
+                "schedule",
+                "1",
+                "--at",
+                "2020-05-08 10:00",
+                "--type",
+                "phone",
+            ],
+        )
+        .unwrap();
+
+        let output = run_args(&db, &["ics", "export", "--follow-ups", "7"]).unwrap();
+
+        assert!(output.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(output.contains("SUMMARY:phone interview with google\r\n"));
+        assert!(output.contains("BEGIN:VTODO\r\n"));
+    }
+
+    #[test]
+    fn test_ics_import() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "mozilla"]).unwrap();
+
+        let path = std::env::temp_dir().join(format!("job-search-{}.ics", process::id()));
+        std::fs::write(
+            &path,
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:abc\r\nDTSTART:20200508T100000Z\r\n\
+             SUMMARY:Call with Mozilla\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let file = path.to_str().unwrap();
+
+        let output = run_args(&db, &["ics", "import", file]).unwrap();
+        assert!(output.starts_with("Created 0, updated 0, unchanged 0, skipped 1\n"));
+        assert!(output.contains("no interview type matches the event"));
+
+        let output = run_args(
+            &db,
+            &["ics", "import", file, "--default-type", "phone", "--json"],
+        )
+        .unwrap();
+        let report: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["created"], json!([1]));
+
+        let output = run_args(&db, &["ics", "import", file, "--json"]).unwrap();
+        let report: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["unchanged"], json!([1]));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_csv_export_and_import() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+
+        let output = run_args(&db, &["csv", "export", "applications"]).unwrap();
+        assert!(output.starts_with("id,company,job_posting,contact,date_applied,status,"));
+        assert!(output.contains("\n1,google,,,"));
+
+        let path = std::env::temp_dir().join(format!("job-search-{}.csv", process::id()));
+        std::fs::write(
+            &path,
+            "Employer,Site\ngoogle,https://google.com\nmozilla,\n",
+        )
+        .unwrap();
+        let file = path.to_str().unwrap();
+
+        let output = run_args(
+            &db,
+            &[
+                "csv",
+                "import",
+                "companies",
+                file,
+                "--map",
+                "Employer=name",
+                "--dry-run",
+            ],
+        )
+        .unwrap();
+        assert!(output.starts_with("Would create 1, update 1, fail 0\n"));
+        assert!(output.contains("Ignored columns: Site\n"));
+        assert_eq!(db.companies().count().unwrap(), 1);
+
+        let output = run_args(
+            &db,
+            &[
+                "csv",
+                "import",
+                "companies",
+                file,
+                "--map",
+                "Employer=name",
+                "--json",
+            ],
+        )
+        .unwrap();
+        let report: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["created"], json!([2]));
+        assert_eq!(report["updated"], json!([1]));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_export_and_import() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+
+        let path = std::env::temp_dir().join(format!("job-search-{}.json", process::id()));
+        let file = path.to_str().unwrap();
+        run_args(&db, &["snapshot", "export", "--output", file]).unwrap();
+
+        let other = Database::open_in_memory().unwrap();
+        let output = run_args(&other, &["snapshot", "import", file]).unwrap();
+        assert!(output.contains("\ncompanies        1        0        0\n"));
+
+        let output = run_args(&other, &["snapshot", "import", file, "--json"]).unwrap();
+        let report: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(report["applications"]["unchanged"], 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_search() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(
+            &db,
+            &[
+                "posting",
+                "add",
+                "https://example.com/sre",
+                "--description",
+                "Kubernetes on call, remote friendly",
+            ],
+        )
+        .unwrap();
+        run_args(&db, &["company", "add", "Kubernetes Inc"]).unwrap();
+
+        let output = run_args(&db, &["search", "kubernetes AND remote", "--json"]).unwrap();
+        let hits: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(hits.as_array().unwrap().len(), 1);
+        assert_eq!(hits[0]["kind"], "job_posting");
+        assert_eq!(hits[0]["id"], 1);
+
+        let result = run_args(&db, &["search", "AND"]);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_reminders() {
+        let db = Database::open_in_memory().unwrap();
+        let today = Local::now().date_naive();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+
+        // Too far out to be a date, whether or not it is a duration
+        for days in &["999999999999999", "999999999"] {
+            let result = run_args(&db, &["status", "1", "screening", "--follow-up", days]);
+            assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+        }
+        let result = run_args(&db, &["ics", "export", "--follow-ups", "999999999999999"]);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+
+        let output = run_args(&db, &["status", "1", "screening", "--follow-up", "0"]).unwrap();
+        assert!(output.contains(&format!(
+            "Reminder 1 added: Follow up with google, due {}\n",
+            today
+        )));
+
+        run_args(
+            &db,
+            &[
+                "remind",
+                "add",
+                "Check the job board",
+                "--due",
+                &(today - Duration::days(1)).to_string(),
+                "--every",
+                "daily",
+            ],
+        )
+        .unwrap();
+
+        let output = run_args(&db, &["agenda", "--json"]).unwrap();
+        let agenda: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(agenda["overdue"][0]["title"], "Check the job board");
+        assert_eq!(agenda["today"][0]["application_id"], 1);
+
+        let output = run_args(&db, &["remind", "done", "2", "--json"]).unwrap();
+        let reminders: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(reminders[0]["done"], true);
+        assert_eq!(
+            reminders[1]["due_date"],
+            (today + Duration::days(1)).to_string()
+        );
+
+        let output = run_args(&db, &["agenda"]).unwrap();
+        assert!(output.starts_with("Overdue:\nNothing\n\nToday:\nID"));
+
+        let result = run_args(&db, &["remind", "done", "2"]);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_interview_complete() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(
+            &db,
+            &[
+                "interview",
+                "schedule",
+                "1",
+                "--at",
+                "2020-05-08 10:00",
+                "--type",
+                "phone",
+            ],
+        )
+        .unwrap();
+
+        let output = run_args(
+            &db,
+            &["interview", "complete", "1", "--follow-up", "2", "--json"],
+        )
+        .unwrap();
+        let interview: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(interview["completed"], true);
+        assert_eq!(
+            db.reminders().get_all().unwrap()[0].title,
+            "Follow up on the phone interview with google"
+        );
+    }
+
+    #[test]
+    fn test_notes() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["apply", "1"]).unwrap();
+        run_args(
+            &db,
+            &["note", "add", "Phone screen with Ada", "--application", "1"],
+        )
+        .unwrap();
+        run_args(
+            &db,
+            &["note", "add", "They use Kubernetes", "--application", "1"],
+        )
+        .unwrap();
+        run_args(
+            &db,
+            &["note", "edit", "1", "Phone screen with Ada, went well"],
+        )
+        .unwrap();
+
+        let output = run_args(&db, &["note", "list", "--application", "1", "--json"]).unwrap();
+        let notes: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(notes[0]["body"], "Phone screen with Ada, went well");
+        assert_eq!(notes[1]["body"], "They use Kubernetes");
+
+        let output = run_args(&db, &["note", "search", "kubernetes"]).unwrap();
+        assert!(output.starts_with("ID"));
+        assert!(output.contains("application 1"));
+
+        assert!(Cli::try_parse_from(["job-search", "note", "add", "Hi"]).is_err());
+        assert!(Cli::try_parse_from([
+            "job-search",
+            "note",
+            "list",
+            "--company",
+            "1",
+            "--contact",
+            "1"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["company", "add", "google"]).unwrap();
+        run_args(&db, &["company", "add", "stripe"]).unwrap();
+        run_args(&db, &["tag", "add", "remote", "--company", "1"]).unwrap();
+        run_args(&db, &["tag", "add", "fin-tech", "--company", "2"]).unwrap();
+        run_args(&db, &["tag", "add", "Remote", "--company", "2"]).unwrap();
+
+        let output = run_args(
+            &db,
+            &[
+                "company",
+                "list",
+                "--tag",
+                "remote",
+                "--tag",
+                "fin-tech",
+                "--all-tags",
+                "--json",
+            ],
+        )
+        .unwrap();
+        let page: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(page.as_array().unwrap().len(), 1);
+        assert_eq!(page[0]["name"], "stripe");
+
+        run_args(&db, &["tag", "rename", "fin-tech", "fintech"]).unwrap();
+        run_args(&db, &["tag", "add", "remote-only", "--company", "2"]).unwrap();
+        run_args(&db, &["tag", "merge", "remote", "remote-only"]).unwrap();
+
+        let output = run_args(&db, &["tag", "list"]).unwrap();
+        assert!(output.starts_with("ID"));
+        assert!(output.contains("remote-only"));
+        assert!(!output.contains("remote "));
+
+        let output = run_args(&db, &["tag", "show", "--company", "2", "--json"]).unwrap();
+        let tags: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(tags[0]["name"], "fintech");
+        assert_eq!(tags[1]["name"], "remote-only");
+
+        let result = run_args(&db, &["tag", "remove", "fintech", "--company", "1"]);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+        assert!(Cli::try_parse_from(["job-search", "company", "list", "--all-tags"]).is_err());
+    }
+
+    #[test]
+    fn test_history() {
+        let db = Database::open_in_memory().unwrap();
+
+        run_args(&db, &["posting", "add", "https://example.com/sre"]).unwrap();
+        run_args(&db, &["posting", "add", "https://example.com/swe"]).unwrap();
+        let mut posting = db.job_postings().get_by_id(1).unwrap();
+        posting.description = Some("Remote".to_string());
+        db.job_postings().update(&mut posting).unwrap();
+
+        let output = run_args(&db, &["history", "posting", "1"]).unwrap();
+        assert!(output.starts_with("ID"));
+        assert!(output.contains("update  description\n"));
+
+        let output = run_args(
+            &db,
+            &["history", "posting", "1", "--diff", "1", "3", "--json"],
+        )
+        .unwrap();
+        let diff: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            diff,
+            json!([{ "field": "description", "old": null, "new": "Remote" }])
+        );
+
+        let result = run_args(&db, &["history", "posting", "1", "--diff", "1", "2"]);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+
+        let output = run_args(&db, &["history", "posting", "1", "--restore", "1"]).unwrap();
+        assert!(output.contains("description  Remote\n"), "{}", output);
+        assert_eq!(db.job_postings().get_by_id(1).unwrap().description, None);
+    }
+
+    #[test]
+    fn test_parse_local_time() {
+        assert!(parse_local_time("2020-05-08 10:00").is_ok());
+        assert!(parse_local_time("2020-05-08").is_err());
+        assert!(Cli::try_parse_from(["job-search", "status", "1", "hired"]).is_err());
+    }
+}
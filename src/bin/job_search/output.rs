@@ -0,0 +1,647 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use job_search::{
+    timestamp, Application, AuditEntry, Company, Contact, CsvImport, FieldChange, IcsImport,
+    ImportIssue, Interview, JobPosting, Note, NoteTarget, Page, Reminder, RowError, SearchHit,
+    SnapshotImport, StatusChange, Tag, TagCount,
+};
+
+/// How a record is shown by the CLI as a table row. As JSON, records are
+/// written in their serde form, the same the server sends.
+pub trait Render: Serialize {
+    fn headers() -> &'static [&'static str];
+    fn row(&self) -> Vec<String>;
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn id(value: Option<i32>) -> String {
+    value.map(|id| id.to_string()).unwrap_or_default()
+}
+
+// Tables show times in the local time zone, JSON in UTC
+fn date_time(value: &Option<DateTime<Utc>>) -> String {
+    value
+        .map(|date| timestamp::format_in(&date, &Local, "%Y-%m-%d %H:%M"))
+        .unwrap_or_default()
+}
+
+fn date(value: &Option<NaiveDate>) -> String {
+    value.map(|date| date.to_string()).unwrap_or_default()
+}
+
+/// Prints `records` as an aligned table, or as a JSON array.
+pub fn print_list<T: Render, W: Write>(
+    out: &mut W,
+    records: &[T],
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        serde_json::to_writer(&mut *out, records)?;
+
+        return writeln!(out);
+    }
+
+    let rows: Vec<Vec<String>> = records.iter().map(Render::row).collect();
+
+    write_table(out, T::headers(), &rows)
+}
+
+/// Prints a page of records like `print_list`. Tables end with how to get
+/// the next page, if there is one.
+pub fn print_page<T: Render, W: Write>(
+    out: &mut W,
+    page: &Page<T>,
+    as_json: bool,
+) -> io::Result<()> {
+    print_list(out, &page.items, as_json)?;
+
+    match page.next {
+        Some(cursor) if !as_json => writeln!(out, "More: --after {}", cursor),
+        _ => Ok(()),
+    }
+}
+
+/// Prints a single record, as a one row table or as a JSON object.
+pub fn print_one<T: Render, W: Write>(out: &mut W, record: &T, as_json: bool) -> io::Result<()> {
+    if as_json {
+        serde_json::to_writer(&mut *out, record)?;
+
+        return writeln!(out);
+    }
+
+    write_table(out, T::headers(), &[record.row()])
+}
+
+/// Prints what an .ics import did: how many interviews it added or updated,
+/// then the events it skipped or could not fully match.
+pub fn print_ics_import<W: Write>(
+    out: &mut W,
+    report: &IcsImport,
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        serde_json::to_writer(&mut *out, report)?;
+
+        return writeln!(out);
+    }
+
+    writeln!(
+        out,
+        "Created {}, updated {}, unchanged {}, skipped {}",
+        report.created.len(),
+        report.updated.len(),
+        report.unchanged.len(),
+        report.skipped.len()
+    )?;
+
+    for (title, issues) in &[
+        ("Skipped", &report.skipped),
+        ("Unmatched", &report.unmatched),
+    ] {
+        if !issues.is_empty() {
+            writeln!(out, "\n{}:", title)?;
+            print_list(out, issues, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints what a CSV import did, or would do in a dry run, then the rows that
+/// failed.
+pub fn print_csv_import<W: Write>(
+    out: &mut W,
+    report: &CsvImport,
+    dry_run: bool,
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        return writeln!(
+            out,
+            "{}",
+            json!({
+                "dry_run": dry_run,
+                "created": report.created,
+                "updated": report.updated,
+                "errors": report.errors,
+                "ignored_columns": report.ignored_columns,
+            })
+        );
+    }
+
+    let counts = (
+        report.created.len(),
+        report.updated.len(),
+        report.errors.len(),
+    );
+    if dry_run {
+        writeln!(
+            out,
+            "Would create {}, update {}, fail {}",
+            counts.0, counts.1, counts.2
+        )?;
+    } else {
+        writeln!(
+            out,
+            "Created {}, updated {}, failed {}",
+            counts.0, counts.1, counts.2
+        )?;
+    }
+
+    if !report.ignored_columns.is_empty() {
+        writeln!(
+            out,
+            "Ignored columns: {}",
+            report.ignored_columns.join(", ")
+        )?;
+    }
+
+    if !report.errors.is_empty() {
+        writeln!(out)?;
+        print_list(out, &report.errors, false)?;
+    }
+
+    Ok(())
+}
+
+/// Prints how many records of each table a snapshot import added, updated
+/// or left as they were.
+pub fn print_snapshot_import<W: Write>(
+    out: &mut W,
+    report: &SnapshotImport,
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        serde_json::to_writer(&mut *out, report)?;
+
+        return writeln!(out);
+    }
+
+    let rows: Vec<Vec<String>> = report
+        .tables()
+        .iter()
+        .map(|(table, counts)| {
+            vec![
+                table.to_string(),
+                counts.created.to_string(),
+                counts.updated.to_string(),
+                counts.unchanged.to_string(),
+            ]
+        })
+        .collect();
+
+    write_table(out, &["table", "created", "updated", "unchanged"], &rows)
+}
+
+/// Prints the reminders that are overdue, due today and due later this week,
+/// each under a heading, or as a JSON object of the three lists.
+pub fn print_agenda<W: Write>(
+    out: &mut W,
+    overdue: &[Reminder],
+    today: &[Reminder],
+    later: &[Reminder],
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        return writeln!(
+            out,
+            "{}",
+            json!({
+                "overdue": overdue,
+                "today": today,
+                "later_this_week": later,
+            })
+        );
+    }
+
+    for (index, (title, reminders)) in [
+        ("Overdue", overdue),
+        ("Today", today),
+        ("Later this week", later),
+    ]
+    .iter()
+    .enumerate()
+    {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}:", title)?;
+
+        if reminders.is_empty() {
+            writeln!(out, "Nothing")?;
+        } else {
+            print_list(out, reminders, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tells which reminder a change added to follow up on it. Only tables get
+/// the note, so JSON output stays a single value.
+pub fn print_follow_up<W: Write>(
+    out: &mut W,
+    reminder: &Reminder,
+    as_json: bool,
+) -> io::Result<()> {
+    if as_json {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "Reminder {} added: {}, due {}",
+        id(reminder.id),
+        reminder.title,
+        reminder.due_date
+    )
+}
+
+fn write_table<W: Write>(out: &mut W, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers: Vec<String> = headers.iter().map(|header| header.to_uppercase()).collect();
+    write_row(out, &widths, &headers)?;
+
+    for row in rows {
+        write_row(out, &widths, row)?;
+    }
+
+    Ok(())
+}
+
+fn write_row<W: Write>(out: &mut W, widths: &[usize], cells: &[String]) -> io::Result<()> {
+    let line: Vec<String> = widths
+        .iter()
+        .zip(cells)
+        .map(|(width, cell)| format!("{:width$}", cell, width = width))
+        .collect();
+
+    writeln!(out, "{}", line.join("  ").trim_end())
+}
+
+impl Render for Company {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name", "website", "phone", "address", "hidden"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            self.name.clone(),
+            text(&self.website),
+            text(&self.phone),
+            text(&self.address),
+            self.hide.to_string(),
+        ]
+    }
+}
+
+impl Render for JobPosting {
+    fn headers() -> &'static [&'static str] {
+        &["id", "link", "description", "hidden"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            self.link.clone(),
+            text(&self.description),
+            self.hide.to_string(),
+        ]
+    }
+}
+
+impl Render for Contact {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name", "type", "email", "phone", "hidden"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            self.name.clone(),
+            self.contact_type
+                .as_ref()
+                .map(|contact_type| contact_type.name.clone())
+                .unwrap_or_default(),
+            text(&self.email),
+            text(&self.phone),
+            self.hide.to_string(),
+        ]
+    }
+}
+
+impl Render for Application {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "id", "applied", "status", "company", "posting", "contact", "hidden",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            date(&self.date_applied),
+            self.status.to_string(),
+            id(self.company_id),
+            id(self.job_posting_id),
+            id(self.contact_id),
+            self.hide.to_string(),
+        ]
+    }
+}
+
+impl Render for Interview {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "id",
+            "date",
+            "type",
+            "company",
+            "contact",
+            "application",
+            "cancelled",
+            "completed",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            date_time(&self.date),
+            self.interview_type
+                .as_ref()
+                .map(|interview_type| interview_type.name.clone())
+                .unwrap_or_default(),
+            self.company
+                .as_ref()
+                .map(|company| company.name.clone())
+                .unwrap_or_default(),
+            self.contact
+                .as_ref()
+                .map(|contact| contact.name.clone())
+                .unwrap_or_default(),
+            id(self.application_id),
+            self.cancelled.to_string(),
+            self.completed.to_string(),
+        ]
+    }
+}
+
+impl Render for Reminder {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "id",
+            "due",
+            "title",
+            "every",
+            "done",
+            "application",
+            "contact",
+            "company",
+            "interview",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.id),
+            self.due_date.to_string(),
+            self.title.clone(),
+            self.recurrence
+                .map(|recurrence| recurrence.to_string())
+                .unwrap_or_default(),
+            self.done.to_string(),
+            id(self.application_id),
+            id(self.contact_id),
+            id(self.company_id),
+            id(self.interview_id),
+        ]
+    }
+}
+
+impl Render for Note {
+    fn headers() -> &'static [&'static str] {
+        &["id", "written", "about", "note"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let about = match self.target() {
+            Some(NoteTarget::Company(id)) => format!("company {}", id),
+            Some(NoteTarget::JobPosting(id)) => format!("posting {}", id),
+            Some(NoteTarget::Contact(id)) => format!("contact {}", id),
+            Some(NoteTarget::Application(id)) => format!("application {}", id),
+            Some(NoteTarget::Interview(id)) => format!("interview {}", id),
+            None => String::new(),
+        };
+
+        vec![
+            id(self.id),
+            date_time(&self.created_date),
+            about,
+            self.body.clone(),
+        ]
+    }
+}
+
+impl Render for Tag {
+    fn headers() -> &'static [&'static str] {
+        &["id", "name"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![id(self.id), self.name.clone()]
+    }
+}
+
+impl Render for TagCount {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "id",
+            "name",
+            "companies",
+            "postings",
+            "contacts",
+            "applications",
+            "total",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            id(self.tag.id),
+            self.tag.name.clone(),
+            self.companies.to_string(),
+            self.job_postings.to_string(),
+            self.contacts.to_string(),
+            self.applications.to_string(),
+            self.total().to_string(),
+        ]
+    }
+}
+
+impl Render for AuditEntry {
+    fn headers() -> &'static [&'static str] {
+        &["id", "changed", "action", "fields"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let fields: Vec<String> = self
+            .changes()
+            .into_iter()
+            .map(|change| change.field)
+            .collect();
+
+        vec![
+            self.id.to_string(),
+            date_time(&Some(self.changed_date)),
+            self.action.to_string(),
+            fields.join(", "),
+        ]
+    }
+}
+
+// Strings without their quotes and nothing for null
+fn value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl Render for FieldChange {
+    fn headers() -> &'static [&'static str] {
+        &["field", "from", "to"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.field.clone(), value(&self.old), value(&self.new)]
+    }
+}
+
+impl Render for StatusChange {
+    fn headers() -> &'static [&'static str] {
+        &["status", "changed"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.status.to_string(), date_time(&Some(self.changed_date))]
+    }
+}
+
+impl Render for SearchHit {
+    fn headers() -> &'static [&'static str] {
+        &["kind", "id", "title", "match"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.kind.to_string(),
+            self.id.to_string(),
+            self.title.clone(),
+            self.snippet.replace('\n', " "),
+        ]
+    }
+}
+
+impl Render for RowError {
+    fn headers() -> &'static [&'static str] {
+        &["line", "problem"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.line.to_string(), self.message.clone()]
+    }
+}
+
+impl Render for ImportIssue {
+    fn headers() -> &'static [&'static str] {
+        &["uid", "summary", "problem"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![text(&self.uid), text(&self.summary), self.message.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use job_search::ContactType;
+
+    use super::*;
+
+    fn render<T: Render>(records: &[T], as_json: bool) -> String {
+        let mut out = Vec::new();
+        print_list(&mut out, records, as_json).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_print_table() {
+        let mut company = Company::new(
+            "google".to_string(),
+            None,
+            Some("https://google.com".to_string()),
+            None,
+        );
+        company.id = Some(1);
+
+        let output = render(&[company], false);
+
+        assert_eq!(
+            output,
+            "ID  NAME    WEBSITE             PHONE  ADDRESS  HIDDEN\n\
+             1   google  https://google.com                  false\n"
+        );
+    }
+
+    #[test]
+    fn test_print_json() {
+        let job_posting = JobPosting::new("https://example.com/job".to_string());
+
+        let output: Value = serde_json::from_str(&render(&[job_posting], true)).unwrap();
+
+        assert_eq!(output[0]["link"], "https://example.com/job");
+        assert_eq!(output[0]["id"], Value::Null);
+    }
+
+    #[test]
+    fn test_print_json_matches_serde() {
+        let contact = || {
+            let mut contact = Contact::new("Marcus".to_string(), None, None, None, 1);
+            contact.contact_type = Some(ContactType::new("recruiter".to_string()));
+            contact
+        };
+        let mut interview = Interview::new(1, 1, Some(Utc::now()));
+        interview.company = Some(Company::new("google".to_string(), None, None, None));
+        interview.contact = Some(contact());
+
+        let output: Value = serde_json::from_str(&render(&[contact()], true)).unwrap();
+        assert_eq!(output, serde_json::to_value([contact()]).unwrap());
+        assert_eq!(output[0]["contact_type"]["name"], "recruiter");
+
+        let interviews = [interview];
+        let output: Value = serde_json::from_str(&render(&interviews, true)).unwrap();
+        assert_eq!(output, serde_json::to_value(&interviews).unwrap());
+        assert_eq!(output[0]["company"]["name"], "google");
+    }
+}
@@ -0,0 +1,740 @@
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use url::form_urlencoded;
+
+use job_search::{
+    Application, ApplicationStatus, Company, Contact, ContactType, Database, Entity, Filter,
+    Interview, InterviewType, JobPosting, JobSearchError, Note, Order, Reminder, SortBy, Tag,
+    TagMatch, Taggable,
+};
+
+/// What to send back for a request. Responses without a body are sent
+/// empty, the others as JSON.
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+impl Response {
+    pub fn empty(status: u16) -> Response {
+        Response { status, body: None }
+    }
+
+    fn json<T: Serialize>(status: u16, value: &T) -> Result<Response, JobSearchError> {
+        let body = serde_json::to_value(value)
+            .map_err(|err| JobSearchError::InvalidInput(err.to_string()))?;
+
+        Ok(Response {
+            status,
+            body: Some(body),
+        })
+    }
+
+    pub fn error(status: u16, message: &str) -> Response {
+        Response {
+            status,
+            body: Some(json!({ "error": message })),
+        }
+    }
+}
+
+impl From<JobSearchError> for Response {
+    fn from(error: JobSearchError) -> Response {
+        let status = match error {
+            JobSearchError::NotFound { .. } => 404,
+            JobSearchError::InvalidInput(_) => 400,
+            JobSearchError::Duplicate { .. }
+            | JobSearchError::ForeignKeyViolation(_)
+            | JobSearchError::InvalidTransition { .. } => 409,
+            _ => 500,
+        };
+
+        Response::error(status, &error.to_string())
+    }
+}
+
+struct Request<'a> {
+    method: &'a str,
+    /// The path after the name of the resource.
+    rest: &'a [&'a str],
+    query: &'a str,
+    body: &'a [u8],
+}
+
+/// Answers one request to the API.
+///
+/// Every resource (`companies`, `job_postings`, `contacts`, `applications`,
+/// `interviews`, `reminders`, `notes`, `tags`, `contact_types` and
+/// `interview_types`) has the same routes:
+///
+/// - `GET /companies` lists one page of records, see `filter`.
+/// - `POST /companies` adds the record in the body and returns it.
+/// - `GET /companies/{id}` returns one record.
+/// - `PUT /companies/{id}` replaces the record with the body. `PATCH` only
+///   changes the fields in the body.
+/// - `DELETE /companies/{id}` deletes the record.
+/// - `GET /companies/{id}/history` lists every change to the record, and
+///   `GET /companies/{id}/diff?from=1&to=2` what changed between the
+///   versions two of those changes left. `POST /companies/{id}/restore`
+///   with a body like `{"entry": 1}` puts the record back the way one of
+///   them left it.
+///
+/// Applications move to a new status with `PUT /applications/{id}/status`
+/// and a body like `{"status": "screening"}`. `GET` on the same path
+/// returns the status history.
+///
+/// Companies, job postings, contacts and applications have tags:
+/// `GET /companies/{id}/tags` lists them, `POST /companies/{id}/tags` with
+/// a body like `{"name": "fintech"}` adds one and
+/// `DELETE /companies/{id}/tags/{tag_id}` takes one off. `GET /tags/counts`
+/// returns how many records carry each tag, and `POST /tags/{id}/merge`
+/// with a body like `{"into": 2}` merges one tag into another.
+///
+/// Failures are sent as `{"error": "..."}`.
+pub fn handle(db: &Database, method: &str, url: &str, body: &[u8]) -> Response {
+    route(db, method, url, body).unwrap_or_else(Response::from)
+}
+
+fn route(db: &Database, method: &str, url: &str, body: &[u8]) -> Result<Response, JobSearchError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let (resource, rest) = match segments.split_first() {
+        Some((resource, rest)) => (*resource, rest),
+        None => return Ok(no_route()),
+    };
+
+    let request = Request {
+        method,
+        rest,
+        query,
+        body,
+    };
+
+    match resource {
+        "companies" => tagged::<Company>(db, &request),
+        "job_postings" => tagged::<JobPosting>(db, &request),
+        "contacts" => tagged::<Contact>(db, &request),
+        "contact_types" => records::<ContactType>(db, &request),
+        "interview_types" => records::<InterviewType>(db, &request),
+        "applications" => applications(db, &request),
+        "interviews" => records::<Interview>(db, &request),
+        "reminders" => records::<Reminder>(db, &request),
+        "notes" => records::<Note>(db, &request),
+        "tags" => tags(db, &request),
+        _ => Ok(no_route()),
+    }
+}
+
+fn no_route() -> Response {
+    Response::error(404, "no such endpoint")
+}
+
+fn records<T>(db: &Database, request: &Request) -> Result<Response, JobSearchError>
+where
+    T: Entity + Serialize + DeserializeOwned,
+{
+    let table = db.table::<T>();
+
+    match (request.method, request.rest) {
+        ("GET", []) => Response::json(200, &table.list(&filter(request.query)?)?),
+        ("POST", []) => {
+            let mut fields = body(request.body)?;
+            fields.remove("id");
+
+            let mut record: T = from_fields(fields)?;
+            table.add(&mut record)?;
+
+            // Read back for what the database filled in
+            let id = record.id().unwrap_or_default();
+            Response::json(201, &table.get_by_id(id)?)
+        }
+        ("GET", [id]) => Response::json(200, &table.get_by_id(parse("id", id)?)?),
+        (method @ ("PUT" | "PATCH"), [id]) => {
+            let id = parse("id", id)?;
+            let existing = table.get_by_id(id)?;
+
+            let mut fields = match (method, serde_json::to_value(&existing)) {
+                ("PATCH", Ok(Value::Object(fields))) => fields,
+                _ => Map::new(),
+            };
+            fields.extend(body(request.body)?);
+            fields.insert("id".to_string(), json!(id));
+
+            let mut record: T = from_fields(fields)?;
+            table.update(&mut record)?;
+
+            Response::json(200, &table.get_by_id(id)?)
+        }
+        ("DELETE", [id]) => {
+            let mut record = table.get_by_id(parse("id", id)?)?;
+            table.delete(&mut record)?;
+
+            Ok(Response::empty(204))
+        }
+        // The history is kept after a delete, so the record does not have
+        // to exist
+        ("GET", [id, "history"]) => Response::json(200, &table.history(parse("id", id)?)?),
+        ("GET", [id, "diff"]) => {
+            let (mut from, mut to) = (None, None);
+            for (name, value) in form_urlencoded::parse(request.query.as_bytes()) {
+                match name.as_ref() {
+                    "from" => from = Some(parse(&name, &value)?),
+                    "to" => to = Some(parse(&name, &value)?),
+                    _ => {
+                        return Err(JobSearchError::InvalidInput(format!(
+                            "unknown query parameter {:?}",
+                            name
+                        )))
+                    }
+                }
+            }
+
+            match (from, to) {
+                (Some(from), Some(to)) => {
+                    Response::json(200, &table.diff(parse("id", id)?, from, to)?)
+                }
+                _ => Err(JobSearchError::InvalidInput(
+                    "expected the ids of two changes, like ?from=1&to=2".to_string(),
+                )),
+            }
+        }
+        ("POST", [id, "restore"]) => {
+            let entry = match body(request.body)?.remove("entry") {
+                Some(Value::Number(entry)) => parse("entry", &entry.to_string())?,
+                _ => {
+                    return Err(JobSearchError::InvalidInput(
+                        "expected the id of a change, like {\"entry\": 1}".to_string(),
+                    ))
+                }
+            };
+
+            Response::json(200, &table.restore(parse("id", id)?, entry)?)
+        }
+        (_, []) | (_, [_]) | (_, [_, "history"]) | (_, [_, "diff"]) | (_, [_, "restore"]) => {
+            Ok(Response::error(405, "method not allowed"))
+        }
+        _ => Ok(no_route()),
+    }
+}
+
+fn applications(db: &Database, request: &Request) -> Result<Response, JobSearchError> {
+    let table = db.applications();
+
+    match (request.method, request.rest) {
+        ("GET", [id, "status"]) => {
+            let application = table.get_by_id(parse("id", id)?)?;
+            let id = application.id.unwrap_or_default();
+
+            Response::json(200, &table.status_history(id)?)
+        }
+        ("PUT", [id, "status"]) => {
+            let mut application = table.get_by_id(parse("id", id)?)?;
+            let status = match body(request.body)?.remove("status") {
+                Some(Value::String(status)) => parse::<ApplicationStatus>("status", &status)?,
+                _ => {
+                    return Err(JobSearchError::InvalidInput(
+                        "expected a status, like {\"status\": \"screening\"}".to_string(),
+                    ))
+                }
+            };
+
+            table.change_status(&mut application, status)?;
+
+            Response::json(200, &application)
+        }
+        (_, [_, "status"]) => Ok(Response::error(405, "method not allowed")),
+        _ => tagged::<Application>(db, request),
+    }
+}
+
+fn tagged<T>(db: &Database, request: &Request) -> Result<Response, JobSearchError>
+where
+    T: Taggable + Serialize + DeserializeOwned,
+{
+    let table = db.table::<T>();
+
+    match (request.method, request.rest) {
+        ("GET", [id, "tags"]) => {
+            let record = table.get_by_id(parse("id", id)?)?;
+
+            Response::json(200, &table.tags(record.id().unwrap_or_default())?)
+        }
+        ("POST", [id, "tags"]) => {
+            let record = table.get_by_id(parse("id", id)?)?;
+            let name = match body(request.body)?.remove("name") {
+                Some(Value::String(name)) => name,
+                _ => {
+                    return Err(JobSearchError::InvalidInput(
+                        "expected a tag name, like {\"name\": \"fintech\"}".to_string(),
+                    ))
+                }
+            };
+
+            Response::json(200, &table.tag(record.id().unwrap_or_default(), &name)?)
+        }
+        ("DELETE", [id, "tags", tag_id]) => {
+            let id = parse("id", id)?;
+            let tag = db.tags().get_by_id(parse("tag id", tag_id)?)?;
+
+            if table.untag(id, &tag.name)? {
+                Ok(Response::empty(204))
+            } else {
+                Ok(Response::error(404, "the record does not have that tag"))
+            }
+        }
+        (_, [_, "tags"]) | (_, [_, "tags", _]) => Ok(Response::error(405, "method not allowed")),
+        _ => records::<T>(db, request),
+    }
+}
+
+fn tags(db: &Database, request: &Request) -> Result<Response, JobSearchError> {
+    let table = db.tags();
+
+    match (request.method, request.rest) {
+        ("GET", ["counts"]) => Response::json(200, &table.counts()?),
+        ("POST", [id, "merge"]) => {
+            let mut from = table.get_by_id(parse("id", id)?)?;
+            let into = match body(request.body)?.remove("into") {
+                Some(Value::Number(into)) => parse("into", &into.to_string())?,
+                _ => {
+                    return Err(JobSearchError::InvalidInput(
+                        "expected the id of a tag, like {\"into\": 2}".to_string(),
+                    ))
+                }
+            };
+            let into = table.get_by_id(into)?;
+
+            table.merge(&mut from, &into)?;
+
+            Response::json(200, &into)
+        }
+        (_, ["counts"]) | (_, [_, "merge"]) => Ok(Response::error(405, "method not allowed")),
+        _ => records::<Tag>(db, request),
+    }
+}
+
+fn body(body: &[u8]) -> Result<Map<String, Value>, JobSearchError> {
+    match serde_json::from_slice(body) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(JobSearchError::InvalidInput(
+            "the body has to be a JSON object".to_string(),
+        )),
+        Err(err) => Err(JobSearchError::InvalidInput(format!(
+            "the body is not valid JSON: {}",
+            err
+        ))),
+    }
+}
+
+fn from_fields<T: DeserializeOwned>(fields: Map<String, Value>) -> Result<T, JobSearchError> {
+    serde_json::from_value(Value::Object(fields))
+        .map_err(|err| JobSearchError::InvalidInput(err.to_string()))
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, JobSearchError> {
+    value
+        .parse()
+        .map_err(|_| JobSearchError::InvalidInput(format!("invalid {}: {:?}", name, value)))
+}
+
+// Enums are named in query strings the way they are in JSON
+fn parse_name<T: DeserializeOwned>(name: &str, value: &str) -> Result<T, JobSearchError> {
+    serde_json::from_value(json!(value))
+        .map_err(|_| JobSearchError::InvalidInput(format!("invalid {}: {:?}", name, value)))
+}
+
+/// Reads a `Filter` from a query string such as
+/// `?status=applied&sort=date&order=descending&limit=20`.
+///
+/// The parameters are `text`, `company`, `status`, `contact_type`, `from`
+/// and `to` (as YYYY-MM-DD), `tag`, which can be given more than once,
+/// `tag_match` (`any` or `all` of the tags), `visibility` (`visible`,
+/// `include_hidden` or `only_hidden`), `sort` (`id`, `date`, `name` or `last_updated`), `order`
+/// (`ascending` or `descending`), `limit`, `offset` and `after`, which
+/// takes the `next` cursor of the previous page.
+fn filter(query: &str) -> Result<Filter, JobSearchError> {
+    let mut filter = Filter::new();
+    let mut sort_by = SortBy::default();
+    let mut order = Order::default();
+    let mut tags = Vec::new();
+    let mut tag_match = TagMatch::default();
+
+    for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+        filter = match name.as_ref() {
+            "text" => filter.text(&value),
+            "company" => filter.company(parse(&name, &value)?),
+            "status" => filter.status(parse(&name, &value)?),
+            "contact_type" => filter.contact_type(parse(&name, &value)?),
+            "from" => filter.from(parse(&name, &value)?),
+            "to" => filter.to(parse(&name, &value)?),
+            "tag" => {
+                tags.push(value.into_owned());
+                filter
+            }
+            "tag_match" => {
+                tag_match = parse_name(&name, &value)?;
+                filter
+            }
+            "visibility" => filter.visibility(parse_name(&name, &value)?),
+            "sort" => {
+                sort_by = parse_name(&name, &value)?;
+                filter
+            }
+            "order" => {
+                order = parse_name(&name, &value)?;
+                filter
+            }
+            "limit" => filter.limit(parse(&name, &value)?),
+            "offset" => filter.offset(parse(&name, &value)?),
+            "after" => filter.after(parse(&name, &value)?),
+            _ => {
+                return Err(JobSearchError::InvalidInput(format!(
+                    "unknown query parameter {:?}",
+                    name
+                )))
+            }
+        };
+    }
+
+    if !tags.is_empty() {
+        filter = filter.tagged(&tags, tag_match);
+    }
+
+    Ok(filter.sort_by(sort_by, order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The in-process client: requests go straight to `handle`
+    fn call(db: &Database, method: &str, url: &str, body: Option<Value>) -> (u16, Value) {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let response = handle(db, method, url, body.as_bytes());
+
+        (response.status, response.body.unwrap_or(Value::Null))
+    }
+
+    fn add_company(db: &Database, name: &str) -> i64 {
+        let (status, company) = call(db, "POST", "/companies", Some(json!({ "name": name })));
+        assert_eq!(status, 201, "{}", company);
+
+        company["id"].as_i64().unwrap()
+    }
+
+    #[test]
+    fn test_crud() {
+        let db = Database::open_in_memory().unwrap();
+
+        let (status, company) = call(
+            &db,
+            "POST",
+            "/companies",
+            Some(json!({ "id": 7, "name": "Google", "website": "https://google.com" })),
+        );
+        assert_eq!(status, 201);
+        assert_eq!(company["id"], 1);
+        assert_eq!(company["name"], "Google");
+        assert!(company["created_date"].is_string());
+
+        let (status, company) = call(&db, "GET", "/companies/1", None);
+        assert_eq!(status, 200);
+        assert_eq!(company["website"], "https://google.com");
+
+        let (status, company) = call(
+            &db,
+            "PATCH",
+            "/companies/1",
+            Some(json!({ "phone": "555-0100" })),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(company["phone"], "555-0100");
+        assert_eq!(company["website"], "https://google.com");
+
+        let (status, company) = call(
+            &db,
+            "PUT",
+            "/companies/1",
+            Some(json!({ "name": "Alphabet" })),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(company["name"], "Alphabet");
+        assert_eq!(company["website"], Value::Null);
+
+        let (status, body) = call(&db, "DELETE", "/companies/1", None);
+        assert_eq!((status, body), (204, Value::Null));
+
+        let (status, body) = call(&db, "GET", "/companies/1", None);
+        assert_eq!(status, 404);
+        assert_eq!(body["error"], "no company with id 1");
+    }
+
+    #[test]
+    fn test_list_pages() {
+        let db = Database::open_in_memory().unwrap();
+
+        for name in &["Amazon", "Google", "Microsoft", "Netflix"] {
+            add_company(&db, name);
+        }
+
+        let (status, page) = call(
+            &db,
+            "GET",
+            "/companies?sort=name&order=descending&limit=3",
+            None,
+        );
+        assert_eq!(status, 200);
+        let names: Vec<&str> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|company| company["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["Netflix", "Microsoft", "Google"]);
+
+        let url = format!(
+            "/companies?sort=name&order=descending&limit=3&after={}",
+            page["next"]
+        );
+        let (_, page) = call(&db, "GET", &url, None);
+        assert_eq!(page["items"][0]["name"], "Amazon");
+        assert_eq!(page["next"], Value::Null);
+
+        let (_, page) = call(&db, "GET", "/companies?text=SOFT", None);
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_list_invalid_filter() {
+        let db = Database::open_in_memory().unwrap();
+
+        for url in &[
+            "/companies?colour=red",
+            "/companies?limit=-1",
+            "/companies?sort=size",
+            "/companies?contact_type=1",
+        ] {
+            let (status, body) = call(&db, "GET", url, None);
+            assert_eq!(status, 400, "{}: {}", url, body);
+        }
+    }
+
+    #[test]
+    fn test_applications() {
+        let db = Database::open_in_memory().unwrap();
+        let google = add_company(&db, "Google");
+        let amazon = add_company(&db, "Amazon");
+
+        for company in &[google, amazon] {
+            let (status, _) = call(
+                &db,
+                "POST",
+                "/applications",
+                Some(json!({ "company_id": company, "date_applied": "2020-05-01" })),
+            );
+            assert_eq!(status, 201);
+        }
+
+        let (status, application) = call(
+            &db,
+            "PUT",
+            "/applications/2/status",
+            Some(json!({ "status": "screening" })),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(application["status"], "screening");
+
+        let (_, history) = call(&db, "GET", "/applications/2/status", None);
+        assert_eq!(history.as_array().unwrap().len(), 2);
+
+        let url = format!("/applications?status=screening&company={}", amazon);
+        let (_, page) = call(&db, "GET", &url, None);
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["id"], 2);
+
+        let (status, _) = call(
+            &db,
+            "PUT",
+            "/applications/2/status",
+            Some(json!({ "status": "applied" })),
+        );
+        assert_eq!(status, 409);
+    }
+
+    #[test]
+    fn test_interviews() {
+        let db = Database::open_in_memory().unwrap();
+        let company = add_company(&db, "Google");
+
+        let (status, interview_type) = call(
+            &db,
+            "POST",
+            "/interview_types",
+            Some(json!({ "name": "phone" })),
+        );
+        assert_eq!(status, 201);
+
+        let (status, interview) = call(
+            &db,
+            "POST",
+            "/interviews",
+            Some(json!({
+                "interview_type_id": interview_type["id"],
+                "company_id": company,
+                "date": "2020-05-08T10:00:00Z",
+            })),
+        );
+        assert_eq!(status, 201, "{}", interview);
+        assert_eq!(interview["company"]["name"], "Google");
+
+        let (_, page) = call(
+            &db,
+            "GET",
+            "/interviews?from=2020-05-08&to=2020-05-08",
+            None,
+        );
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tags() {
+        let db = Database::open_in_memory().unwrap();
+        let google = add_company(&db, "Google");
+        let stripe = add_company(&db, "Stripe");
+
+        for (company, tag) in &[
+            (google, "remote-only"),
+            (stripe, "fintech"),
+            (stripe, "Remote-Only"),
+        ] {
+            let url = format!("/companies/{}/tags", company);
+            let (status, body) = call(&db, "POST", &url, Some(json!({ "name": tag })));
+            assert_eq!(status, 200, "{}", body);
+        }
+
+        let (_, tags) = call(&db, "GET", &format!("/companies/{}/tags", stripe), None);
+        assert_eq!(tags[0]["name"], "fintech");
+        assert_eq!(tags[1]["name"], "remote-only");
+
+        let (_, page) = call(
+            &db,
+            "GET",
+            "/companies?tag=fintech&tag=remote-only&tag_match=all",
+            None,
+        );
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["name"], "Stripe");
+
+        let (status, into) = call(&db, "POST", "/tags/2/merge", Some(json!({ "into": 1 })));
+        assert_eq!(status, 200);
+        assert_eq!(into["name"], "remote-only");
+
+        let (_, counts) = call(&db, "GET", "/tags/counts", None);
+        assert_eq!(counts.as_array().unwrap().len(), 1);
+        assert_eq!(counts[0]["companies"], 2);
+
+        let url = format!("/companies/{}/tags/1", google);
+        let (status, _) = call(&db, "DELETE", &url, None);
+        assert_eq!(status, 204);
+        let (status, _) = call(&db, "DELETE", &url, None);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_history() {
+        let db = Database::open_in_memory().unwrap();
+        let google = add_company(&db, "Google");
+
+        let url = format!("/companies/{}", google);
+        call(
+            &db,
+            "PATCH",
+            &url,
+            Some(json!({ "website": "https://google.com" })),
+        );
+        call(&db, "DELETE", &url, None);
+
+        let (status, history) = call(&db, "GET", &format!("{}/history", url), None);
+        assert_eq!(status, 200);
+        let actions: Vec<&str> = history
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, vec!["insert", "update", "delete"]);
+
+        let diff_url = format!(
+            "{}/diff?from={}&to={}",
+            url, history[0]["id"], history[1]["id"]
+        );
+        let (status, diff) = call(&db, "GET", &diff_url, None);
+        assert_eq!(status, 200);
+        assert_eq!(
+            diff,
+            json!([{ "field": "website", "old": null, "new": "https://google.com" }])
+        );
+
+        let (status, _) = call(&db, "GET", &format!("{}/diff?from=1", url), None);
+        assert_eq!(status, 400);
+        let (status, _) = call(&db, "GET", "/contacts/1/diff?from=1&to=2", None);
+        assert_eq!(status, 400);
+
+        // Undoing the delete brings back the record as it was
+        let restore_url = format!("{}/restore", url);
+        let (status, company) = call(
+            &db,
+            "POST",
+            &restore_url,
+            Some(json!({ "entry": history[2]["id"] })),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(company["website"], "https://google.com");
+        let (status, company) = call(
+            &db,
+            "POST",
+            &restore_url,
+            Some(json!({ "entry": history[0]["id"] })),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(company["website"], Value::Null);
+        let (status, _) = call(&db, "POST", &restore_url, Some(json!({})));
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_errors() {
+        let db = Database::open_in_memory().unwrap();
+        add_company(&db, "Google");
+
+        let test_cases = vec![
+            ("POST", "/companies", Some(json!({ "name": "Google" })), 409),
+            ("POST", "/companies", Some(json!({ "website": "x" })), 400),
+            ("POST", "/companies", Some(json!(["Google"])), 400),
+            ("POST", "/companies", None, 400),
+            (
+                "POST",
+                "/applications",
+                Some(json!({ "company_id": 99 })),
+                409,
+            ),
+            ("GET", "/companies/google", None, 400),
+            ("GET", "/contacts/1", None, 404),
+            ("GET", "/salaries", None, 404),
+            ("GET", "/companies/1/jobs", None, 404),
+            ("POST", "/companies/1", None, 405),
+            ("DELETE", "/companies", None, 405),
+        ];
+
+        for (method, url, body, expected) in test_cases {
+            let (status, body) = call(&db, method, url, body);
+            assert_eq!(status, expected, "{} {}: {}", method, url, body);
+            assert!(body["error"].is_string(), "{} {}", method, url);
+        }
+    }
+}
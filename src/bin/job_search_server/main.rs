@@ -0,0 +1,370 @@
+mod api;
+
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+use tiny_http::{Header, Method, Request, Server};
+
+use job_search::Database;
+
+/// Serves the records of a job search as a JSON API over HTTP, for browser
+/// extensions and dashboards.
+#[derive(Debug, Parser)]
+#[command(name = "job-search-server", version)]
+struct Cli {
+    /// The database file. It is created if it does not exist yet.
+    #[arg(long, env = "JOB_SEARCH_DB", default_value = "job_search.db")]
+    db: PathBuf,
+
+    /// The address to listen on. Only this machine can connect to the
+    /// default one.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: String,
+
+    /// Let web pages from ORIGIN call the API, e.g. http://localhost:3000
+    #[arg(long, value_name = "ORIGIN")]
+    allow_origin: Option<String>,
+
+    /// Also answer requests addressed to HOST, such as the name of this
+    /// machine when binding to every address. Can be given more than once.
+    #[arg(long, value_name = "HOST")]
+    allow_host: Vec<String>,
+}
+
+/// Bodies larger than this are refused.
+const BODY_LIMIT: u64 = 1024 * 1024;
+
+// The name in a Host header, without the port
+fn host_name(host: &str) -> &str {
+    match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => host.split(':').next().unwrap_or(host),
+    }
+}
+
+/// The host names requests may be addressed to. Anything else is refused,
+/// so a page on another site can not reach the API by pointing its own
+/// name at this machine.
+struct Hosts {
+    names: Vec<String>,
+}
+
+impl Hosts {
+    fn new(bind: &str, allow_host: &[String]) -> Hosts {
+        let names = ["localhost", "127.0.0.1", "::1", host_name(bind)]
+            .iter()
+            .map(|name| name.to_string())
+            .chain(allow_host.iter().map(|host| host_name(host).to_string()))
+            .map(|name| name.to_lowercase())
+            .collect();
+
+        Hosts { names }
+    }
+
+    fn allows(&self, host: &str) -> bool {
+        let name = host_name(host.trim()).to_lowercase();
+
+        self.names.contains(&name)
+    }
+}
+
+fn request_header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+// Browsers send forms and other simple requests to any site without asking
+// first, but never with a JSON body. Requiring one for every change keeps
+// other sites from making them.
+fn refuse(request: &Request, hosts: &Hosts) -> Option<api::Response> {
+    match request_header(request, "Host") {
+        Some(host) if hosts.allows(host) => {}
+        _ => return Some(api::Response::error(403, "unknown host")),
+    }
+
+    let changes = matches!(request.method(), Method::Post | Method::Put | Method::Patch);
+    let json = request_header(request, "Content-Type").is_some_and(|content_type| {
+        let media_type = content_type.split(';').next().unwrap_or_default();
+
+        media_type.trim().eq_ignore_ascii_case("application/json")
+    });
+
+    if changes && !json {
+        return Some(api::Response::error(
+            415,
+            "the body has to be sent as application/json",
+        ));
+    }
+
+    None
+}
+
+// tiny_http takes line breaks in values as they are, which would start a
+// new header
+fn header(name: &str, value: &str) -> Result<Header, String> {
+    let invalid = || format!("{:?} is not a valid {} header", value, name);
+
+    if value.chars().any(char::is_control) {
+        return Err(invalid());
+    }
+
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).map_err(|_| invalid())
+}
+
+/// The headers added to responses, built once at startup so a bad
+/// `--allow-origin` is reported before serving anything.
+struct Headers {
+    json: Header,
+    cors: Vec<Header>,
+}
+
+impl Headers {
+    fn new(allow_origin: Option<&str>) -> Result<Headers, String> {
+        let cors = match allow_origin {
+            Some(origin) => vec![
+                header("Access-Control-Allow-Origin", origin)?,
+                header(
+                    "Access-Control-Allow-Methods",
+                    "GET, POST, PUT, PATCH, DELETE",
+                )?,
+                header("Access-Control-Allow-Headers", "Content-Type")?,
+            ],
+            None => Vec::new(),
+        };
+
+        Ok(Headers {
+            json: header("Content-Type", "application/json")?,
+            cors,
+        })
+    }
+}
+
+/// Answers one request. Requests are handled one at a time, as the
+/// database connection can not be shared between threads.
+fn serve(db: &Database, mut request: Request, headers: &Headers, hosts: &Hosts) -> io::Result<()> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(BODY_LIMIT + 1)
+        .read_to_end(&mut body)?;
+
+    let response = if let Some(response) = refuse(&request, hosts) {
+        response
+    } else if body.len() as u64 > BODY_LIMIT {
+        api::Response::error(413, "the body is too large")
+    } else if *request.method() == Method::Options {
+        // Browsers ask before sending anything but simple requests
+        api::Response::empty(204)
+    } else {
+        api::handle(db, request.method().as_str(), request.url(), &body)
+    };
+
+    let data = match response.body {
+        Some(ref body) => body.to_string().into_bytes(),
+        None => Vec::new(),
+    };
+    let mut http = tiny_http::Response::from_data(data).with_status_code(response.status);
+
+    if response.body.is_some() {
+        http.add_header(headers.json.clone());
+    }
+
+    for header in &headers.cors {
+        http.add_header(header.clone());
+    }
+
+    request.respond(http)
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let headers = Headers::new(cli.allow_origin.as_deref()).unwrap_or_else(|err| {
+        eprintln!("job-search-server: --allow-origin: {}", err);
+        process::exit(1);
+    });
+
+    let db = Database::open_or_create(&cli.db).unwrap_or_else(|err| {
+        eprintln!("job-search-server: {}", err);
+        process::exit(1);
+    });
+
+    let server = Server::http(&cli.bind).unwrap_or_else(|err| {
+        eprintln!("job-search-server: can not listen on {}: {}", cli.bind, err);
+        process::exit(1);
+    });
+
+    let hosts = Hosts::new(&cli.bind, &cli.allow_host);
+
+    println!("Listening on http://{}", cli.bind);
+
+    for request in server.incoming_requests() {
+        if let Err(err) = serve(&db, request, &headers, &hosts) {
+            eprintln!("job-search-server: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+
+    use super::*;
+
+    fn send(server: &Server, db: &Database, request: &str, allow_origin: Option<&str>) -> String {
+        let headers = Headers::new(allow_origin).unwrap();
+        let hosts = Hosts::new("127.0.0.1:0", &["job-search.local".to_string()]);
+        let addr = server.server_addr().to_ip().unwrap();
+        let request = request.to_string();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+
+        serve(db, server.recv().unwrap(), &headers, &hosts).unwrap();
+
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn test_serve() {
+        let db = Database::open_in_memory().unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+
+        let body = r#"{"name": "Google"}"#;
+        let response = send(
+            &server,
+            &db,
+            &format!(
+                "POST /companies HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                 Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+            None,
+        );
+
+        assert!(response.starts_with("HTTP/1.1 201"), "{}", response);
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.contains(r#""name":"Google""#));
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+        assert_eq!(db.companies().count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_serve_preflight() {
+        let db = Database::open_in_memory().unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+
+        let response = send(
+            &server,
+            &db,
+            "OPTIONS /companies HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            Some("http://localhost:3000"),
+        );
+
+        assert!(response.starts_with("HTTP/1.1 204"), "{}", response);
+        assert!(response.contains("Access-Control-Allow-Origin: http://localhost:3000"));
+    }
+
+    #[test]
+    fn test_serve_text_plain() {
+        let db = Database::open_in_memory().unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+
+        // What an HTML form on another site can send without asking first
+        let body = r#"{"name": "Google"}"#;
+        let response = send(
+            &server,
+            &db,
+            &format!(
+                "POST /companies HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                 Content-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+            None,
+        );
+
+        assert!(response.starts_with("HTTP/1.1 415"), "{}", response);
+        assert_eq!(db.companies().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_serve_foreign_host() {
+        let db = Database::open_in_memory().unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+
+        let response = send(
+            &server,
+            &db,
+            "GET /companies HTTP/1.1\r\nHost: evil.example\r\nConnection: close\r\n\r\n",
+            None,
+        );
+        assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+
+        for host in [
+            "localhost:8080",
+            "127.0.0.1",
+            "[::1]:8080",
+            "Job-Search.local",
+        ] {
+            let response = send(
+                &server,
+                &db,
+                &format!(
+                    "GET /companies HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                    host
+                ),
+                None,
+            );
+            assert!(
+                response.starts_with("HTTP/1.1 200"),
+                "{}: {}",
+                host,
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn test_serve_body_limit() {
+        let db = Database::open_in_memory().unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+
+        let body = format!(r#"{{"name": "{}"}}"#, "a".repeat(BODY_LIMIT as usize));
+        let response = send(
+            &server,
+            &db,
+            &format!(
+                "POST /companies HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+                 Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+            None,
+        );
+
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        assert_eq!(db.companies().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_invalid_origin() {
+        assert!(Headers::new(Some("http://bücher.example")).is_err());
+        assert!(Headers::new(Some("http://localhost:3000\r\nX-Injected: 1")).is_err());
+        assert!(Headers::new(None).unwrap().cors.is_empty());
+    }
+}
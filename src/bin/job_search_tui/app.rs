@@ -0,0 +1,1198 @@
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use job_search::{
+    timestamp, Application, ApplicationStatus, Company, Contact, ContactType, Database, Interview,
+    InterviewType, JobPosting, JobSearchError, Visibility,
+};
+
+use crate::form::{Form, FormAction};
+
+/// An application on the board, with the names it is shown by.
+#[derive(Debug)]
+pub struct Card {
+    pub application: Application,
+    pub company: String,
+    pub posting: Option<String>,
+}
+
+/// The applications in one status.
+#[derive(Debug)]
+pub struct Column {
+    pub status: ApplicationStatus,
+    pub cards: Vec<Card>,
+}
+
+/// The parts of the detail view, in the order Tab moves through them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Company,
+    Posting,
+    Contacts,
+    Interviews,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 4] = [
+        Pane::Company,
+        Pane::Posting,
+        Pane::Contacts,
+        Pane::Interviews,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Pane::Company => "Company",
+            Pane::Posting => "Posting",
+            Pane::Contacts => "Contacts",
+            Pane::Interviews => "Interviews",
+        }
+    }
+
+    fn offset(self, by: usize) -> Pane {
+        let index = Pane::ALL.iter().position(|&pane| pane == self).unwrap_or(0);
+
+        Pane::ALL[(index + by) % Pane::ALL.len()]
+    }
+}
+
+/// One application with the records around it.
+#[derive(Debug)]
+pub struct Detail {
+    pub application: Application,
+    pub company: Option<Company>,
+    pub posting: Option<JobPosting>,
+    /// The contact on the application, then the ones met in its
+    /// interviews.
+    pub contacts: Vec<Contact>,
+    pub interviews: Vec<Interview>,
+    pub pane: Pane,
+    /// The selected contact or interview, in the panes listing them.
+    pub selected: usize,
+}
+
+impl Detail {
+    fn load(db: &Database, application_id: i32) -> Result<Detail, JobSearchError> {
+        let application = db.applications().get_by_id(application_id)?;
+        let company = match application.company_id {
+            Some(id) => Some(db.companies().get_by_id(id)?),
+            None => None,
+        };
+        let posting = match application.job_posting_id {
+            Some(id) => Some(db.job_postings().get_by_id(id)?),
+            None => None,
+        };
+        let interviews = db.interviews().get_by_application(application_id)?;
+
+        let contact_ids = application.contact_id.into_iter().chain(
+            interviews
+                .iter()
+                .filter_map(|interview| interview.contact_id),
+        );
+
+        let mut contacts: Vec<Contact> = Vec::new();
+        for id in contact_ids {
+            if contacts.iter().all(|contact| contact.id != Some(id)) {
+                let contact = db.contacts().get_by_id(id)?;
+
+                if !contact.hide {
+                    contacts.push(contact);
+                }
+            }
+        }
+
+        Ok(Detail {
+            application,
+            company,
+            posting,
+            contacts,
+            interviews,
+            pane: Pane::Company,
+            selected: 0,
+        })
+    }
+
+    fn application_id(&self) -> i32 {
+        self.application.id.unwrap_or_default()
+    }
+
+    /// How many records the focused pane lists.
+    fn len(&self) -> usize {
+        match self.pane {
+            Pane::Company | Pane::Posting => 1,
+            Pane::Contacts => self.contacts.len(),
+            Pane::Interviews => self.interviews.len(),
+        }
+    }
+}
+
+/// Where the record in a form goes when it is saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    NewApplication,
+    Application(i32),
+    Company(i32),
+    /// A new posting is linked to the application.
+    Posting {
+        application_id: i32,
+        posting_id: Option<i32>,
+    },
+    /// A new contact becomes the contact of the application.
+    Contact {
+        application_id: i32,
+        contact_id: Option<i32>,
+    },
+    Interview {
+        application_id: i32,
+        interview_id: Option<i32>,
+    },
+}
+
+/// Chooses the status to move an application to, out of the ones it may
+/// move to.
+#[derive(Debug)]
+pub struct Picker {
+    pub application_id: i32,
+    pub from: ApplicationStatus,
+    pub options: Vec<ApplicationStatus>,
+    pub selected: usize,
+}
+
+/// The state of the terminal UI: a board of applications by status, the
+/// detail of one application, and the form or status picker open on top.
+///
+/// Everything happens in `handle_key`, so the UI can be driven without a
+/// terminal.
+pub struct App<'a> {
+    db: &'a Database,
+    pub columns: Vec<Column>,
+    pub column: usize,
+    pub row: usize,
+    pub detail: Option<Detail>,
+    pub form: Option<Form<Target>>,
+    pub picker: Option<Picker>,
+    /// What the last key did, or why it failed.
+    pub message: Option<String>,
+    pub quit: bool,
+}
+
+fn date(value: Option<String>) -> Result<Option<NaiveDate>, JobSearchError> {
+    value
+        .map(|value| {
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| {
+                JobSearchError::InvalidInput(format!("{:?} is not a YYYY-MM-DD date", value))
+            })
+        })
+        .transpose()
+}
+
+// Times are typed in the local time zone
+fn local_time(
+    value: Option<String>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, JobSearchError> {
+    value
+        .map(|value| {
+            NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M")
+                .map_err(|_| {
+                    JobSearchError::InvalidInput(format!(
+                        "{:?} is not a YYYY-MM-DD HH:MM time",
+                        value
+                    ))
+                })
+                .and_then(|time| timestamp::from_local(time, &Local))
+        })
+        .transpose()
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+impl<'a> App<'a> {
+    pub fn new(db: &'a Database) -> Result<App<'a>, JobSearchError> {
+        let mut app = App {
+            db,
+            columns: Vec::new(),
+            column: 0,
+            row: 0,
+            detail: None,
+            form: None,
+            picker: None,
+            message: None,
+            quit: false,
+        };
+        app.reload()?;
+
+        Ok(app)
+    }
+
+    /// The card selected on the board.
+    pub fn selected(&self) -> Option<&Card> {
+        self.columns.get(self.column)?.cards.get(self.row)
+    }
+
+    fn selected_id(&self) -> Option<i32> {
+        self.selected().and_then(|card| card.application.id)
+    }
+
+    /// Reads the board and the open detail again. The selected application
+    /// stays selected while it is on the board.
+    pub fn reload(&mut self) -> Result<(), JobSearchError> {
+        let selected = self.selected_id();
+
+        // Hidden companies and postings still name the applications to them
+        let companies: HashMap<i32, String> = self
+            .db
+            .companies()
+            .get_all_with(Visibility::IncludeHidden)?
+            .into_iter()
+            .filter_map(|company| Some((company.id?, company.name)))
+            .collect();
+        let postings: HashMap<i32, String> = self
+            .db
+            .job_postings()
+            .get_all_with(Visibility::IncludeHidden)?
+            .into_iter()
+            .filter_map(|posting| Some((posting.id?, posting.link)))
+            .collect();
+
+        let mut columns: Vec<Column> = ApplicationStatus::ALL
+            .iter()
+            .map(|&status| Column {
+                status,
+                cards: Vec::new(),
+            })
+            .collect();
+
+        for application in self.db.applications().get_all()? {
+            let company = application
+                .company_id
+                .and_then(|id| companies.get(&id))
+                .cloned()
+                .unwrap_or_default();
+            let posting = application
+                .job_posting_id
+                .and_then(|id| postings.get(&id))
+                .cloned();
+
+            if let Some(column) = columns
+                .iter_mut()
+                .find(|column| column.status == application.status)
+            {
+                column.cards.push(Card {
+                    application,
+                    company,
+                    posting,
+                });
+            }
+        }
+
+        self.columns = columns;
+
+        for (column, cards) in self.columns.iter().enumerate() {
+            let found = cards
+                .cards
+                .iter()
+                .position(|card| card.application.id.is_some() && card.application.id == selected);
+
+            if let Some(row) = found {
+                self.column = column;
+                self.row = row;
+            }
+        }
+        self.clamp_row();
+
+        if let Some(detail) = self.detail.take() {
+            let mut reloaded = Detail::load(self.db, detail.application_id())?;
+            reloaded.pane = detail.pane;
+            reloaded.selected = detail.selected.min(reloaded.len().saturating_sub(1));
+
+            self.detail = Some(reloaded);
+        }
+
+        Ok(())
+    }
+
+    fn clamp_row(&mut self) {
+        let len = self
+            .columns
+            .get(self.column)
+            .map(|column| column.cards.len())
+            .unwrap_or_default();
+
+        self.row = self.row.min(len.saturating_sub(1));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        self.message = None;
+
+        let result = if self.form.is_some() {
+            self.form_key(key)
+        } else if self.picker.is_some() {
+            self.picker_key(key)
+        } else if self.detail.is_some() {
+            self.detail_key(key)
+        } else {
+            self.board_key(key)
+        };
+
+        if let Err(err) = result {
+            self.message = Some(err.to_string());
+        }
+    }
+
+    fn board_key(&mut self, key: KeyEvent) -> Result<(), JobSearchError> {
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.column = self.column.saturating_sub(1);
+                self.clamp_row();
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.column = (self.column + 1).min(self.columns.len().saturating_sub(1));
+                self.clamp_row();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.row = self.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.row += 1;
+                self.clamp_row();
+            }
+            KeyCode::Char('r') => self.reload()?,
+            KeyCode::Char('a') => {
+                self.form = Some(Form::new(
+                    "New application",
+                    Target::NewApplication,
+                    vec![
+                        ("Company", String::new()),
+                        ("Posting", String::new()),
+                        ("Applied", String::new()),
+                    ],
+                ));
+            }
+            _ => {
+                if let Some(id) = self.selected_id() {
+                    self.application_key(key, id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Keys for the selected application, on the board and in its detail
+    fn application_key(&mut self, key: KeyEvent, id: i32) -> Result<(), JobSearchError> {
+        match key.code {
+            KeyCode::Enter => self.detail = Some(Detail::load(self.db, id)?),
+            KeyCode::Char('m') => self.open_picker(id)?,
+            KeyCode::Char('e') => {
+                let application = self.db.applications().get_by_id(id)?;
+                let card = self
+                    .selected()
+                    .filter(|card| card.application.id == Some(id));
+
+                self.form = Some(Form::new(
+                    "Application",
+                    Target::Application(id),
+                    vec![
+                        (
+                            "Company",
+                            card.map(|card| card.company.clone()).unwrap_or_default(),
+                        ),
+                        (
+                            "Posting",
+                            card.and_then(|card| card.posting.clone())
+                                .unwrap_or_default(),
+                        ),
+                        (
+                            "Applied",
+                            application
+                                .date_applied
+                                .map(|date| date.to_string())
+                                .unwrap_or_default(),
+                        ),
+                    ],
+                ));
+            }
+            KeyCode::Char('x') => {
+                let mut application = self.db.applications().get_by_id(id)?;
+                self.db.applications().archive(&mut application)?;
+
+                self.message = Some("Hid the application".to_string());
+                self.reload()?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn detail_key(&mut self, key: KeyEvent) -> Result<(), JobSearchError> {
+        let detail = match self.detail.as_mut() {
+            Some(detail) => detail,
+            None => return Ok(()),
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.detail = None,
+            KeyCode::Tab | KeyCode::Right => {
+                detail.pane = detail.pane.offset(1);
+                detail.selected = 0;
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                detail.pane = detail.pane.offset(Pane::ALL.len() - 1);
+                detail.selected = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => detail.selected = detail.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                detail.selected = (detail.selected + 1).min(detail.len().saturating_sub(1));
+            }
+            KeyCode::Char('a') => self.form = self.add_form()?,
+            KeyCode::Char('e') => self.form = self.edit_form()?,
+            KeyCode::Char('x') => self.hide_focused()?,
+            KeyCode::Char('m') => {
+                let id = detail.application_id();
+                self.open_picker(id)?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    // A form for a new record in the focused pane
+    fn add_form(&mut self) -> Result<Option<Form<Target>>, JobSearchError> {
+        let detail = match self.detail {
+            Some(ref detail) => detail,
+            None => return Ok(None),
+        };
+        let application_id = detail.application_id();
+
+        let form = match detail.pane {
+            Pane::Company => {
+                self.message = Some("Press e to edit the company".to_string());
+                return Ok(None);
+            }
+            Pane::Posting => Form::new(
+                "New posting",
+                Target::Posting {
+                    application_id,
+                    posting_id: None,
+                },
+                vec![("Link", String::new()), ("Description", String::new())],
+            ),
+            Pane::Contacts => Form::new(
+                "New contact",
+                Target::Contact {
+                    application_id,
+                    contact_id: None,
+                },
+                vec![
+                    ("Name", String::new()),
+                    ("Type", String::new()),
+                    ("Email", String::new()),
+                    ("Phone", String::new()),
+                    ("Description", String::new()),
+                ],
+            ),
+            Pane::Interviews => Form::new(
+                "New interview",
+                Target::Interview {
+                    application_id,
+                    interview_id: None,
+                },
+                vec![
+                    ("Type", String::new()),
+                    ("Date", String::new()),
+                    ("Description", String::new()),
+                ],
+            ),
+        };
+
+        Ok(Some(form))
+    }
+
+    // A form for the record selected in the focused pane
+    fn edit_form(&mut self) -> Result<Option<Form<Target>>, JobSearchError> {
+        let detail = match self.detail {
+            Some(ref detail) => detail,
+            None => return Ok(None),
+        };
+        let application_id = detail.application_id();
+
+        let form = match detail.pane {
+            Pane::Company => detail.company.as_ref().map(|company| {
+                Form::new(
+                    "Company",
+                    Target::Company(company.id.unwrap_or_default()),
+                    vec![
+                        ("Name", company.name.clone()),
+                        ("Website", text(&company.website)),
+                        ("Phone", text(&company.phone)),
+                        ("Address", text(&company.address)),
+                    ],
+                )
+            }),
+            Pane::Posting => detail.posting.as_ref().map(|posting| {
+                Form::new(
+                    "Posting",
+                    Target::Posting {
+                        application_id,
+                        posting_id: posting.id,
+                    },
+                    vec![
+                        ("Link", posting.link.clone()),
+                        ("Description", text(&posting.description)),
+                    ],
+                )
+            }),
+            Pane::Contacts => detail.contacts.get(detail.selected).map(|contact| {
+                let contact_type = contact.contact_type.as_ref();
+
+                Form::new(
+                    "Contact",
+                    Target::Contact {
+                        application_id,
+                        contact_id: contact.id,
+                    },
+                    vec![
+                        ("Name", contact.name.clone()),
+                        (
+                            "Type",
+                            contact_type.map(|t| t.name.clone()).unwrap_or_default(),
+                        ),
+                        ("Email", text(&contact.email)),
+                        ("Phone", text(&contact.phone)),
+                        ("Description", text(&contact.description)),
+                    ],
+                )
+            }),
+            Pane::Interviews => detail.interviews.get(detail.selected).map(|interview| {
+                let interview_type = interview.interview_type.as_ref();
+                let date = interview
+                    .date
+                    .map(|date| timestamp::format_in(&date, &Local, "%Y-%m-%d %H:%M"));
+
+                Form::new(
+                    "Interview",
+                    Target::Interview {
+                        application_id,
+                        interview_id: interview.id,
+                    },
+                    vec![
+                        (
+                            "Type",
+                            interview_type.map(|t| t.name.clone()).unwrap_or_default(),
+                        ),
+                        ("Date", date.unwrap_or_default()),
+                        ("Description", text(&interview.description)),
+                    ],
+                )
+            }),
+        };
+
+        if form.is_none() {
+            return self.add_form();
+        }
+
+        Ok(form)
+    }
+
+    fn hide_focused(&mut self) -> Result<(), JobSearchError> {
+        let detail = match self.detail {
+            Some(ref detail) => detail,
+            None => return Ok(()),
+        };
+
+        let hidden = match detail.pane {
+            Pane::Company => match detail.company {
+                Some(ref company) => {
+                    let mut company = self
+                        .db
+                        .companies()
+                        .get_by_id(company.id.unwrap_or_default())?;
+                    self.db.companies().archive(&mut company)?;
+                    company.name
+                }
+                None => return Ok(()),
+            },
+            Pane::Posting => match detail.posting {
+                Some(ref posting) => {
+                    let mut posting = self
+                        .db
+                        .job_postings()
+                        .get_by_id(posting.id.unwrap_or_default())?;
+                    self.db.job_postings().archive(&mut posting)?;
+                    posting.link
+                }
+                None => return Ok(()),
+            },
+            Pane::Contacts => match detail.contacts.get(detail.selected) {
+                Some(contact) => {
+                    let mut contact = self
+                        .db
+                        .contacts()
+                        .get_by_id(contact.id.unwrap_or_default())?;
+                    self.db.contacts().archive(&mut contact)?;
+                    contact.name
+                }
+                None => return Ok(()),
+            },
+            Pane::Interviews => match detail.interviews.get(detail.selected) {
+                Some(interview) => {
+                    let mut interview = self
+                        .db
+                        .interviews()
+                        .get_by_id(interview.id.unwrap_or_default())?;
+                    self.db.interviews().archive(&mut interview)?;
+                    "the interview".to_string()
+                }
+                None => return Ok(()),
+            },
+        };
+
+        self.message = Some(format!("Hid {}", hidden));
+        self.reload()
+    }
+
+    fn open_picker(&mut self, application_id: i32) -> Result<(), JobSearchError> {
+        let application = self.db.applications().get_by_id(application_id)?;
+
+        if application.status.is_final() {
+            self.message = Some(format!(
+                "The application is {}, it can not move any more",
+                application.status
+            ));
+            return Ok(());
+        }
+
+        self.picker = Some(Picker {
+            application_id,
+            from: application.status,
+            options: application.status.next().to_vec(),
+            selected: 0,
+        });
+
+        Ok(())
+    }
+
+    fn picker_key(&mut self, key: KeyEvent) -> Result<(), JobSearchError> {
+        let picker = match self.picker.as_mut() {
+            Some(picker) => picker,
+            None => return Ok(()),
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.picker = None,
+            KeyCode::Up | KeyCode::Char('k') => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                picker.selected = (picker.selected + 1).min(picker.options.len() - 1);
+            }
+            KeyCode::Enter => {
+                let status = picker.options[picker.selected];
+                let mut application = self.db.applications().get_by_id(picker.application_id)?;
+                self.picker = None;
+
+                self.db
+                    .applications()
+                    .change_status(&mut application, status)?;
+                self.message = Some(format!("Moved the application to {}", status));
+                self.reload()?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn form_key(&mut self, key: KeyEvent) -> Result<(), JobSearchError> {
+        let action = match self.form.as_mut() {
+            Some(form) => form.handle_key(key),
+            None => return Ok(()),
+        };
+
+        match action {
+            FormAction::None => {}
+            FormAction::Cancel => self.form = None,
+            FormAction::Submit => {
+                let mut form = match self.form.take() {
+                    Some(form) => form,
+                    None => return Ok(()),
+                };
+
+                // A form that can not be saved stays open to be fixed
+                match self.save(&form) {
+                    Ok(message) => {
+                        self.message = Some(message);
+                        self.reload()?;
+                    }
+                    Err(err) => {
+                        form.error = Some(err.to_string());
+                        self.form = Some(form);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save(&self, form: &Form<Target>) -> Result<String, JobSearchError> {
+        let applications = self.db.applications();
+
+        match form.target {
+            Target::NewApplication => {
+                let company = form.required("Company")?;
+                let mut application = Application::new(
+                    self.company_id(&company)?,
+                    self.posting_id(form.optional("Posting"))?,
+                    None,
+                );
+                application.date_applied = date(form.optional("Applied"))?;
+
+                applications.add(&mut application)?;
+
+                Ok(format!("Added an application to {}", company))
+            }
+            Target::Application(id) => {
+                let mut application = applications.get_by_id(id)?;
+                application.company_id = Some(self.company_id(&form.required("Company")?)?);
+                application.job_posting_id = self.posting_id(form.optional("Posting"))?;
+                if let Some(date_applied) = date(form.optional("Applied"))? {
+                    application.date_applied = Some(date_applied);
+                }
+
+                applications.update(&mut application)?;
+
+                Ok("Saved the application".to_string())
+            }
+            Target::Company(id) => {
+                let mut company = self.db.companies().get_by_id(id)?;
+                company.name = form.required("Name")?;
+                company.website = form.optional("Website");
+                company.phone = form.optional("Phone");
+                company.address = form.optional("Address");
+
+                self.db.companies().update(&mut company)?;
+
+                Ok(format!("Saved {}", company.name))
+            }
+            Target::Posting {
+                application_id,
+                posting_id,
+            } => {
+                let link = form.required("Link")?;
+                let description = form.optional("Description");
+
+                match posting_id {
+                    Some(id) => {
+                        let mut posting = self.db.job_postings().get_by_id(id)?;
+                        posting.link = link;
+                        posting.description = description;
+
+                        self.db.job_postings().update(&mut posting)?;
+                    }
+                    None => {
+                        let mut application = applications.get_by_id(application_id)?;
+                        let mut posting = JobPosting::new(link);
+                        posting.description = description;
+
+                        self.db.job_postings().add(&mut posting)?;
+                        application.job_posting_id = posting.id;
+                        applications.update(&mut application)?;
+                    }
+                }
+
+                Ok("Saved the posting".to_string())
+            }
+            Target::Contact {
+                application_id,
+                contact_id,
+            } => {
+                let name = form.required("Name")?;
+                let type_id = self.contact_type_id(&form.required("Type")?)?;
+
+                match contact_id {
+                    Some(id) => {
+                        let mut contact = self.db.contacts().get_by_id(id)?;
+                        contact.name = name.clone();
+                        contact.type_id = Some(type_id);
+                        contact.email = form.optional("Email");
+                        contact.phone = form.optional("Phone");
+                        contact.description = form.optional("Description");
+
+                        self.db.contacts().update(&mut contact)?;
+                    }
+                    None => {
+                        let mut application = applications.get_by_id(application_id)?;
+                        let mut contact = Contact::new(
+                            name.clone(),
+                            form.optional("Email"),
+                            form.optional("Phone"),
+                            form.optional("Description"),
+                            type_id,
+                        );
+
+                        self.db.contacts().add(&mut contact)?;
+                        application.contact_id = contact.id;
+                        applications.update(&mut application)?;
+                    }
+                }
+
+                Ok(format!("Saved {}", name))
+            }
+            Target::Interview {
+                application_id,
+                interview_id,
+            } => {
+                let type_id = self.interview_type_id(&form.required("Type")?)?;
+                let date = local_time(form.optional("Date"))?;
+                let description = form.optional("Description");
+
+                match interview_id {
+                    Some(id) => {
+                        let mut interview = self.db.interviews().get_by_id(id)?;
+                        interview.interview_type_id = type_id;
+                        interview.date = date;
+                        interview.description = description;
+
+                        self.db.interviews().update(&mut interview)?;
+                    }
+                    None => {
+                        let application = applications.get_by_id(application_id)?;
+                        let mut interview = Interview::new(
+                            type_id,
+                            application.company_id.unwrap_or_default(),
+                            date,
+                        );
+                        interview.application_id = application.id;
+                        interview.job_posting_id = application.job_posting_id;
+                        interview.description = description;
+
+                        self.db.interviews().add(&mut interview)?;
+                    }
+                }
+
+                Ok("Saved the interview".to_string())
+            }
+        }
+    }
+
+    // Companies are typed in by name, and added when they are new
+    fn company_id(&self, name: &str) -> Result<i32, JobSearchError> {
+        let company = match self.db.companies().get_by_name(name)? {
+            Some(company) => company,
+            None => {
+                let mut company = Company::new(name.to_string(), None, None, None);
+                self.db.companies().add(&mut company)?;
+                company
+            }
+        };
+
+        Ok(company.id.unwrap_or_default())
+    }
+
+    fn posting_id(&self, link: Option<String>) -> Result<Option<i32>, JobSearchError> {
+        let link = match link {
+            Some(link) => link,
+            None => return Ok(None),
+        };
+
+        let posting = match self.db.job_postings().get_by_link(&link)? {
+            Some(posting) => posting,
+            None => {
+                let mut posting = JobPosting::new(link);
+                self.db.job_postings().add(&mut posting)?;
+                posting
+            }
+        };
+
+        Ok(posting.id)
+    }
+
+    fn contact_type_id(&self, name: &str) -> Result<i32, JobSearchError> {
+        if let Some(contact_type) = self.db.contact_types().get_by_name(name)? {
+            return Ok(contact_type.id.unwrap_or_default());
+        }
+
+        let mut contact_type = ContactType::new(name.to_string());
+        self.db.contact_types().add(&mut contact_type)?;
+
+        Ok(contact_type.id.unwrap_or_default())
+    }
+
+    fn interview_type_id(&self, name: &str) -> Result<i32, JobSearchError> {
+        if let Some(interview_type) = self.db.interview_types().get_by_name(name)? {
+            return Ok(interview_type.id.unwrap_or_default());
+        }
+
+        let mut interview_type = InterviewType::new(name.to_string());
+        self.db.interview_types().add(&mut interview_type)?;
+
+        Ok(interview_type.id.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn add_application(db: &Database, company: &str, status: ApplicationStatus) -> i32 {
+        let mut company = Company::new(company.to_string(), None, None, None);
+        db.companies().add(&mut company).unwrap();
+
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        db.applications().add(&mut application).unwrap();
+
+        if status != application.status {
+            db.applications()
+                .change_status(&mut application, status)
+                .unwrap();
+        }
+
+        application.id.unwrap()
+    }
+
+    fn column(app: &App, status: ApplicationStatus) -> Vec<String> {
+        app.columns
+            .iter()
+            .find(|column| column.status == status)
+            .unwrap()
+            .cards
+            .iter()
+            .map(|card| card.company.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_board() {
+        let db = Database::open_in_memory().unwrap();
+        add_application(&db, "Google", ApplicationStatus::Applied);
+        add_application(&db, "Amazon", ApplicationStatus::Applied);
+        add_application(&db, "Netflix", ApplicationStatus::Screening);
+
+        let mut app = App::new(&db).unwrap();
+
+        assert_eq!(app.columns.len(), ApplicationStatus::ALL.len());
+        assert_eq!(
+            column(&app, ApplicationStatus::Applied),
+            vec!["Google", "Amazon"]
+        );
+        assert_eq!(column(&app, ApplicationStatus::Screening), vec!["Netflix"]);
+        assert!(app.selected().is_none());
+
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected().unwrap().company, "Amazon");
+
+        // The column of screening has only one card
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.selected().unwrap().company, "Netflix");
+
+        add_application(&db, "Apple", ApplicationStatus::Screening);
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.selected().unwrap().company, "Netflix");
+        assert_eq!(column(&app, ApplicationStatus::Screening).len(), 2);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn test_add_application() {
+        let db = Database::open_in_memory().unwrap();
+        let mut app = App::new(&db).unwrap();
+
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "Google");
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "https://careers.google.com/1");
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "2020-05-01");
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.form.is_none());
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Added an application to Google")
+        );
+        assert_eq!(column(&app, ApplicationStatus::Applied), vec!["Google"]);
+
+        let application = db.applications().get_by_id(1).unwrap();
+        assert_eq!(
+            application.date_applied,
+            NaiveDate::from_ymd_opt(2020, 5, 1)
+        );
+        let posting = db
+            .job_postings()
+            .get_by_id(application.job_posting_id.unwrap());
+        assert_eq!(posting.unwrap().link, "https://careers.google.com/1");
+    }
+
+    #[test]
+    fn test_form_errors() {
+        let db = Database::open_in_memory().unwrap();
+        let mut app = App::new(&db).unwrap();
+
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Enter);
+
+        let form = app.form.as_ref().unwrap();
+        assert_eq!(
+            form.error.as_deref(),
+            Some("invalid input: Company can not be empty")
+        );
+
+        type_text(&mut app, "Google");
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "May 1st");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.form.as_ref().unwrap().error.is_some());
+        assert_eq!(db.applications().count().unwrap(), 0);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.form.is_none());
+    }
+
+    #[test]
+    fn test_move_and_hide() {
+        let db = Database::open_in_memory().unwrap();
+        let id = add_application(&db, "Google", ApplicationStatus::Applied);
+
+        let mut app = App::new(&db).unwrap();
+        press(&mut app, KeyCode::Right);
+
+        press(&mut app, KeyCode::Char('m'));
+        let picker = app.picker.as_ref().unwrap();
+        assert_eq!(picker.options, ApplicationStatus::Applied.next());
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.picker.is_none());
+        assert_eq!(
+            db.applications().get_by_id(id).unwrap().status,
+            ApplicationStatus::Interviewing
+        );
+        // The selection follows the card to its new column
+        assert_eq!(
+            app.columns[app.column].status,
+            ApplicationStatus::Interviewing
+        );
+        assert_eq!(app.selected().unwrap().company, "Google");
+
+        press(&mut app, KeyCode::Char('x'));
+        assert!(db.applications().get_by_id(id).unwrap().hide);
+        assert!(app.selected().is_none());
+    }
+
+    #[test]
+    fn test_final_status() {
+        let db = Database::open_in_memory().unwrap();
+        add_application(&db, "Google", ApplicationStatus::Rejected);
+
+        let mut app = App::new(&db).unwrap();
+        for _ in 0..6 {
+            press(&mut app, KeyCode::Right);
+        }
+
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.picker.is_none());
+        assert_eq!(
+            app.message.as_deref(),
+            Some("The application is rejected, it can not move any more")
+        );
+    }
+
+    #[test]
+    fn test_detail() {
+        let db = Database::open_in_memory().unwrap();
+        let id = add_application(&db, "Google", ApplicationStatus::Applied);
+
+        let mut app = App::new(&db).unwrap();
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.detail.as_ref().unwrap().application.id, Some(id));
+
+        // Edit the company
+        press(&mut app, KeyCode::Char('e'));
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "https://google.com");
+        press(&mut app, KeyCode::Enter);
+        let detail = app.detail.as_ref().unwrap();
+        assert_eq!(
+            detail.company.as_ref().unwrap().website.as_deref(),
+            Some("https://google.com")
+        );
+
+        // Add a posting
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "https://careers.google.com/1");
+        press(&mut app, KeyCode::Enter);
+
+        // Add a contact
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "Marcus");
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "recruiter");
+        press(&mut app, KeyCode::Enter);
+
+        // Add an interview
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "phone");
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "2020-05-08 10:00");
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.form.is_none(), "{:?}", app.form);
+        let detail = app.detail.as_ref().unwrap();
+        assert_eq!(detail.pane, Pane::Interviews);
+        assert_eq!(
+            detail.posting.as_ref().unwrap().link,
+            "https://careers.google.com/1"
+        );
+        assert_eq!(detail.contacts.len(), 1);
+        assert_eq!(detail.contacts[0].name, "Marcus");
+        assert_eq!(detail.interviews.len(), 1);
+
+        let application = db.applications().get_by_id(id).unwrap();
+        assert_eq!(application.contact_id, detail.contacts[0].id);
+        let interview = &db.interviews().get_by_application(id).unwrap()[0];
+        assert_eq!(
+            interview.date,
+            local_time(Some("2020-05-08 10:00".to_string())).unwrap()
+        );
+
+        // Hide the interview, then go back to the board
+        press(&mut app, KeyCode::Char('x'));
+        assert!(app.detail.as_ref().unwrap().interviews.is_empty());
+        assert_eq!(app.message.as_deref(), Some("Hid the interview"));
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.detail.is_none());
+        assert_eq!(
+            app.selected().unwrap().posting.as_deref(),
+            Some("https://careers.google.com/1")
+        );
+    }
+}
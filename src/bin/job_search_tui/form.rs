@@ -0,0 +1,135 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+use job_search::JobSearchError;
+
+/// One line of text in a form.
+#[derive(Debug)]
+pub struct Field {
+    pub label: &'static str,
+    pub value: String,
+}
+
+/// What a key pressed in a form asks for.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormAction {
+    None,
+    Submit,
+    Cancel,
+}
+
+/// A record being added or edited. Every field is typed in as text and
+/// only checked when the form is submitted.
+#[derive(Debug)]
+pub struct Form<T> {
+    pub title: String,
+    /// Where the record goes once the form is submitted.
+    pub target: T,
+    pub fields: Vec<Field>,
+    pub focus: usize,
+    /// Why the last submit failed.
+    pub error: Option<String>,
+}
+
+impl<T> Form<T> {
+    pub fn new(title: &str, target: T, fields: Vec<(&'static str, String)>) -> Form<T> {
+        Form {
+            title: title.to_string(),
+            target,
+            fields: fields
+                .into_iter()
+                .map(|(label, value)| Field { label, value })
+                .collect(),
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// Tab and the arrow keys move between fields, Enter submits and Esc
+    /// cancels. Anything else is typed into the focused field.
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormAction {
+        let count = self.fields.len();
+
+        match key.code {
+            KeyCode::Enter => return FormAction::Submit,
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % count,
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + count - 1) % count,
+            KeyCode::Backspace => {
+                self.fields[self.focus].value.pop();
+            }
+            KeyCode::Char(c) => self.fields[self.focus].value.push(c),
+            _ => {}
+        }
+
+        FormAction::None
+    }
+
+    /// The trimmed text of the field labelled `label`.
+    pub fn text(&self, label: &str) -> &str {
+        self.fields
+            .iter()
+            .find(|field| field.label == label)
+            .map(|field| field.value.trim())
+            .unwrap_or_default()
+    }
+
+    /// Empty fields are left out.
+    pub fn optional(&self, label: &str) -> Option<String> {
+        match self.text(label) {
+            "" => None,
+            value => Some(value.to_string()),
+        }
+    }
+
+    pub fn required(&self, label: &str) -> Result<String, JobSearchError> {
+        self.optional(label)
+            .ok_or_else(|| JobSearchError::InvalidInput(format!("{} can not be empty", label)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn press(form: &mut Form<()>, code: KeyCode) -> FormAction {
+        form.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_typing() {
+        let mut form = Form::new(
+            "Company",
+            (),
+            vec![("Name", "Goo".to_string()), ("Website", String::new())],
+        );
+
+        for code in [
+            KeyCode::Char('g'),
+            KeyCode::Char('x'),
+            KeyCode::Backspace,
+            KeyCode::Char('l'),
+            KeyCode::Char('e'),
+            KeyCode::Tab,
+            KeyCode::Char(' '),
+        ] {
+            assert_eq!(press(&mut form, code), FormAction::None);
+        }
+
+        assert_eq!(form.text("Name"), "Google");
+        assert_eq!(form.optional("Website"), None);
+        assert!(matches!(
+            form.required("Website"),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+
+        assert_eq!(press(&mut form, KeyCode::Down), FormAction::None);
+        assert_eq!(form.focus, 0);
+        assert_eq!(press(&mut form, KeyCode::Up), FormAction::None);
+        assert_eq!(form.focus, 1);
+
+        assert_eq!(press(&mut form, KeyCode::Enter), FormAction::Submit);
+        assert_eq!(press(&mut form, KeyCode::Esc), FormAction::Cancel);
+    }
+}
@@ -0,0 +1,62 @@
+mod app;
+mod form;
+mod ui;
+
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+use job_search::Database;
+
+use crate::app::App;
+
+/// Shows the applications of a job search on a board by status, and edits
+/// them and the records around them.
+#[derive(Debug, Parser)]
+#[command(name = "job-search-tui", version)]
+struct Cli {
+    /// The database file. It is created if it does not exist yet.
+    #[arg(long, env = "JOB_SEARCH_DB", default_value = "job_search.db")]
+    db: PathBuf,
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        // Some terminals report releases as well
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let db = Database::open_or_create(&cli.db).unwrap_or_else(|err| {
+        eprintln!("job-search-tui: {}", err);
+        process::exit(1);
+    });
+    let mut app = App::new(&db).unwrap_or_else(|err| {
+        eprintln!("job-search-tui: {}", err);
+        process::exit(1);
+    });
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+
+    if let Err(err) = result {
+        eprintln!("job-search-tui: {}", err);
+        process::exit(1);
+    }
+}
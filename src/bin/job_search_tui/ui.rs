@@ -0,0 +1,406 @@
+use chrono::Local;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use job_search::timestamp;
+
+use crate::app::{App, Detail, Pane, Picker, Target};
+use crate::form::Form;
+
+const BOARD_KEYS: &str =
+    "←→↑↓ move  enter open  a add  e edit  m move to  x hide  r reload  q quit";
+const DETAIL_KEYS: &str = "tab pane  ↑↓ select  a add  e edit  x hide  m move to  esc back";
+const FORM_KEYS: &str = "tab next field  enter save  esc cancel";
+const PICKER_KEYS: &str = "↑↓ select  enter move  esc cancel";
+
+fn focused(is_focused: bool) -> Style {
+    if is_focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    }
+}
+
+fn selected() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+fn dim() -> Style {
+    Style::default().add_modifier(Modifier::DIM)
+}
+
+fn local(time: &Option<chrono::DateTime<chrono::Utc>>) -> String {
+    time.map(|time| timestamp::format_in(&time, &Local, "%Y-%m-%d %H:%M"))
+        .unwrap_or_default()
+}
+
+// A box of at most `width` by `height` in the middle of `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Draws the board, or the detail of one application, with the form or
+/// status picker on top.
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+    match app.detail {
+        Some(ref detail) => draw_detail(frame, main, detail),
+        None => draw_board(frame, main, app),
+    }
+
+    let keys = if app.form.is_some() {
+        FORM_KEYS
+    } else if app.picker.is_some() {
+        PICKER_KEYS
+    } else if app.detail.is_some() {
+        DETAIL_KEYS
+    } else {
+        BOARD_KEYS
+    };
+    let status = match app.message {
+        Some(ref message) => Line::from(message.as_str()),
+        None => Line::styled(keys, dim()),
+    };
+    frame.render_widget(Paragraph::new(status), footer);
+
+    if let Some(ref picker) = app.picker {
+        draw_picker(frame, picker);
+    }
+
+    if let Some(ref form) = app.form {
+        draw_form(frame, form);
+    }
+}
+
+fn draw_board(frame: &mut Frame, area: Rect, app: &App) {
+    let count = app.columns.len() as u32;
+    let areas =
+        Layout::horizontal(vec![Constraint::Ratio(1, count); app.columns.len()]).split(area);
+
+    for (index, (column, area)) in app.columns.iter().zip(areas.iter()).enumerate() {
+        let is_focused = index == app.column;
+
+        let items: Vec<ListItem> = column
+            .cards
+            .iter()
+            .map(|card| {
+                let mut lines = vec![Line::from(card.company.as_str())];
+
+                if let Some(ref posting) = card.posting {
+                    lines.push(Line::styled(posting.as_str(), dim()));
+                }
+
+                ListItem::new(lines)
+            })
+            .collect();
+
+        let block = Block::bordered()
+            .title(format!("{} ({})", column.status, column.cards.len()))
+            .border_style(focused(is_focused));
+        let list = List::new(items).block(block).highlight_style(selected());
+
+        let mut state = ListState::default();
+        if is_focused && !column.cards.is_empty() {
+            state.select(Some(app.row));
+        }
+
+        frame.render_stateful_widget(list, *area, &mut state);
+    }
+}
+
+fn pane_block(detail: &Detail, pane: Pane) -> Block<'static> {
+    Block::bordered()
+        .title(pane.title())
+        .border_style(focused(detail.pane == pane))
+}
+
+// A list of records in a pane, with the selected one highlighted while the
+// pane is focused
+fn draw_list(frame: &mut Frame, area: Rect, detail: &Detail, pane: Pane, items: Vec<ListItem>) {
+    let mut state = ListState::default();
+    if detail.pane == pane && !items.is_empty() {
+        state.select(Some(detail.selected));
+    }
+
+    let list = List::new(items)
+        .block(pane_block(detail, pane))
+        .highlight_style(selected());
+
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_detail(frame: &mut Frame, area: Rect, detail: &Detail) {
+    let [header, top, bottom] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Percentage(40),
+        Constraint::Percentage(60),
+    ])
+    .areas(area);
+    let [company_area, posting_area] =
+        Layout::horizontal([Constraint::Percentage(50); 2]).areas(top);
+    let [contacts_area, interviews_area] =
+        Layout::horizontal([Constraint::Percentage(50); 2]).areas(bottom);
+
+    let application = &detail.application;
+    let company = detail.company.as_ref();
+    let summary = format!(
+        "{} · {} · applied {}",
+        company
+            .map(|company| company.name.as_str())
+            .unwrap_or_default(),
+        application.status,
+        application
+            .date_applied
+            .map(|date| date.to_string())
+            .unwrap_or_default()
+    );
+    let title = format!("Application {}", application.id.unwrap_or_default());
+    frame.render_widget(
+        Paragraph::new(summary).block(Block::bordered().title(title)),
+        header,
+    );
+
+    let company_lines: Vec<Line> = match company {
+        Some(company) => vec![
+            Some(company.name.clone()),
+            company.website.clone(),
+            company.phone.clone(),
+            company.address.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(Line::from)
+        .collect(),
+        None => vec![Line::styled("No company", dim())],
+    };
+    frame.render_widget(
+        Paragraph::new(company_lines)
+            .block(pane_block(detail, Pane::Company))
+            .wrap(Wrap { trim: false }),
+        company_area,
+    );
+
+    let posting_lines: Vec<Line> = match detail.posting {
+        Some(ref posting) => {
+            let mut lines = vec![Line::from(posting.link.as_str())];
+            if let Some(ref description) = posting.description {
+                lines.push(Line::from(""));
+                lines.extend(description.lines().map(Line::from));
+            }
+            lines
+        }
+        None => vec![Line::styled("No posting, press a to add one", dim())],
+    };
+    frame.render_widget(
+        Paragraph::new(posting_lines)
+            .block(pane_block(detail, Pane::Posting))
+            .wrap(Wrap { trim: false }),
+        posting_area,
+    );
+
+    let contacts: Vec<ListItem> = detail
+        .contacts
+        .iter()
+        .map(|contact| {
+            let contact_type = contact
+                .contact_type
+                .as_ref()
+                .map(|contact_type| contact_type.name.as_str())
+                .unwrap_or_default();
+
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::raw(contact.name.as_str()),
+                    Span::styled(format!(" ({})", contact_type), dim()),
+                ]),
+                Line::styled(contact.email.clone().unwrap_or_default(), dim()),
+            ])
+        })
+        .collect();
+    draw_list(frame, contacts_area, detail, Pane::Contacts, contacts);
+
+    let interviews: Vec<ListItem> = detail
+        .interviews
+        .iter()
+        .map(|interview| {
+            let interview_type = interview
+                .interview_type
+                .as_ref()
+                .map(|interview_type| interview_type.name.as_str())
+                .unwrap_or_default();
+            let mut line = format!("{}  {}", local(&interview.date), interview_type);
+            if interview.cancelled {
+                line.push_str("  cancelled");
+            } else if interview.completed {
+                line.push_str("  completed");
+            }
+
+            ListItem::new(vec![
+                Line::from(line),
+                Line::styled(interview.description.clone().unwrap_or_default(), dim()),
+            ])
+        })
+        .collect();
+    draw_list(frame, interviews_area, detail, Pane::Interviews, interviews);
+}
+
+fn draw_picker(frame: &mut Frame, picker: &Picker) {
+    let area = centered(frame.area(), 30, picker.options.len() as u16 + 2);
+
+    let items: Vec<ListItem> = picker
+        .options
+        .iter()
+        .map(|status| ListItem::new(status.as_str()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!("Move from {}", picker.from)))
+        .highlight_style(selected());
+
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_form(frame: &mut Frame, form: &Form<Target>) {
+    let width = form
+        .fields
+        .iter()
+        .map(|field| field.label.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let label = format!("{:>width$}: ", field.label, width = width);
+
+            if index == form.focus {
+                Line::from(vec![
+                    Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(field.value.as_str()),
+                    Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                ])
+            } else {
+                Line::from(vec![Span::raw(label), Span::raw(field.value.as_str())])
+            }
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        "Dates are YYYY-MM-DD, times YYYY-MM-DD HH:MM",
+        dim(),
+    ));
+    if let Some(ref error) = form.error {
+        lines.push(Line::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let area = centered(frame.area(), 70, lines.len() as u16 + 2);
+    let form = Paragraph::new(lines)
+        .block(Block::bordered().title(form.title.as_str()))
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(form, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+
+    use job_search::{Application, Company, Database};
+
+    use super::*;
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(140, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn setup() -> Database {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut company = Company::new("Google".to_string(), None, None, None);
+        db.companies().add(&mut company).unwrap();
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        db.applications().add(&mut application).unwrap();
+
+        db
+    }
+
+    #[test]
+    fn test_draw_board() {
+        let db = setup();
+        let app = App::new(&db).unwrap();
+
+        let screen = screen(&app);
+
+        assert!(screen.contains("saved (0)"), "{}", screen);
+        assert!(screen.contains("applied (1)"));
+        assert!(screen.contains("ghosted (0)"));
+        assert!(screen.contains("Google"));
+        assert!(screen.contains("enter open"));
+    }
+
+    #[test]
+    fn test_draw_detail_and_form() {
+        let db = setup();
+        let mut app = App::new(&db).unwrap();
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Enter);
+
+        let detail = screen(&app);
+        assert!(detail.contains("Application 1"), "{}", detail);
+        assert!(detail.contains("Google · applied"));
+        assert!(detail.contains("No posting, press a to add one"));
+        assert!(detail.contains("Interviews"));
+
+        press(&mut app, KeyCode::Char('e'));
+        let form = screen(&app);
+        assert!(form.contains("   Name: Google_"), "{}", form);
+        assert!(form.contains("Website: "));
+        assert!(form.contains("enter save"));
+
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('m'));
+        let picker = screen(&app);
+        assert!(picker.contains("Move from applied"), "{}", picker);
+        assert!(picker.contains("screening"));
+    }
+}
@@ -1,24 +1,49 @@
-use chrono::{DateTime, Local};
-use rusqlite::{params, Connection, NO_PARAMS};
-
-use crate::errors::JobSearchError;
-use crate::utils::convert_option_string_to_option_date;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::database::Table;
+use crate::entity::{self, Entity};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+// The ids of the records archiving a company hides with it, by table
+const RELATED_RECORDS: [(&str, &str); 3] = [
+    (
+        "job_postings",
+        "SELECT job_posting_id FROM applied_to WHERE company_id = (?1) \
+         UNION SELECT job_posting_id FROM interviews WHERE company_id = (?1) \
+         EXCEPT SELECT job_posting_id FROM applied_to WHERE company_id != (?1) \
+         AND IFNULL(hide, 0) = 0",
+    ),
+    (
+        "interviews",
+        "SELECT id FROM interviews WHERE company_id = (?1) \
+         OR application_id IN (SELECT id FROM applied_to WHERE company_id = (?1))",
+    ),
+    (
+        "applied_to",
+        "SELECT id FROM applied_to WHERE company_id = (?1)",
+    ),
+];
 
 #[derive(Debug)]
-struct Company {
-    id: Option<i32>,
-    name: String,
-    address: Option<String>,
-    website: Option<String>,
-    phone: Option<String>,
-    created_date: Option<DateTime<Local>>,
-    last_updated: Option<DateTime<Local>>,
-    hide: bool,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Company {
+    pub id: Option<i32>,
+    pub name: String,
+    pub address: Option<String>,
+    pub website: Option<String>,
+    pub phone: Option<String>,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
 }
 
-#[allow(dead_code)]
 impl Company {
-    fn new(
+    pub fn new(
         name: String,
         address: Option<String>,
         website: Option<String>,
@@ -35,112 +60,190 @@ impl Company {
             hide: false,
         }
     }
-    fn new_from_db(
-        id: Option<i32>,
-        name: String,
-        address: Option<String>,
-        website: Option<String>,
-        phone: Option<String>,
-        created_date: Option<DateTime<Local>>,
-        last_updated: Option<DateTime<Local>>,
+
+    /// Names are matched case-insensitively. Hidden companies are found too.
+    pub(crate) fn get_by_name(
+        conn: &Connection,
+        name: &str,
+    ) -> Result<Option<Company>, JobSearchError> {
+        let companies: Vec<Company> = entity::query(
+            conn,
+            &format!(
+                "{} WHERE name = (?1) COLLATE NOCASE ORDER BY id",
+                Company::SELECT
+            ),
+            params![name.trim()],
+        )?;
+
+        Ok(companies.into_iter().next())
+    }
+
+    /// Hides or shows the company together with its applications, its
+    /// interviews and the postings it was applied to or interviewed for.
+    /// Postings that a visible application to another company refers to are
+    /// left alone. Showing the company again only shows the records hiding
+    /// it hid, not those that were archived on their own.
+    pub(crate) fn set_hidden_with_related(
+        &mut self,
+        conn: &Connection,
         hide: bool,
-    ) -> Company {
-        Company {
-            id,
-            name,
-            address,
-            website,
-            phone,
-            created_date,
-            last_updated,
-            hide,
-        }
+    ) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "company")?;
+
+        with_savepoint(conn, || {
+            if hide {
+                for (table, ids) in RELATED_RECORDS.iter() {
+                    let _ = conn.execute(
+                        &format!(
+                            "INSERT OR IGNORE INTO archived_with_company \
+                             (company_id, table_name, record_id) \
+                             SELECT (?1), (?2), id FROM {table} \
+                             WHERE IFNULL(hide, 0) = 0 AND id IN ({ids})",
+                            table = table,
+                            ids = ids
+                        ),
+                        params![id, table],
+                    )?;
+                }
+            }
+
+            for (table, _) in RELATED_RECORDS.iter() {
+                let _ = conn.execute(
+                    &format!(
+                        "UPDATE {} SET hide = (?3) WHERE id IN ( \
+                             SELECT record_id FROM archived_with_company \
+                             WHERE company_id = (?1) AND table_name = (?2) \
+                         )",
+                        table
+                    ),
+                    params![id, table, hide],
+                )?;
+            }
+
+            if hide {
+                self.archive(conn)
+            } else {
+                let _ = conn.execute(
+                    "DELETE FROM archived_with_company WHERE company_id = (?1)",
+                    params![id],
+                )?;
+
+                self.unarchive(conn)
+            }
+        })
     }
+}
 
-    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let hide = if self.hide { 1 } else { 0 };
+impl Entity for Company {
+    const TABLE: &'static str = "companies";
+    const NAME: &'static str = "company";
+    const SELECT: &'static str = "SELECT id, name, address, website, phone, created_date, \
+         last_updated, hide FROM companies";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("companies.created_date"),
+        company: Some("companies.id = ?"),
+        status: Some("companies.id IN (SELECT company_id FROM applied_to WHERE status = ?)"),
+        contact_type: None,
+        text: &[
+            "companies.name",
+            "companies.address",
+            "companies.website",
+            "companies.phone",
+        ],
+        name: Some("companies.name"),
+        tag: Some(
+            "companies.id IN (SELECT company_tags.company_id FROM company_tags \
+             JOIN tags ON tags.id = company_tags.tag_id WHERE tags.name = ?)",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Company, JobSearchError> {
+        let company = Company {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            address: row.get(2)?,
+            website: row.get(3)?,
+            phone: row.get(4)?,
+            created_date: timestamp::get(row, 5)?,
+            last_updated: timestamp::get(row, 6)?,
+            hide: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
+        };
+
+        Ok(company)
+    }
 
-        let _ = conn.execute(
-            "UPDATE companies SET name=(?1), address=(?2), website=(?3), phone=(?4), hide=(?5) WHERE id = (?6)",
-            params![self.name, self.address, self.website, self.phone, hide, self.id]
-        )?;
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
 
-        let row = conn.query_row(
-            "SELECT last_updated FROM companies WHERE id=(?1)",
-            params![self.id],
-            |row| {
-                let last_updated: Option<String> = row.get(0)?;
-                let last_updated = convert_option_string_to_option_date(last_updated);
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.name, "company name")?;
 
-                Ok(last_updated)
-            },
+        let _ = conn.execute(
+            "INSERT INTO companies (name, address, website, phone, hide, created_date, last_updated) \
+             VALUES (?1, ?2, ?3, ?4, ?5, COALESCE(?6, CURRENT_TIMESTAMP), ?7)",
+            params![
+                self.name,
+                self.address,
+                self.website,
+                self.phone,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
         )?;
 
-        self.last_updated = row;
+        let company = Company::get_by_id(conn, conn.last_insert_rowid() as i32)?;
+
+        self.id = company.id;
+        self.created_date = company.created_date;
+        self.last_updated = company.last_updated;
 
         Ok(())
     }
 
-    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let hide = if self.hide { 1 } else { 0 };
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "company")?;
+        require_text(&self.name, "company name")?;
 
         let _ = conn.execute(
-            "INSERT INTO companies (name, address, website, phone, hide) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![self.name, self.address, self.website, self.phone, hide]
+            "UPDATE companies SET name=(?1), address=(?2), website=(?3), phone=(?4), hide=(?5) WHERE id = (?6)",
+            params![self.name, self.address, self.website, self.phone, self.hide, id],
         )?;
 
-        let id = conn.last_insert_rowid() as i32;
-
-        let row = conn.query_row(
-            "SELECT created_date, last_updated FROM companies WHERE id=(?1)",
-            params![id],
-            |row| {
-                let created_date: Option<String> = row.get(0)?;
-                let created_date = convert_option_string_to_option_date(created_date);
+        self.last_updated = Company::get_by_id(conn, id)?.last_updated;
 
-                let last_updated: Option<String> = row.get(1)?;
-                let last_updated = convert_option_string_to_option_date(last_updated);
+        Ok(())
+    }
 
-                Ok((created_date, last_updated))
-            },
-        )?;
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Company>(conn, self.id)?;
 
-        self.id = Some(id);
-        self.created_date = row.0;
-        self.last_updated = row.1;
+        self.id = None;
 
         Ok(())
     }
+}
+
+impl<'a> Table<'a, Company> {
+    pub fn get_by_name(&self, name: &str) -> Result<Option<Company>, JobSearchError> {
+        Company::get_by_name(self.conn(), name)
+    }
 
-    #[allow(dead_code)]
-    fn get_all(conn: &Connection) -> Result<Vec<Company>, JobSearchError> {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, address, website, phone, created_date, last_updated, hide FROM companies")?;
-
-        let companies_iter = stmt.query_map(NO_PARAMS, |row| {
-            let hide = match row.get(8)? {
-                0 => true,
-                _ => false,
-            };
-
-            Ok(Company::new_from_db(
-                row.get(1)?,
-                row.get(2)?,
-                row.get(3)?,
-                row.get(4)?,
-                row.get(5)?,
-                row.get(6)?,
-                row.get(7)?,
-                hide,
-            ))
-        })?;
-
-        let mut companies_list = Vec::new();
-        for company in companies_iter {
-            companies_list.push(company?);
-        }
+    /// Archives the company along with its applications, interviews and the
+    /// postings only it refers to.
+    pub fn archive_with_related(&self, company: &mut Company) -> Result<(), JobSearchError> {
+        company.set_hidden_with_related(self.conn(), true)
+    }
 
-        Ok(companies_list)
+    /// Unarchives the company along with the records `archive_with_related`
+    /// archived. Records that were archived on their own stay archived.
+    pub fn unarchive_with_related(&self, company: &mut Company) -> Result<(), JobSearchError> {
+        company.set_hidden_with_related(self.conn(), false)
     }
 }
 
@@ -178,9 +281,206 @@ mod tests {
             Some(phone.clone()),
         );
 
-        let _ = company.add_to_db(&conn).unwrap();
+        company.add_to_db(&conn).unwrap();
 
         assert_ne!(company.created_date, None);
         assert_eq!(company.last_updated, None);
     }
+
+    #[test]
+    fn test_get_all() {
+        let conn = create_in_memory_db().unwrap();
+
+        Company::new("first".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+        Company::new("second".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let companies = Company::get_all(&conn).unwrap();
+
+        assert_eq!(companies.len(), 2);
+        assert_eq!(companies[0].id, Some(1));
+        assert_eq!(companies[0].name, "first");
+        assert!(!companies[0].hide);
+        assert_ne!(companies[0].created_date, None);
+    }
+
+    #[test]
+    fn test_get_by_id() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("testing".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+
+        let company_from_db = Company::get_by_id(&conn, company.id.unwrap()).unwrap();
+
+        assert_eq!(company_from_db.name, company.name);
+    }
+
+    #[test]
+    fn test_get_by_name() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+
+        let found = Company::get_by_name(&conn, " google").unwrap().unwrap();
+
+        assert_eq!(found.id, company.id);
+        assert!(Company::get_by_name(&conn, "mozilla").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_by_id_not_found() {
+        let conn = create_in_memory_db().unwrap();
+
+        match Company::get_by_id(&conn, 42) {
+            Err(JobSearchError::NotFound { entity, id }) => {
+                assert_eq!(entity, "company");
+                assert_eq!(id, 42);
+            }
+            result => panic!("expected NotFound, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_add_to_db_validation() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("  ".to_string(), None, None, None);
+
+        assert!(matches!(
+            company.add_to_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_to_db_duplicate() {
+        let conn = create_in_memory_db().unwrap();
+
+        Company::new("google".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+        let result = Company::new("google".to_string(), None, None, None).add_to_db(&conn);
+
+        assert!(matches!(result, Err(JobSearchError::Duplicate { .. })));
+    }
+
+    #[test]
+    fn test_update_db_without_id() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+
+        assert!(matches!(
+            company.update_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_set_hidden_with_related() {
+        use crate::application::Application;
+        use crate::interview::Interview;
+        use crate::interview_type::InterviewType;
+        use crate::job_posting::JobPosting;
+
+        let conn = create_in_memory_db().unwrap();
+
+        let mut google = Company::new("google".to_string(), None, None, None);
+        google.add_to_db(&conn).unwrap();
+        let mut mozilla = Company::new("mozilla".to_string(), None, None, None);
+        mozilla.add_to_db(&conn).unwrap();
+
+        let mut own_posting = JobPosting::new("https://google.com/job".to_string());
+        own_posting.add_to_db(&conn).unwrap();
+        let mut shared_posting = JobPosting::new("https://example.com/job".to_string());
+        shared_posting.add_to_db(&conn).unwrap();
+
+        let mut application = Application::new(google.id.unwrap(), own_posting.id, None);
+        application.add_to_db(&conn).unwrap();
+        Application::new(google.id.unwrap(), shared_posting.id, None)
+            .add_to_db(&conn)
+            .unwrap();
+        Application::new(mozilla.id.unwrap(), shared_posting.id, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut interview = Interview::new(interview_type.id.unwrap(), google.id.unwrap(), None);
+        interview.application_id = application.id;
+        interview.add_to_db(&conn).unwrap();
+
+        google.set_hidden_with_related(&conn, true).unwrap();
+
+        assert!(google.hide);
+        assert_eq!(Company::count(&conn).unwrap(), 1);
+        assert_eq!(Application::count(&conn).unwrap(), 1);
+        assert_eq!(Interview::count(&conn).unwrap(), 0);
+        assert_eq!(
+            JobPosting::get_all(&conn).unwrap()[0].link,
+            shared_posting.link
+        );
+
+        google.set_hidden_with_related(&conn, false).unwrap();
+
+        assert!(!google.hide);
+        assert_eq!(Application::count(&conn).unwrap(), 3);
+        assert_eq!(Interview::count(&conn).unwrap(), 1);
+        assert_eq!(JobPosting::count(&conn).unwrap(), 2);
+
+        // Records archived on their own stay archived
+        application.archive(&conn).unwrap();
+        own_posting.archive(&conn).unwrap();
+        google.set_hidden_with_related(&conn, true).unwrap();
+        google.set_hidden_with_related(&conn, false).unwrap();
+
+        assert_eq!(Application::count(&conn).unwrap(), 2);
+        assert_eq!(Interview::count(&conn).unwrap(), 1);
+        assert_eq!(JobPosting::count(&conn).unwrap(), 1);
+        assert!(
+            Application::get_by_id(&conn, application.id.unwrap())
+                .unwrap()
+                .hide
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+
+        let json = serde_json::to_value(&company).unwrap();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                "address",
+                "created_date",
+                "hide",
+                "id",
+                "last_updated",
+                "name",
+                "phone",
+                "website"
+            ]
+        );
+        let created = json["created_date"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(created).is_ok());
+
+        // Fields left out are empty, and records are shown unless told
+        // otherwise
+        let company: Company = serde_json::from_str(r#"{"name": "Mozilla"}"#).unwrap();
+        assert_eq!(company.name, "Mozilla");
+        assert_eq!(company.id, None);
+        assert!(!company.hide);
+    }
 }
@@ -0,0 +1,320 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::contact_type::ContactType;
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::timestamp;
+
+const SELECT_CONTACTS: &str = "SELECT contacts.id, contacts.name, contacts.created_date, \
+     contacts.last_updated, contacts.email, contacts.phone, contacts.description, \
+     contacts.type_id, contacts.hide, contact_types.id, contact_types.name, \
+     contact_types.last_updated, contact_types.hide \
+     FROM contacts LEFT JOIN contact_types ON contact_types.id = contacts.type_id";
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contact {
+    pub id: Option<i32>,
+    pub name: String,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub description: Option<String>,
+    // Databases created before the schema was versioned allow contacts
+    // without a type, so this can be missing when reading them back.
+    pub type_id: Option<i32>,
+    /// The contact type `type_id` points to. Filled in when the contact is
+    /// read from the database.
+    pub contact_type: Option<ContactType>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+}
+
+impl Contact {
+    pub fn new(
+        name: String,
+        email: Option<String>,
+        phone: Option<String>,
+        description: Option<String>,
+        type_id: i32,
+    ) -> Contact {
+        Contact {
+            id: None,
+            name,
+            created_date: None,
+            last_updated: None,
+            email,
+            phone,
+            description,
+            type_id: Some(type_id),
+            contact_type: None,
+            hide: false,
+        }
+    }
+
+    /// Email addresses are matched case-insensitively. More than one contact
+    /// can share an address, e.g. a shared recruiting inbox.
+    pub(crate) fn get_by_email(
+        conn: &Connection,
+        email: &str,
+    ) -> Result<Vec<Contact>, JobSearchError> {
+        entity::query(
+            conn,
+            &format!(
+                "{} WHERE contacts.email = (?1) COLLATE NOCASE AND {} ORDER BY contacts.id",
+                SELECT_CONTACTS,
+                Visibility::Visible.condition(Contact::TABLE)
+            ),
+            params![email],
+        )
+    }
+}
+
+impl Entity for Contact {
+    const TABLE: &'static str = "contacts";
+    const NAME: &'static str = "contact";
+    const SELECT: &'static str = SELECT_CONTACTS;
+    const ORDER_BY: &'static str = "contacts.id";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("contacts.created_date"),
+        company: Some("contacts.id IN (SELECT contact_id FROM applied_to WHERE company_id = ?)"),
+        status: None,
+        contact_type: Some("contacts.type_id = ?"),
+        text: &[
+            "contacts.name",
+            "contacts.email",
+            "contacts.phone",
+            "contacts.description",
+        ],
+        name: Some("contacts.name"),
+        tag: Some(
+            "contacts.id IN (SELECT contact_tags.contact_id FROM contact_tags \
+             JOIN tags ON tags.id = contact_tags.tag_id WHERE tags.name = ?)",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Contact, JobSearchError> {
+        let contact_type = match row.get::<_, Option<i32>>(9)? {
+            Some(id) => Some(ContactType::new_from_db(
+                id,
+                row.get(10)?,
+                timestamp::get(row, 11)?,
+                row.get::<_, Option<bool>>(12)?.unwrap_or(false),
+            )),
+            None => None,
+        };
+
+        let contact = Contact {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            created_date: timestamp::get(row, 2)?,
+            last_updated: timestamp::get(row, 3)?,
+            email: row.get(4)?,
+            phone: row.get(5)?,
+            description: row.get(6)?,
+            type_id: row.get(7)?,
+            contact_type,
+            hide: row.get::<_, Option<bool>>(8)?.unwrap_or(false),
+        };
+
+        Ok(contact)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.name, "contact name")?;
+
+        let _ = conn.execute(
+            "INSERT INTO contacts (name, email, phone, description, type_id, hide, created_date, last_updated) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, COALESCE(?7, CURRENT_TIMESTAMP), ?8)",
+            params![
+                self.name,
+                self.email,
+                self.phone,
+                self.description,
+                self.type_id,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        let id = conn.last_insert_rowid() as i32;
+
+        let contact = Contact::get_by_id(conn, id)?;
+
+        self.id = contact.id;
+        self.created_date = contact.created_date;
+        self.last_updated = contact.last_updated;
+        self.contact_type = contact.contact_type;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "contact")?;
+        require_text(&self.name, "contact name")?;
+
+        let _ = conn.execute(
+            "UPDATE contacts SET name=(?1), email=(?2), phone=(?3), description=(?4), type_id=(?5), hide=(?6) WHERE id = (?7)",
+            params![self.name, self.email, self.phone, self.description, self.type_id, self.hide, id],
+        )?;
+
+        // The type may have changed, so resolve it again along with the
+        // timestamp set by the trigger
+        let contact = Contact::get_by_id(conn, id)?;
+
+        self.last_updated = contact.last_updated;
+        self.contact_type = contact.contact_type;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Contact>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Contact> {
+    pub fn get_by_email(&self, email: &str) -> Result<Vec<Contact>, JobSearchError> {
+        Contact::get_by_email(self.conn(), email)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_in_memory_db;
+
+    fn create_contact_type(conn: &Connection, name: &str) -> i32 {
+        let mut contact_type = ContactType::new(name.to_string());
+        contact_type.add_to_db(conn).unwrap();
+
+        contact_type.id.unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let contact = Contact::new("Marcus".to_string(), None, None, None, 1);
+
+        assert_eq!(contact.id, None);
+        assert_eq!(contact.type_id, Some(1));
+        assert!(contact.contact_type.is_none());
+        assert!(!contact.hide);
+    }
+
+    #[test]
+    fn test_add_to_db() {
+        let conn = create_in_memory_db().unwrap();
+        let type_id = create_contact_type(&conn, "recruiter");
+
+        let mut contact = Contact::new(
+            "Marcus".to_string(),
+            Some("marcus@example.com".to_string()),
+            None,
+            None,
+            type_id,
+        );
+        contact.add_to_db(&conn).unwrap();
+
+        assert_ne!(contact.id, None);
+        assert_ne!(contact.created_date, None);
+        assert_eq!(contact.contact_type.unwrap().name, "recruiter");
+    }
+
+    #[test]
+    fn test_add_to_db_with_unknown_type() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut contact = Contact::new("Marcus".to_string(), None, None, None, 42);
+
+        assert!(contact.add_to_db(&conn).is_err());
+    }
+
+    #[test]
+    fn test_update_db() {
+        let conn = create_in_memory_db().unwrap();
+        let recruiter = create_contact_type(&conn, "recruiter");
+        let referral = create_contact_type(&conn, "referral");
+
+        let mut contact = Contact::new("Marcus".to_string(), None, None, None, recruiter);
+        contact.add_to_db(&conn).unwrap();
+
+        contact.type_id = Some(referral);
+        contact.phone = Some("555-555-5555".to_string());
+        contact.update_db(&conn).unwrap();
+
+        assert_ne!(contact.last_updated, None);
+        assert_eq!(contact.contact_type.unwrap().name, "referral");
+    }
+
+    #[test]
+    fn test_get_by_id() {
+        let conn = create_in_memory_db().unwrap();
+        let type_id = create_contact_type(&conn, "recruiter");
+
+        let mut contact = Contact::new("Marcus".to_string(), None, None, None, type_id);
+        contact.add_to_db(&conn).unwrap();
+
+        let contact_from_db = Contact::get_by_id(&conn, contact.id.unwrap()).unwrap();
+
+        assert_eq!(contact_from_db.name, contact.name);
+        assert_eq!(contact_from_db.contact_type.unwrap().id, Some(type_id));
+    }
+
+    #[test]
+    fn test_get_all() {
+        let conn = create_in_memory_db().unwrap();
+        let type_id = create_contact_type(&conn, "recruiter");
+
+        Contact::new("Marcus".to_string(), None, None, None, type_id)
+            .add_to_db(&conn)
+            .unwrap();
+        Contact::new("Allen".to_string(), None, None, None, type_id)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let contacts = Contact::get_all(&conn).unwrap();
+
+        assert_eq!(contacts.len(), 2);
+    }
+
+    #[test]
+    fn test_get_by_email() {
+        let conn = create_in_memory_db().unwrap();
+        let type_id = create_contact_type(&conn, "recruiter");
+
+        Contact::new(
+            "Marcus".to_string(),
+            Some("marcus@example.com".to_string()),
+            None,
+            None,
+            type_id,
+        )
+        .add_to_db(&conn)
+        .unwrap();
+        Contact::new("Allen".to_string(), None, None, None, type_id)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let contacts = Contact::get_by_email(&conn, "Marcus@Example.com").unwrap();
+
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].name, "Marcus");
+    }
+}
@@ -1,20 +1,24 @@
-use chrono::{DateTime, Local};
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
 
-use crate::errors::JobSearchError;
-use crate::utils::convert_option_string_to_option_date;
+use crate::database::Table;
+use crate::entity::{self, Entity};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::timestamp;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContactType {
-    id: Option<i32>,
+    pub id: Option<i32>,
     pub name: String,
-    last_updated: Option<DateTime<Local>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub hide: bool,
 }
 
-#[allow(dead_code)]
 impl ContactType {
-    fn new(name: String) -> ContactType {
+    pub fn new(name: String) -> ContactType {
         ContactType {
             id: None,
             name,
@@ -23,17 +27,12 @@ impl ContactType {
         }
     }
 
-    fn new_from_db(
+    pub(crate) fn new_from_db(
         id: i32,
         name: String,
-        last_updated: Option<DateTime<Local>>,
-        hide: i32,
+        last_updated: Option<DateTime<Utc>>,
+        hide: bool,
     ) -> ContactType {
-        let hide = match hide {
-            0 => false,
-            _ => true,
-        };
-
         ContactType {
             id: Some(id),
             name,
@@ -42,119 +41,92 @@ impl ContactType {
         }
     }
 
-    fn get_by_id(conn: &Connection, id: i32) -> Result<ContactType, JobSearchError> {
-        let contact_type = conn.query_row(
-            "SELECT id, name, last_updated, hide FROM contact_types WHERE id = (?1)",
-            params![id],
-            |row| {
-                let id: i32 = row.get(0)?;
-                let name: String = row.get(1)?;
-                let last_updated: Option<String> = match row.get(2)? {
-                    Some(time) => Some(time),
-                    None => None,
-                };
-                let last_updated = convert_option_string_to_option_date(last_updated);
-                let hide: i32 = row.get(3)?;
+    /// Names are unique, so there is at most one match.
+    pub(crate) fn get_by_name(
+        conn: &Connection,
+        name: &str,
+    ) -> Result<Option<ContactType>, JobSearchError> {
+        let contact_types: Vec<ContactType> = entity::query(
+            conn,
+            &format!("{} WHERE name = (?1)", ContactType::SELECT),
+            params![name],
+        )?;
+
+        Ok(contact_types.into_iter().next())
+    }
+}
+
+impl Entity for ContactType {
+    const TABLE: &'static str = "contact_types";
+    const NAME: &'static str = "contact type";
+    const SELECT: &'static str = "SELECT id, name, last_updated, hide FROM contact_types";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        text: &["contact_types.name"],
+        name: Some("contact_types.name"),
+        ..FilterColumns::NONE
+    };
+
+    fn new_from_row(row: &Row) -> Result<ContactType, JobSearchError> {
+        let contact_type = ContactType {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            last_updated: timestamp::get(row, 2)?,
+            hide: row.get::<_, Option<bool>>(3)?.unwrap_or(false),
+        };
+
+        Ok(contact_type)
+    }
 
-                Ok(ContactType::new_from_db(id, name, last_updated, hide))
-            },
-        );
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
 
-        Ok(contact_type?)
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
     }
 
     fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.name, "contact type name")?;
+
         let _ = conn.execute(
-            "INSERT INTO contact_types (name) VALUES (?1)",
-            params![self.name],
+            "INSERT INTO contact_types (name, hide, last_updated) VALUES (?1, ?2, ?3)",
+            params![self.name, self.hide, timestamp::to_sql(&self.last_updated)],
         )?;
 
-        // Update the id
-        let id = conn.last_insert_rowid() as i32;
+        let contact_type = ContactType::get_by_id(conn, conn.last_insert_rowid() as i32)?;
 
-        let mut stmt =
-            conn.prepare("SELECT name, last_updated, hide FROM contact_types WHERE id=(?1)")?;
-
-        let row = stmt.query_row(params![id], |row| {
-            let name: String = row.get(0)?;
-            let last_updated: Option<String> = match row.get(1)? {
-                Some(time) => Some(time),
-                None => None,
-            };
-            let last_updated = convert_option_string_to_option_date(last_updated);
-            let hide = match row.get(2)? {
-                0 => false,
-                _ => true,
-            };
-
-            Ok((name, last_updated, hide))
-        })?;
-
-        self.id = Some(id);
-        self.name = row.0;
-        self.last_updated = row.1;
-        self.hide = row.2;
+        self.id = contact_type.id;
+        self.last_updated = contact_type.last_updated;
 
         Ok(())
     }
 
     fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let hide = if self.hide { 1 } else { 0 };
+        let id = require_id(self.id, "contact type")?;
+        require_text(&self.name, "contact type name")?;
 
         let _ = conn.execute(
             "UPDATE contact_types SET name = (?1), hide = (?2) WHERE id = (?3)",
-            params![self.name, hide, self.id],
-        )?;
-
-        //need to update the last_updated field
-        let last_updated = conn.query_row(
-            "SELECT last_updated FROM contact_types WHERE id = (?1)",
-            params![self.id],
-            |row| {
-                let last_updated: Option<String> = match row.get(0)? {
-                    Some(time) => Some(time),
-                    None => None,
-                };
-                let last_updated = convert_option_string_to_option_date(last_updated);
-
-                Ok(last_updated)
-            },
+            params![self.name, self.hide, id],
         )?;
 
-        self.last_updated = last_updated;
+        self.last_updated = ContactType::get_by_id(conn, id)?.last_updated;
 
         Ok(())
     }
 
-    pub fn new_from_row(row: &rusqlite::Row) -> Result<ContactType, JobSearchError> {
-        //add error handling
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<ContactType>(conn, self.id)?;
 
-        let result = ContactType {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            last_updated: row.get(2)?,
-            hide: match row.get(3)? {
-                0 => false,
-                _ => true,
-            },
-        };
+        self.id = None;
 
-        Ok(result)
+        Ok(())
     }
+}
 
-    fn get_all(conn: &Connection) -> Result<Vec<ContactType>, JobSearchError> {
-        let mut stmt = conn.prepare("SELECT id, name, last_updated, hide FROM contact_types")?;
-
-        let contact_types_iter =
-            stmt.query_map(params![], |row| Ok(ContactType::new_from_row(row).unwrap()))?;
-
-        let mut contact_types = Vec::new();
-
-        for types in contact_types_iter {
-            contact_types.push(types?);
-        }
-
-        Ok(contact_types)
+impl<'a> Table<'a, ContactType> {
+    pub fn get_by_name(&self, name: &str) -> Result<Option<ContactType>, JobSearchError> {
+        ContactType::get_by_name(self.conn(), name)
     }
 }
 
@@ -172,23 +144,22 @@ mod tests {
         assert_eq!(contact_type.id, None);
         assert_eq!(contact_type.name, name);
         assert_eq!(contact_type.last_updated, None);
-        assert_eq!(contact_type.hide, false);
+        assert!(!contact_type.hide);
     }
 
     #[test]
     fn test_new_from_db() {
         let id: i32 = 5;
         let name = "testing".to_string();
-        let last_updated = Local::now();
-        let hide = 1; //everything other than 0 is true
+        let last_updated = Utc::now();
+        let hide = true;
 
-        let contact_type =
-            ContactType::new_from_db(id, name.clone(), Some(last_updated.clone()), hide);
+        let contact_type = ContactType::new_from_db(id, name.clone(), Some(last_updated), hide);
 
         assert_eq!(contact_type.id, Some(id));
         assert_eq!(contact_type.name, name);
         assert_eq!(contact_type.last_updated, Some(last_updated));
-        assert_eq!(contact_type.hide, true);
+        assert!(contact_type.hide);
     }
 
     #[test]
@@ -197,7 +168,7 @@ mod tests {
 
         let conn = create_in_memory_db().unwrap();
 
-        let _ = contact_type.add_to_db(&conn).unwrap();
+        contact_type.add_to_db(&conn).unwrap();
 
         assert_eq!(contact_type.id, Some(1));
     }
@@ -208,7 +179,7 @@ mod tests {
 
         let conn = create_in_memory_db().unwrap();
 
-        let _ = contact_type.add_to_db(&conn).unwrap();
+        contact_type.add_to_db(&conn).unwrap();
 
         let contact_type_from_db = ContactType::get_by_id(&conn, contact_type.id.unwrap()).unwrap();
 
@@ -219,11 +190,11 @@ mod tests {
     fn test_update_db() {
         let mut contact_type = ContactType::new("testing".to_string());
         let conn = create_in_memory_db().unwrap();
-        let _ = contact_type.add_to_db(&conn).unwrap();
+        contact_type.add_to_db(&conn).unwrap();
 
         contact_type.hide = true;
 
-        let _ = contact_type.update_db(&conn).unwrap();
+        contact_type.update_db(&conn).unwrap();
 
         assert_ne!(contact_type.last_updated, None);
     }
@@ -234,7 +205,7 @@ mod tests {
 
         let items = ContactType::get_all(&conn).unwrap();
 
-        assert_eq!(items.is_empty(), true);
+        assert!(items.is_empty());
     }
 
     #[test]
@@ -243,10 +214,24 @@ mod tests {
 
         let conn = create_in_memory_db().unwrap();
 
-        let _ = contact_type.add_to_db(&conn).unwrap();
+        contact_type.add_to_db(&conn).unwrap();
 
         let all_contact_types = ContactType::get_all(&conn).unwrap();
 
-        assert_eq!(all_contact_types.is_empty(), false);
+        assert!(!all_contact_types.is_empty());
+    }
+
+    #[test]
+    fn test_get_by_name() {
+        let conn = create_in_memory_db().unwrap();
+
+        ContactType::new("phone".to_string())
+            .add_to_db(&conn)
+            .unwrap();
+
+        let found = ContactType::get_by_name(&conn, "phone").unwrap();
+
+        assert_eq!(found.unwrap().name, "phone");
+        assert!(ContactType::get_by_name(&conn, "onsite").unwrap().is_none());
     }
 }
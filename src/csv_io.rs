@@ -0,0 +1,1206 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use csv::{ReaderBuilder, StringRecord, Trim, Writer};
+use rusqlite::{params, Connection};
+
+use crate::application::Application;
+use crate::application_status::ApplicationStatus;
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::contact_type::ContactType;
+use crate::entity::{Entity, Visibility};
+use crate::errors::JobSearchError;
+use crate::interview::Interview;
+use crate::interview_type::InterviewType;
+use crate::job_posting::JobPosting;
+use crate::timestamp;
+use crate::utils::{with_rollback, with_savepoint};
+
+// Written on export but never read back, since the database sets them
+const READ_ONLY: &[&str] = &["id", "created_date", "last_updated"];
+
+/// A table that can be exported to and imported from CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CsvTable {
+    Companies,
+    JobPostings,
+    Contacts,
+    ContactTypes,
+    InterviewTypes,
+    Applications,
+    Interviews,
+}
+
+impl CsvTable {
+    pub const ALL: [CsvTable; 7] = [
+        CsvTable::Companies,
+        CsvTable::JobPostings,
+        CsvTable::Contacts,
+        CsvTable::ContactTypes,
+        CsvTable::InterviewTypes,
+        CsvTable::Applications,
+        CsvTable::Interviews,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CsvTable::Companies => "companies",
+            CsvTable::JobPostings => "job_postings",
+            CsvTable::Contacts => "contacts",
+            CsvTable::ContactTypes => "contact_types",
+            CsvTable::InterviewTypes => "interview_types",
+            CsvTable::Applications => "applications",
+            CsvTable::Interviews => "interviews",
+        }
+    }
+
+    /// The columns of the CSV file, in the order they are exported.
+    /// References to other records are given by name, or by link for job
+    /// postings, instead of by id.
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            CsvTable::Companies => &[
+                "id",
+                "name",
+                "address",
+                "website",
+                "phone",
+                "created_date",
+                "last_updated",
+                "hide",
+            ],
+            CsvTable::JobPostings => &[
+                "id",
+                "link",
+                "description",
+                "created_date",
+                "last_updated",
+                "hide",
+            ],
+            CsvTable::Contacts => &[
+                "id",
+                "name",
+                "email",
+                "phone",
+                "description",
+                "contact_type",
+                "created_date",
+                "last_updated",
+                "hide",
+            ],
+            CsvTable::ContactTypes | CsvTable::InterviewTypes => {
+                &["id", "name", "last_updated", "hide"]
+            }
+            CsvTable::Applications => &[
+                "id",
+                "company",
+                "job_posting",
+                "contact",
+                "date_applied",
+                "status",
+                "last_updated",
+                "hide",
+            ],
+            CsvTable::Interviews => &[
+                "id",
+                "date",
+                "company",
+                "interview_type",
+                "contact",
+                "job_posting",
+                "description",
+                "cancelled",
+                "completed",
+                "created_date",
+                "last_updated",
+                "hide",
+            ],
+        }
+    }
+
+    /// The columns an imported file has to have.
+    pub fn required_columns(self) -> &'static [&'static str] {
+        match self {
+            CsvTable::Companies | CsvTable::ContactTypes | CsvTable::InterviewTypes => &["name"],
+            CsvTable::JobPostings => &["link"],
+            CsvTable::Contacts => &["name", "contact_type"],
+            CsvTable::Applications => &["company"],
+            CsvTable::Interviews => &["company", "interview_type"],
+        }
+    }
+}
+
+impl fmt::Display for CsvTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CsvTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CsvTable, String> {
+        CsvTable::ALL
+            .iter()
+            .find(|table| table.as_str() == normalize(s))
+            .copied()
+            .ok_or_else(|| format!("unknown table: {}", s))
+    }
+}
+
+/// How `Database::import_csv` reads a file.
+#[derive(Debug, Clone, Default)]
+pub struct CsvImportOptions {
+    /// Maps headers of the file to columns of the table, for files that use
+    /// their own names, such as "Company Name" for `name`. Other headers are
+    /// matched to the column of the same name, ignoring case and treating
+    /// spaces as underscores.
+    pub columns: HashMap<String, String>,
+    /// Check every row and report what would be added or updated, without
+    /// saving anything.
+    pub dry_run: bool,
+}
+
+/// A row of an imported file that could not be imported.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RowError {
+    /// The line of the file the row starts on, counting from 1.
+    pub line: u64,
+    pub message: String,
+}
+
+/// What `Database::import_csv` did with each row of a file.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CsvImport {
+    /// The ids of the records added. In a dry run, the ids they would have
+    /// been given.
+    pub created: Vec<i32>,
+    /// The ids of the companies, postings and types that already existed
+    /// and were updated from the file.
+    pub updated: Vec<i32>,
+    /// Rows that were left out, and why.
+    pub errors: Vec<RowError>,
+    /// Headers of the file that match no column of the table.
+    pub ignored_columns: Vec<String>,
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn id(value: Option<i32>) -> String {
+    value.map(|id| id.to_string()).unwrap_or_default()
+}
+
+fn date_time(value: &Option<DateTime<Utc>>) -> String {
+    value.map(|time| time.to_rfc3339()).unwrap_or_default()
+}
+
+// Names of the records other records refer to, by id
+fn names<T: Entity>(
+    conn: &Connection,
+    name: impl Fn(&T) -> String,
+) -> Result<HashMap<i32, String>, JobSearchError> {
+    Ok(T::get_all_with(conn, Visibility::IncludeHidden)?
+        .iter()
+        .filter_map(|record| Some((record.id()?, name(record))))
+        .collect())
+}
+
+fn name_of(names: &HashMap<i32, String>, id: Option<i32>) -> String {
+    id.and_then(|id| names.get(&id).cloned())
+        .unwrap_or_default()
+}
+
+fn rows(conn: &Connection, table: CsvTable) -> Result<Vec<Vec<String>>, JobSearchError> {
+    let all = Visibility::IncludeHidden;
+
+    let rows = match table {
+        CsvTable::Companies => Company::get_all_with(conn, all)?
+            .iter()
+            .map(|company| {
+                vec![
+                    id(company.id),
+                    company.name.clone(),
+                    text(&company.address),
+                    text(&company.website),
+                    text(&company.phone),
+                    date_time(&company.created_date),
+                    date_time(&company.last_updated),
+                    company.hide.to_string(),
+                ]
+            })
+            .collect(),
+        CsvTable::JobPostings => JobPosting::get_all_with(conn, all)?
+            .iter()
+            .map(|job_posting| {
+                vec![
+                    id(job_posting.id),
+                    job_posting.link.clone(),
+                    text(&job_posting.description),
+                    date_time(&job_posting.created_date),
+                    date_time(&job_posting.last_updated),
+                    job_posting.hide.to_string(),
+                ]
+            })
+            .collect(),
+        CsvTable::Contacts => {
+            let contact_types =
+                names(conn, |contact_type: &ContactType| contact_type.name.clone())?;
+
+            Contact::get_all_with(conn, all)?
+                .iter()
+                .map(|contact| {
+                    vec![
+                        id(contact.id),
+                        contact.name.clone(),
+                        text(&contact.email),
+                        text(&contact.phone),
+                        text(&contact.description),
+                        name_of(&contact_types, contact.type_id),
+                        date_time(&contact.created_date),
+                        date_time(&contact.last_updated),
+                        contact.hide.to_string(),
+                    ]
+                })
+                .collect()
+        }
+        CsvTable::ContactTypes => ContactType::get_all_with(conn, all)?
+            .iter()
+            .map(|contact_type| {
+                vec![
+                    id(contact_type.id),
+                    contact_type.name.clone(),
+                    date_time(&contact_type.last_updated),
+                    contact_type.hide.to_string(),
+                ]
+            })
+            .collect(),
+        CsvTable::InterviewTypes => InterviewType::get_all_with(conn, all)?
+            .iter()
+            .map(|interview_type| {
+                vec![
+                    id(interview_type.id),
+                    interview_type.name.clone(),
+                    date_time(&interview_type.last_updated),
+                    interview_type.hide.to_string(),
+                ]
+            })
+            .collect(),
+        CsvTable::Applications => {
+            let companies = names(conn, |company: &Company| company.name.clone())?;
+            let job_postings = names(conn, |job_posting: &JobPosting| job_posting.link.clone())?;
+            let contacts = names(conn, |contact: &Contact| contact.name.clone())?;
+
+            Application::get_all_with(conn, all)?
+                .iter()
+                .map(|application| {
+                    vec![
+                        id(application.id),
+                        name_of(&companies, application.company_id),
+                        name_of(&job_postings, application.job_posting_id),
+                        name_of(&contacts, application.contact_id),
+                        application
+                            .date_applied
+                            .map(|date| date.to_string())
+                            .unwrap_or_default(),
+                        application.status.to_string(),
+                        date_time(&application.last_updated),
+                        application.hide.to_string(),
+                    ]
+                })
+                .collect()
+        }
+        CsvTable::Interviews => {
+            let companies = names(conn, |company: &Company| company.name.clone())?;
+            let interview_types = names(conn, |interview_type: &InterviewType| {
+                interview_type.name.clone()
+            })?;
+            let job_postings = names(conn, |job_posting: &JobPosting| job_posting.link.clone())?;
+            let contacts = names(conn, |contact: &Contact| contact.name.clone())?;
+
+            Interview::get_all_with(conn, all)?
+                .iter()
+                .map(|interview| {
+                    vec![
+                        id(interview.id),
+                        date_time(&interview.date),
+                        name_of(&companies, Some(interview.company_id)),
+                        name_of(&interview_types, Some(interview.interview_type_id)),
+                        name_of(&contacts, interview.contact_id),
+                        name_of(&job_postings, interview.job_posting_id),
+                        text(&interview.description),
+                        interview.cancelled.to_string(),
+                        interview.completed.to_string(),
+                        date_time(&interview.created_date),
+                        date_time(&interview.last_updated),
+                        interview.hide.to_string(),
+                    ]
+                })
+                .collect()
+        }
+    };
+
+    Ok(rows)
+}
+
+/// Writes every record of `table`, hidden ones included, as CSV with a
+/// header row. Times are in UTC, in RFC 3339 format.
+pub(crate) fn export<W: Write>(
+    conn: &Connection,
+    table: CsvTable,
+    out: W,
+) -> Result<(), JobSearchError> {
+    let mut writer = Writer::from_writer(out);
+
+    writer.write_record(table.columns())?;
+    for row in rows(conn, table)? {
+        writer.write_record(&row)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+// A row of an imported file, by column of the table
+struct Record<'a> {
+    values: HashMap<&'static str, &'a str>,
+}
+
+impl<'a> Record<'a> {
+    fn new(columns: &[Option<&'static str>], row: &'a StringRecord) -> Record<'a> {
+        let values = columns
+            .iter()
+            .zip(row.iter())
+            .filter_map(|(column, value)| Some(((*column)?, value)))
+            .collect();
+
+        Record { values }
+    }
+
+    // None if the file has no such column. Empty cells are Some(None).
+    fn text(&self, column: &str) -> Option<Option<String>> {
+        self.values
+            .get(column)
+            .map(|value| Some(value.trim().to_string()).filter(|value| !value.is_empty()))
+    }
+
+    fn required(&self, column: &str) -> Result<String, JobSearchError> {
+        self.text(column)
+            .flatten()
+            .ok_or_else(|| JobSearchError::InvalidInput(format!("{} is required", column)))
+    }
+
+    // Cells that are empty or missing leave the record as it is
+    fn parse<T>(
+        &self,
+        column: &str,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<Option<T>, JobSearchError> {
+        match self.text(column).flatten() {
+            Some(value) => parse(&value).map(Some).map_err(|message| {
+                JobSearchError::InvalidInput(format!("{}: {}", column, message))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    // Looks up the record a column names. Empty cells clear the reference.
+    fn reference(
+        &self,
+        column: &str,
+        find: impl Fn(&str) -> Result<i32, JobSearchError>,
+    ) -> Result<Option<Option<i32>>, JobSearchError> {
+        match self.text(column) {
+            Some(Some(name)) => Ok(Some(Some(find(&name)?))),
+            Some(None) => Ok(Some(None)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => Ok(true),
+        "false" | "no" | "n" | "0" => Ok(false),
+        _ => Err(format!("{:?} is not true or false", value)),
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("{:?} is not a YYYY-MM-DD date", value))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    timestamp::parse(value).map_err(|_| format!("{:?} is not a timestamp", value))
+}
+
+fn missing(entity: &str, name: &str) -> JobSearchError {
+    JobSearchError::InvalidInput(format!("there is no {} named {:?}", entity, name))
+}
+
+fn company_id(conn: &Connection, name: &str) -> Result<i32, JobSearchError> {
+    Company::get_by_name(conn, name)?
+        .and_then(|company| company.id)
+        .ok_or_else(|| missing(Company::NAME, name))
+}
+
+fn job_posting_id(conn: &Connection, link: &str) -> Result<i32, JobSearchError> {
+    JobPosting::get_by_link(conn, link)?
+        .and_then(|job_posting| job_posting.id)
+        .ok_or_else(|| JobSearchError::InvalidInput(format!("there is no job posting at {}", link)))
+}
+
+// Contact names are not unique, so a name has to pick out a single contact
+fn contact_id(conn: &Connection, name: &str) -> Result<i32, JobSearchError> {
+    let contacts = Contact::query(
+        conn,
+        &format!(
+            "{} WHERE contacts.name = (?1) COLLATE NOCASE",
+            Contact::SELECT
+        ),
+        params![name],
+    )?;
+
+    match contacts.as_slice() {
+        [contact] => Ok(contact.id.unwrap_or_default()),
+        [] => Err(missing(Contact::NAME, name)),
+        _ => Err(JobSearchError::InvalidInput(format!(
+            "{} contacts are named {:?}",
+            contacts.len(),
+            name
+        ))),
+    }
+}
+
+fn contact_type_id(conn: &Connection, name: &str) -> Result<i32, JobSearchError> {
+    ContactType::get_by_name(conn, name)?
+        .and_then(|contact_type| contact_type.id)
+        .ok_or_else(|| missing(ContactType::NAME, name))
+}
+
+fn interview_type_id(conn: &Connection, name: &str) -> Result<i32, JobSearchError> {
+    InterviewType::get_by_name(conn, name)?
+        .and_then(|interview_type| interview_type.id)
+        .ok_or_else(|| missing(InterviewType::NAME, name))
+}
+
+enum Outcome {
+    Created(i32),
+    Updated(i32),
+}
+
+// Adds `record`, or updates it if `existing`
+fn save<T: Entity>(
+    conn: &Connection,
+    record: &mut T,
+    existing: bool,
+) -> Result<Outcome, JobSearchError> {
+    if existing {
+        record.update_db(conn)?;
+
+        Ok(Outcome::Updated(record.id().unwrap_or_default()))
+    } else {
+        record.add_to_db(conn)?;
+
+        Ok(Outcome::Created(record.id().unwrap_or_default()))
+    }
+}
+
+fn import_company(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let name = record.required("name")?;
+
+    let existing = Company::get_by_name(conn, &name)?;
+    let found = existing.is_some();
+    let mut company = existing.unwrap_or_else(|| Company::new(name, None, None, None));
+
+    if let Some(address) = record.text("address") {
+        company.address = address;
+    }
+    if let Some(website) = record.text("website") {
+        company.website = website;
+    }
+    if let Some(phone) = record.text("phone") {
+        company.phone = phone;
+    }
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        company.hide = hide;
+    }
+
+    save(conn, &mut company, found)
+}
+
+fn import_job_posting(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let link = record.required("link")?;
+
+    let existing = JobPosting::get_by_link(conn, &link)?;
+    let found = existing.is_some();
+    let mut job_posting = existing.unwrap_or_else(|| JobPosting::new(link));
+
+    if let Some(description) = record.text("description") {
+        job_posting.description = description;
+    }
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        job_posting.hide = hide;
+    }
+
+    save(conn, &mut job_posting, found)
+}
+
+fn import_contact_type(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let name = record.required("name")?;
+
+    let existing = ContactType::get_by_name(conn, &name)?;
+    let found = existing.is_some();
+    let mut contact_type = existing.unwrap_or_else(|| ContactType::new(name));
+
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        contact_type.hide = hide;
+    }
+
+    save(conn, &mut contact_type, found)
+}
+
+fn import_interview_type(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let name = record.required("name")?;
+
+    let existing = InterviewType::get_by_name(conn, &name)?;
+    let found = existing.is_some();
+    let mut interview_type = existing.unwrap_or_else(|| InterviewType::new(name));
+
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        interview_type.hide = hide;
+    }
+
+    save(conn, &mut interview_type, found)
+}
+
+// Contacts are the same if they have the same name and type, and the same
+// email when the name and type alone are shared
+fn find_contact(
+    conn: &Connection,
+    name: &str,
+    type_id: i32,
+    email: Option<&str>,
+) -> Result<Option<Contact>, JobSearchError> {
+    let mut contacts = Contact::query(
+        conn,
+        &format!(
+            "{} WHERE contacts.name = (?1) COLLATE NOCASE AND contacts.type_id = (?2) ORDER BY {}",
+            Contact::SELECT,
+            Contact::ORDER_BY
+        ),
+        params![name, type_id],
+    )?;
+
+    if contacts.len() > 1 {
+        contacts.retain(|contact| {
+            contact.email.as_deref().map(str::to_lowercase) == email.map(str::to_lowercase)
+        });
+    }
+
+    match contacts.len() {
+        0 | 1 => Ok(contacts.pop()),
+        count => Err(JobSearchError::InvalidInput(format!(
+            "{} contacts are named {:?}",
+            count, name
+        ))),
+    }
+}
+
+// Applications have no name, so one to the same company for the same
+// posting on the same day is taken to be the same
+fn find_application(
+    conn: &Connection,
+    company_id: i32,
+    job_posting_id: Option<i32>,
+    date_applied: Option<NaiveDate>,
+) -> Result<Option<Application>, JobSearchError> {
+    let applications = Application::query(
+        conn,
+        &format!(
+            "{} WHERE company_id = (?1) AND job_posting_id IS (?2) AND date_applied IS (?3) \
+             ORDER BY id LIMIT 1",
+            Application::SELECT
+        ),
+        params![company_id, job_posting_id, date_applied],
+    )?;
+
+    Ok(applications.into_iter().next())
+}
+
+// Likewise an interview with the same company, type and time
+fn find_interview(
+    conn: &Connection,
+    company_id: i32,
+    interview_type_id: i32,
+    date: Option<DateTime<Utc>>,
+) -> Result<Option<Interview>, JobSearchError> {
+    let interviews = Interview::query(
+        conn,
+        &format!(
+            "{} WHERE interviews.company_id = (?1) AND interviews.interview_type_id = (?2) \
+             AND datetime(interviews.date) IS datetime(?3) ORDER BY interviews.id LIMIT 1",
+            Interview::SELECT
+        ),
+        params![company_id, interview_type_id, timestamp::to_sql(&date)],
+    )?;
+
+    Ok(interviews.into_iter().next())
+}
+
+fn import_contact(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let name = record.required("name")?;
+    let type_id = contact_type_id(conn, &record.required("contact_type")?)?;
+    let email = record.text("email");
+
+    let existing = find_contact(conn, &name, type_id, email.clone().flatten().as_deref())?;
+    let found = existing.is_some();
+    let mut contact = existing.unwrap_or_else(|| Contact::new(name, None, None, None, type_id));
+
+    if let Some(email) = email {
+        contact.email = email;
+    }
+    if let Some(phone) = record.text("phone") {
+        contact.phone = phone;
+    }
+    if let Some(description) = record.text("description") {
+        contact.description = description;
+    }
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        contact.hide = hide;
+    }
+
+    save(conn, &mut contact, found)
+}
+
+fn import_application(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let company_id = company_id(conn, &record.required("company")?)?;
+    let job_posting_id = record.reference("job_posting", |link| job_posting_id(conn, link))?;
+    let contact_id = record.reference("contact", |name| contact_id(conn, name))?;
+    let date_applied = record.parse("date_applied", parse_date)?;
+
+    let existing = find_application(conn, company_id, job_posting_id.flatten(), date_applied)?;
+    let found = existing.is_some();
+    let mut application = existing.unwrap_or_else(|| {
+        let mut application = Application::new(company_id, None, None);
+        application.date_applied = date_applied;
+        application
+    });
+
+    if let Some(job_posting_id) = job_posting_id {
+        application.job_posting_id = job_posting_id;
+    }
+    if let Some(contact_id) = contact_id {
+        application.contact_id = contact_id;
+    }
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        application.hide = hide;
+    }
+
+    match record.parse("status", ApplicationStatus::from_str)? {
+        // An update leaves the status alone, so a new one has to be a
+        // change in the pipeline
+        Some(status) if found && status != application.status => {
+            let outcome = save(conn, &mut application, found)?;
+            application.change_status(conn, status)?;
+
+            Ok(outcome)
+        }
+        Some(status) => {
+            application.status = status;
+
+            save(conn, &mut application, found)
+        }
+        None => save(conn, &mut application, found),
+    }
+}
+
+fn import_interview(conn: &Connection, record: &Record) -> Result<Outcome, JobSearchError> {
+    let company_id = company_id(conn, &record.required("company")?)?;
+    let interview_type_id = interview_type_id(conn, &record.required("interview_type")?)?;
+    let date = record.parse("date", parse_time)?;
+
+    let existing = find_interview(conn, company_id, interview_type_id, date)?;
+    let found = existing.is_some();
+    let mut interview = match existing {
+        Some(interview) => interview,
+        None => {
+            let mut interview = Interview::new(interview_type_id, company_id, date);
+            // Applications have no name to refer to them by. The latest one
+            // to the company is the likeliest.
+            interview.application_id = Application::get_by_company(conn, company_id)?
+                .pop()
+                .and_then(|application| application.id);
+            interview
+        }
+    };
+
+    if let Some(contact_id) = record.reference("contact", |name| contact_id(conn, name))? {
+        interview.contact_id = contact_id;
+    }
+    if let Some(job_posting_id) =
+        record.reference("job_posting", |link| job_posting_id(conn, link))?
+    {
+        interview.job_posting_id = job_posting_id;
+    }
+    if let Some(description) = record.text("description") {
+        interview.description = description;
+    }
+    if let Some(cancelled) = record.parse("cancelled", parse_bool)? {
+        interview.cancelled = cancelled;
+    }
+    if let Some(completed) = record.parse("completed", parse_bool)? {
+        interview.completed = completed;
+    }
+    if let Some(hide) = record.parse("hide", parse_bool)? {
+        interview.hide = hide;
+    }
+
+    save(conn, &mut interview, found)
+}
+
+fn import_record(
+    conn: &Connection,
+    table: CsvTable,
+    record: &Record,
+) -> Result<Outcome, JobSearchError> {
+    match table {
+        CsvTable::Companies => import_company(conn, record),
+        CsvTable::JobPostings => import_job_posting(conn, record),
+        CsvTable::Contacts => import_contact(conn, record),
+        CsvTable::ContactTypes => import_contact_type(conn, record),
+        CsvTable::InterviewTypes => import_interview_type(conn, record),
+        CsvTable::Applications => import_application(conn, record),
+        CsvTable::Interviews => import_interview(conn, record),
+    }
+}
+
+// The column of the table for each header of the file, if any, and the
+// headers that match no column
+fn map_headers(
+    table: CsvTable,
+    headers: &StringRecord,
+    mapping: &HashMap<String, String>,
+) -> Result<(Vec<Option<&'static str>>, Vec<String>), JobSearchError> {
+    let column = |name: &str| {
+        table
+            .columns()
+            .iter()
+            .find(|column| **column == normalize(name))
+            .copied()
+    };
+
+    let mapping: HashMap<String, &'static str> = mapping
+        .iter()
+        .map(|(header, name)| {
+            let target = column(name).ok_or_else(|| {
+                JobSearchError::InvalidInput(format!(
+                    "{} has no column {:?}, only {}",
+                    table,
+                    name,
+                    table.columns().join(", ")
+                ))
+            })?;
+
+            Ok((normalize(header), target))
+        })
+        .collect::<Result<_, JobSearchError>>()?;
+
+    let mut columns = Vec::new();
+    let mut ignored = Vec::new();
+
+    for header in headers {
+        let target = mapping
+            .get(&normalize(header))
+            .copied()
+            .or_else(|| column(header));
+
+        match target {
+            Some(target) if columns.contains(&Some(target)) => {
+                return Err(JobSearchError::InvalidInput(format!(
+                    "more than one column of the file is {}",
+                    target
+                )));
+            }
+            Some(target) if READ_ONLY.contains(&target) => columns.push(None),
+            Some(target) => columns.push(Some(target)),
+            None => {
+                ignored.push(header.to_string());
+                columns.push(None);
+            }
+        }
+    }
+
+    for required in table.required_columns() {
+        if !columns.contains(&Some(required)) {
+            return Err(JobSearchError::InvalidInput(format!(
+                "the file has no {} column",
+                required
+            )));
+        }
+    }
+
+    Ok((columns, ignored))
+}
+
+/// Imports each row of a CSV file with a header row into `table`.
+///
+/// Companies are matched by name, job postings by link, contact and
+/// interview types by name, contacts by name and type, applications by
+/// company, job posting and date and interviews by company, type and date,
+/// and updated with the columns the file has. Rows that fail are left out
+/// and reported, and the rest are imported.
+pub(crate) fn import<R: Read>(
+    conn: &Connection,
+    table: CsvTable,
+    input: R,
+    options: &CsvImportOptions,
+) -> Result<CsvImport, JobSearchError> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .flexible(true)
+        .from_reader(input);
+
+    let (columns, ignored_columns) = map_headers(table, reader.headers()?, &options.columns)?;
+
+    let run = || {
+        let mut report = CsvImport {
+            ignored_columns,
+            ..CsvImport::default()
+        };
+
+        for row in reader.records() {
+            let row = match row {
+                Ok(row) => row,
+                Err(err) => {
+                    report.errors.push(RowError {
+                        line: err.position().map_or(0, |position| position.line()),
+                        message: JobSearchError::from(err).to_string(),
+                    });
+                    continue;
+                }
+            };
+            let line = row.position().map_or(0, |position| position.line());
+
+            let record = Record::new(&columns, &row);
+            match with_savepoint(conn, || import_record(conn, table, &record)) {
+                Ok(Outcome::Created(id)) => report.created.push(id),
+                Ok(Outcome::Updated(id)) => report.updated.push(id),
+                Err(err) => report.errors.push(RowError {
+                    line,
+                    message: err.to_string(),
+                }),
+            }
+        }
+
+        Ok(report)
+    };
+
+    if options.dry_run {
+        with_rollback(conn, run)
+    } else {
+        with_savepoint(conn, run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_in_memory_db;
+
+    fn export_to_string(conn: &Connection, table: CsvTable) -> String {
+        let mut out = Vec::new();
+        export(conn, table, &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    fn import_str(
+        conn: &Connection,
+        table: CsvTable,
+        csv: &str,
+        options: &CsvImportOptions,
+    ) -> CsvImport {
+        import(conn, table, csv.as_bytes(), options).unwrap()
+    }
+
+    #[test]
+    fn test_export_resolves_references() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Google, Inc.".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut job_posting = JobPosting::new("https://google.com/jobs/1".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+
+        let mut application = Application::new(company.id.unwrap(), job_posting.id, None);
+        application.date_applied = NaiveDate::from_ymd_opt(2020, 5, 1);
+        application.add_to_db(&conn).unwrap();
+        application.archive(&conn).unwrap();
+
+        let csv = export_to_string(&conn, CsvTable::Applications);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "id,company,job_posting,contact,date_applied,status,last_updated,hide"
+        );
+        assert!(lines[1]
+            .starts_with("1,\"Google, Inc.\",https://google.com/jobs/1,,2020-05-01,applied,"));
+        assert!(lines[1].ends_with(",true"));
+    }
+
+    #[test]
+    fn test_import_upserts_companies() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new(
+            "Google".to_string(),
+            Some("Mountain View".to_string()),
+            None,
+            None,
+        );
+        company.add_to_db(&conn).unwrap();
+
+        let csv = "Company Name,Website,Notes\n\
+                   google,https://google.com,x\n\
+                   Mozilla,https://mozilla.org,y\n";
+        let mut options = CsvImportOptions::default();
+        options
+            .columns
+            .insert("Company Name".to_string(), "name".to_string());
+
+        let report = import_str(&conn, CsvTable::Companies, csv, &options);
+
+        assert_eq!(report.updated, vec![company.id.unwrap()]);
+        assert_eq!(report.created.len(), 1);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.ignored_columns, vec!["Notes".to_string()]);
+
+        let google = Company::get_by_id(&conn, company.id.unwrap()).unwrap();
+        assert_eq!(google.name, "Google");
+        assert_eq!(google.website.as_deref(), Some("https://google.com"));
+        // The file has no address column, so it is kept
+        assert_eq!(google.address.as_deref(), Some("Mountain View"));
+
+        // Importing again updates the same companies
+        let report = import_str(&conn, CsvTable::Companies, csv, &options);
+        assert!(report.created.is_empty());
+        assert_eq!(report.updated.len(), 2);
+        assert_eq!(Company::count(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_import_reports_row_errors() {
+        let conn = create_in_memory_db().unwrap();
+
+        Company::new("Google".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Allen".to_string(),
+            None,
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+
+        let csv = "company,date_applied,status,contact\n\
+                   Google,2020-05-01,screening,allen\n\
+                   Mozilla,2020-05-02,applied,\n\
+                   Google,yesterday,applied,\n\
+                   Google,,unknown,\n";
+
+        let report = import_str(
+            &conn,
+            CsvTable::Applications,
+            csv,
+            &CsvImportOptions::default(),
+        );
+
+        assert_eq!(report.created, vec![1]);
+        let lines: Vec<u64> = report.errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![3, 4, 5]);
+        assert!(report.errors[0]
+            .message
+            .contains("no company named \"Mozilla\""));
+        assert!(report.errors[1].message.contains("date_applied"));
+
+        let application = Application::get_by_id(&conn, 1).unwrap();
+        assert_eq!(application.status, ApplicationStatus::Screening);
+        assert_eq!(application.contact_id, contact.id);
+        assert_eq!(
+            application.date_applied,
+            NaiveDate::from_ymd_opt(2020, 5, 1)
+        );
+    }
+
+    #[test]
+    fn test_import_dry_run() {
+        let conn = create_in_memory_db().unwrap();
+
+        let options = CsvImportOptions {
+            dry_run: true,
+            ..CsvImportOptions::default()
+        };
+        let report = import_str(
+            &conn,
+            CsvTable::JobPostings,
+            "link,description\nhttps://example.com/1,Rust\n,No link\n",
+            &options,
+        );
+
+        assert_eq!(report.created, vec![1]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(JobPosting::count(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_import_invalid_headers() {
+        let conn = create_in_memory_db().unwrap();
+
+        let result = import(
+            &conn,
+            CsvTable::Contacts,
+            "name,email\nAllen,allen@mozilla.org\n".as_bytes(),
+            &CsvImportOptions::default(),
+        );
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+
+        let mut options = CsvImportOptions::default();
+        options
+            .columns
+            .insert("Who".to_string(), "nickname".to_string());
+        let result = import(&conn, CsvTable::Companies, "name\n".as_bytes(), &options);
+        assert!(matches!(result, Err(JobSearchError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        Contact::new(
+            "Allen".to_string(),
+            Some("allen@mozilla.org".to_string()),
+            None,
+            Some("Met at a meetup".to_string()),
+            contact_type.id.unwrap(),
+        )
+        .add_to_db(&conn)
+        .unwrap();
+
+        let csv = export_to_string(&conn, CsvTable::Contacts);
+
+        let other = create_in_memory_db().unwrap();
+        ContactType::new("recruiter".to_string())
+            .add_to_db(&other)
+            .unwrap();
+
+        let report = import_str(
+            &other,
+            CsvTable::Contacts,
+            &csv,
+            &CsvImportOptions::default(),
+        );
+        assert_eq!(report.created, vec![1]);
+        assert!(report.ignored_columns.is_empty());
+
+        let contact = Contact::get_by_id(&other, 1).unwrap();
+        assert_eq!(contact.name, "Allen");
+        assert_eq!(contact.description.as_deref(), Some("Met at a meetup"));
+    }
+
+    #[test]
+    fn test_reimport_export() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut job_posting = JobPosting::new("https://mozilla.org/jobs/1".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Onsite".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+
+        let mut allen = Contact::new(
+            "Allen".to_string(),
+            None,
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        allen.add_to_db(&conn).unwrap();
+        // Two people with the same name, told apart by their email
+        for email in &["sam@mozilla.org", "sam@example.com"] {
+            Contact::new(
+                "Sam".to_string(),
+                Some(email.to_string()),
+                None,
+                None,
+                contact_type.id.unwrap(),
+            )
+            .add_to_db(&conn)
+            .unwrap();
+        }
+
+        let mut application = Application::new(company.id.unwrap(), job_posting.id, allen.id);
+        application.add_to_db(&conn).unwrap();
+        application
+            .change_status(&conn, ApplicationStatus::Screening)
+            .unwrap();
+        Application::new(company.id.unwrap(), None, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let mut interview = Interview::new(
+            interview_type.id.unwrap(),
+            company.id.unwrap(),
+            Some("2020-05-08T10:00:00Z".parse().unwrap()),
+        );
+        interview.contact_id = allen.id;
+        interview.add_to_db(&conn).unwrap();
+
+        let counts = |conn: &Connection| {
+            (
+                Contact::count(conn).unwrap(),
+                Application::count(conn).unwrap(),
+                Interview::count(conn).unwrap(),
+            )
+        };
+        let before = counts(&conn);
+
+        for table in CsvTable::ALL.iter() {
+            let csv = export_to_string(&conn, *table);
+            let report = import_str(&conn, *table, &csv, &CsvImportOptions::default());
+
+            assert!(report.created.is_empty(), "{}", table);
+            assert!(report.errors.is_empty(), "{}: {:?}", table, report.errors);
+        }
+
+        assert_eq!(counts(&conn), before);
+        let application = Application::get_by_id(&conn, application.id.unwrap()).unwrap();
+        assert_eq!(application.status, ApplicationStatus::Screening);
+        assert_eq!(application.job_posting_id, job_posting.id);
+    }
+}
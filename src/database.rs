@@ -0,0 +1,566 @@
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use chrono::Utc;
+use rusqlite::Connection;
+
+use crate::application::Application;
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::contact_type::ContactType;
+use crate::csv_io::{self, CsvImport, CsvImportOptions, CsvTable};
+use crate::entity::{Entity, Visibility};
+use crate::errors::JobSearchError;
+use crate::filter::{Filter, Page};
+use crate::ical::{self, IcsImport, IcsImportOptions, IcsOptions};
+use crate::interview::Interview;
+use crate::interview_type::InterviewType;
+use crate::job_posting::JobPosting;
+use crate::migrations;
+use crate::note::Note;
+use crate::reminder::Reminder;
+use crate::search::{self, SearchHit};
+use crate::snapshot::{self, SnapshotImport};
+use crate::tag::Tag;
+
+/// Where the schema of a newly created database comes from.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Schema {
+    /// The migrations compiled into the library.
+    #[default]
+    Bundled,
+    /// SQL supplied by the caller, run as-is instead of the bundled
+    /// migrations. It is responsible for setting `PRAGMA user_version`;
+    /// any bundled migration above that version is applied when the
+    /// database is opened again.
+    Custom(String),
+}
+
+/// An open job search database.
+///
+/// Foreign keys and triggers are always enabled on the underlying
+/// connection. Records are read and written through the typed tables
+/// returned by `companies`, `job_postings`, `contacts` and friends.
+#[derive(Debug)]
+pub struct Database {
+    conn: Connection,
+}
+
+/// The records of one type stored in a `Database`.
+#[derive(Debug)]
+pub struct Table<'a, T> {
+    conn: &'a Connection,
+    entity: PhantomData<T>,
+}
+
+impl<'a, T> Table<'a, T> {
+    fn new(conn: &'a Connection) -> Table<'a, T> {
+        Table {
+            conn,
+            entity: PhantomData,
+        }
+    }
+
+    pub(crate) fn conn(&self) -> &'a Connection {
+        self.conn
+    }
+}
+
+impl<'a, T: Entity> Table<'a, T> {
+    pub fn add(&self, entity: &mut T) -> Result<(), JobSearchError> {
+        entity.add_to_db(self.conn)
+    }
+
+    pub fn update(&self, entity: &mut T) -> Result<(), JobSearchError> {
+        entity.update_db(self.conn)
+    }
+
+    /// Fails with a `ForeignKeyViolation` while other records still point to
+    /// the one being deleted.
+    pub fn delete(&self, entity: &mut T) -> Result<(), JobSearchError> {
+        entity.delete_from_db(self.conn)
+    }
+
+    /// Hides the record from `get_all` and `count` without deleting it.
+    pub fn archive(&self, entity: &mut T) -> Result<(), JobSearchError> {
+        entity.archive(self.conn)
+    }
+
+    pub fn unarchive(&self, entity: &mut T) -> Result<(), JobSearchError> {
+        entity.unarchive(self.conn)
+    }
+
+    pub fn get_by_id(&self, id: i32) -> Result<T, JobSearchError> {
+        T::get_by_id(self.conn, id)
+    }
+
+    /// Every record that is not archived.
+    pub fn get_all(&self) -> Result<Vec<T>, JobSearchError> {
+        T::get_all(self.conn)
+    }
+
+    pub fn get_all_with(&self, visibility: Visibility) -> Result<Vec<T>, JobSearchError> {
+        T::get_all_with(self.conn, visibility)
+    }
+
+    /// The number of records that are not archived.
+    pub fn count(&self) -> Result<i64, JobSearchError> {
+        T::count(self.conn)
+    }
+
+    pub fn count_with(&self, visibility: Visibility) -> Result<i64, JobSearchError> {
+        T::count_with(self.conn, visibility)
+    }
+
+    /// One page of the records matching `filter`. Only the page is read
+    /// from the database.
+    pub fn list(&self, filter: &Filter) -> Result<Page<T>, JobSearchError> {
+        T::list(self.conn, filter)
+    }
+
+    pub fn count_matching(&self, filter: &Filter) -> Result<i64, JobSearchError> {
+        T::count_matching(self.conn, filter)
+    }
+}
+
+impl Database {
+    /// Creates a new database file with the bundled schema.
+    ///
+    /// Fails if something already exists at `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Database, JobSearchError> {
+        Database::create_with_schema(path, Schema::Bundled)
+    }
+
+    pub fn create_with_schema<P: AsRef<Path>>(
+        path: P,
+        schema: Schema,
+    ) -> Result<Database, JobSearchError> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            return Err(JobSearchError::DatabaseExists(path.to_path_buf()));
+        }
+
+        let conn = Connection::open(path)?;
+
+        Database::init(conn, schema)
+    }
+
+    /// Opens an existing database, applying any pending migrations.
+    ///
+    /// Fails if there is no file at `path` or if the database was written by
+    /// a newer version of the library.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Database, JobSearchError> {
+        let path = path.as_ref();
+
+        if !path.is_file() {
+            return Err(JobSearchError::DatabaseNotFound(path.to_path_buf()));
+        }
+
+        let conn = Connection::open(path)?;
+
+        Database::init(conn, Schema::Bundled)
+    }
+
+    /// Opens the database at `path`, creating it with the bundled schema if
+    /// there is nothing there yet.
+    pub fn open_or_create<P: AsRef<Path>>(path: P) -> Result<Database, JobSearchError> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            Database::open(path)
+        } else {
+            Database::create(path)
+        }
+    }
+
+    pub fn open_in_memory() -> Result<Database, JobSearchError> {
+        Database::open_in_memory_with_schema(Schema::Bundled)
+    }
+
+    pub fn open_in_memory_with_schema(schema: Schema) -> Result<Database, JobSearchError> {
+        let conn = Connection::open_in_memory()?;
+
+        Database::init(conn, schema)
+    }
+
+    fn init(mut conn: Connection, schema: Schema) -> Result<Database, JobSearchError> {
+        match schema {
+            Schema::Bundled => {
+                migrations::migrate(&mut conn)?;
+            }
+            Schema::Custom(sql) => {
+                conn.execute_batch(&sql)?;
+            }
+        }
+
+        crate::enable_config_options(&conn)?;
+
+        Ok(Database { conn })
+    }
+
+    /// The schema version the database is on.
+    pub fn version(&self) -> Result<u32, JobSearchError> {
+        migrations::current_version(&self.conn)
+    }
+
+    /// The table of any entity, for code that works with records in general.
+    pub fn table<T: Entity>(&self) -> Table<'_, T> {
+        Table::new(&self.conn)
+    }
+
+    pub fn companies(&self) -> Table<'_, Company> {
+        Table::new(&self.conn)
+    }
+
+    pub fn job_postings(&self) -> Table<'_, JobPosting> {
+        Table::new(&self.conn)
+    }
+
+    pub fn contacts(&self) -> Table<'_, Contact> {
+        Table::new(&self.conn)
+    }
+
+    pub fn applications(&self) -> Table<'_, Application> {
+        Table::new(&self.conn)
+    }
+
+    pub fn contact_types(&self) -> Table<'_, ContactType> {
+        Table::new(&self.conn)
+    }
+
+    pub fn interviews(&self) -> Table<'_, Interview> {
+        Table::new(&self.conn)
+    }
+
+    pub fn interview_types(&self) -> Table<'_, InterviewType> {
+        Table::new(&self.conn)
+    }
+
+    pub fn reminders(&self) -> Table<'_, Reminder> {
+        Table::new(&self.conn)
+    }
+
+    pub fn notes(&self) -> Table<'_, Note> {
+        Table::new(&self.conn)
+    }
+
+    /// The tags themselves. Records are tagged through their own table,
+    /// e.g. `db.companies().tag(id, "fintech")`.
+    pub fn tags(&self) -> Table<'_, Tag> {
+        Table::new(&self.conn)
+    }
+
+    /// Finds the companies, job postings, contacts, interviews and notes
+    /// whose text matches `query`, best matches first. Hidden records are left out.
+    ///
+    /// `query` uses the SQLite FTS5 syntax, e.g. `kubernetes AND remote`,
+    /// `"site reliability"` or `recruit*`. A malformed query fails with
+    /// `InvalidInput`.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>, JobSearchError> {
+        search::search(&self.conn, query, None)
+    }
+
+    /// Like `search`, returning at most `limit` hits.
+    pub fn search_limit(&self, query: &str, limit: u32) -> Result<Vec<SearchHit>, JobSearchError> {
+        search::search(&self.conn, query, Some(limit))
+    }
+
+    /// Writes the scheduled interviews as an iCalendar (RFC 5545) file, one
+    /// event per interview. Events keep their UID across exports, so
+    /// importing the file again updates them instead of adding copies.
+    pub fn export_ics<W: Write>(
+        &self,
+        out: &mut W,
+        options: &IcsOptions,
+    ) -> Result<(), JobSearchError> {
+        ical::export(&self.conn, out, options, Utc::now())
+    }
+
+    /// Adds an interview for each event of an iCalendar file, matching
+    /// events to companies, contacts and interview types by name or email.
+    /// Events imported before are found again by their UID and updated, so
+    /// importing the same file twice adds nothing.
+    pub fn import_ics<R: Read>(
+        &self,
+        input: &mut R,
+        options: &IcsImportOptions,
+    ) -> Result<IcsImport, JobSearchError> {
+        let mut calendar = Vec::new();
+        input.read_to_end(&mut calendar)?;
+
+        ical::import(&self.conn, &calendar, options)
+    }
+
+    /// Writes every record of `table`, hidden ones included, as CSV. Other
+    /// records are referred to by name, or by link for job postings.
+    pub fn export_csv<W: Write>(&self, table: CsvTable, out: &mut W) -> Result<(), JobSearchError> {
+        csv_io::export(&self.conn, table, out)
+    }
+
+    /// Imports the rows of a CSV file into `table`. Records that already
+    /// exist, such as a company by name or a job posting by link, are
+    /// updated, so importing an export adds nothing. Rows that fail are
+    /// reported and the others imported, unless it is a dry run, which
+    /// saves nothing.
+    pub fn import_csv<R: Read>(
+        &self,
+        table: CsvTable,
+        input: &mut R,
+        options: &CsvImportOptions,
+    ) -> Result<CsvImport, JobSearchError> {
+        csv_io::import(&self.conn, table, input, options)
+    }
+
+    /// Writes every record as a versioned JSON document, to be merged into
+    /// another database with `import_snapshot`.
+    pub fn export_snapshot<W: Write>(&self, out: &mut W) -> Result<(), JobSearchError> {
+        snapshot::export(&self.conn, out, Utc::now())
+    }
+
+    /// Merges a snapshot from `export_snapshot` into this database. Records
+    /// that already exist, by their unique name, link or event UID, are
+    /// updated if the snapshot's copy is newer. Nothing is imported if any
+    /// part of the snapshot is invalid.
+    pub fn import_snapshot<R: Read>(
+        &self,
+        input: &mut R,
+    ) -> Result<SnapshotImport, JobSearchError> {
+        snapshot::import(&self.conn, input)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn conn(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rusqlite::config::DbConfig;
+    use rusqlite::NO_PARAMS;
+
+    use super::*;
+    use crate::application_status::ApplicationStatus;
+    use crate::reminder::FollowUp;
+
+    #[test]
+    fn test_create_and_open() {
+        let file_name = "database_create_test.db";
+
+        let db = Database::create(file_name).unwrap();
+        assert_eq!(db.version().unwrap(), migrations::latest_version());
+        drop(db);
+
+        let db = Database::open(file_name).unwrap();
+        assert_eq!(db.version().unwrap(), migrations::latest_version());
+        drop(db);
+
+        // Cleaning up the created file
+        fs::remove_file(file_name).unwrap()
+    }
+
+    #[test]
+    fn test_open_or_create() {
+        let file_name = "database_open_or_create_test.db";
+
+        let db = Database::open_or_create(file_name).unwrap();
+        db.companies()
+            .add(&mut Company::new("Google".to_string(), None, None, None))
+            .unwrap();
+        drop(db);
+
+        let db = Database::open_or_create(file_name).unwrap();
+        assert_eq!(db.companies().count().unwrap(), 1);
+        drop(db);
+
+        // Cleaning up the created file
+        fs::remove_file(file_name).unwrap()
+    }
+
+    #[test]
+    fn test_create_existing_file() {
+        let result = Database::create("Cargo.toml");
+
+        assert!(matches!(result, Err(JobSearchError::DatabaseExists(_))));
+    }
+
+    #[test]
+    fn test_open_missing_file() {
+        let result = Database::open("does_not_exist.db");
+
+        assert!(matches!(result, Err(JobSearchError::DatabaseNotFound(_))));
+        assert!(!Path::new("does_not_exist.db").exists());
+    }
+
+    #[test]
+    fn test_open_in_memory() {
+        let db = Database::open_in_memory().unwrap();
+
+        let count: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM companies", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .unwrap();
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema = Schema::Custom("CREATE TABLE custom (id INTEGER PRIMARY KEY);".to_string());
+
+        let db = Database::open_in_memory_with_schema(schema).unwrap();
+
+        let count: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM custom", NO_PARAMS, |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(count, 0);
+        assert_eq!(db.version().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_config_options_enabled() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(db
+            .conn()
+            .db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY)
+            .unwrap());
+        assert!(db
+            .conn()
+            .db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_TRIGGER)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_tables() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut company = Company::new("company".to_string(), None, None, None);
+        db.companies().add(&mut company).unwrap();
+
+        company.website = Some("https://example.com".to_string());
+        db.companies().update(&mut company).unwrap();
+
+        let mut job_posting = JobPosting::new("https://example.com/job".to_string());
+        db.job_postings().add(&mut job_posting).unwrap();
+
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        db.contact_types().add(&mut contact_type).unwrap();
+
+        let mut contact = Contact::new(
+            "Marcus".to_string(),
+            None,
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        db.contacts().add(&mut contact).unwrap();
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        db.interview_types().add(&mut interview_type).unwrap();
+
+        let company_from_db = db.companies().get_by_id(company.id.unwrap()).unwrap();
+
+        assert_eq!(company_from_db.website, company.website);
+        assert_eq!(db.job_postings().get_all().unwrap().len(), 1);
+        assert_eq!(db.contact_types().get_all().unwrap().len(), 1);
+        assert_eq!(db.contacts().get_all().unwrap().len(), 1);
+        assert_eq!(db.interview_types().get_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_and_count() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut company = Company::new("company".to_string(), None, None, None);
+        db.table::<Company>().add(&mut company).unwrap();
+
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        db.applications().add(&mut application).unwrap();
+
+        assert_eq!(db.companies().count().unwrap(), 1);
+
+        // Still referenced by the application
+        assert!(matches!(
+            db.companies().delete(&mut company),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+
+        db.applications().delete(&mut application).unwrap();
+        db.companies().delete(&mut company).unwrap();
+
+        assert_eq!(db.applications().count().unwrap(), 0);
+        assert_eq!(db.companies().count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_follow_ups() {
+        let db = Database::open_in_memory().unwrap();
+        let follow_up = FollowUp::new(chrono::Duration::days(5));
+
+        let mut company = Company::new("company".to_string(), None, None, None);
+        db.companies().add(&mut company).unwrap();
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        db.applications().add(&mut application).unwrap();
+
+        let reminder = db
+            .applications()
+            .change_status_with_follow_up(
+                &mut application,
+                ApplicationStatus::Screening,
+                &follow_up,
+            )
+            .unwrap();
+
+        assert_eq!(application.status, ApplicationStatus::Screening);
+        assert_eq!(reminder.application_id, application.id);
+        assert_eq!(
+            reminder.due_date,
+            chrono::Local::now().date_naive() + chrono::Duration::days(5)
+        );
+
+        // No reminder for a move the pipeline does not allow
+        assert!(matches!(
+            db.applications().change_status_with_follow_up(
+                &mut application,
+                ApplicationStatus::Saved,
+                &follow_up
+            ),
+            Err(JobSearchError::InvalidTransition { .. })
+        ));
+        assert_eq!(db.reminders().count().unwrap(), 1);
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        db.interview_types().add(&mut interview_type).unwrap();
+        let mut interview = Interview::new(interview_type.id.unwrap(), company.id.unwrap(), None);
+        db.interviews().add(&mut interview).unwrap();
+
+        let reminder = db
+            .interviews()
+            .complete_with_follow_up(&mut interview, &follow_up)
+            .unwrap();
+
+        assert!(
+            db.interviews()
+                .get_by_id(interview.id.unwrap())
+                .unwrap()
+                .completed
+        );
+        assert_eq!(reminder.interview_id, interview.id);
+        assert_eq!(
+            db.reminders()
+                .due_between(reminder.due_date, reminder.due_date)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
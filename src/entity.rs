@@ -0,0 +1,319 @@
+use rusqlite::{params, Connection, Row, ToSql, NO_PARAMS};
+
+use crate::errors::{not_found, require_id, JobSearchError};
+use crate::filter::{self, Filter, FilterColumns, Page};
+
+/// Which records a list query returns, based on their `hide` flag.
+///
+/// Hiding is how records are archived: they stay in the database, and can
+/// still be looked up by id, but are left out of lists unless asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Visibility {
+    #[default]
+    Visible,
+    IncludeHidden,
+    OnlyHidden,
+}
+
+impl Visibility {
+    // Databases created before the schema was versioned may have NULL in
+    // the hide column, which counts as visible.
+    pub(crate) fn condition(self, table: &str) -> String {
+        match self {
+            Visibility::Visible => format!("IFNULL({}.hide, 0) = 0", table),
+            Visibility::IncludeHidden => "1".to_string(),
+            Visibility::OnlyHidden => format!("IFNULL({}.hide, 0) != 0", table),
+        }
+    }
+}
+
+/// A type of record stored in its own table.
+///
+/// Every entity can be added, updated, deleted, looked up by id, listed and
+/// counted the same way, so code that handles records in general (exports,
+/// syncing, admin screens) can be written once against this trait. Most
+/// callers go through the matching `Table` of a `Database` instead of
+/// calling these methods directly.
+pub trait Entity: Sized {
+    /// The table the records are stored in.
+    const TABLE: &'static str;
+    /// What a single record is called in error messages.
+    const NAME: &'static str;
+    /// Selects the columns `new_from_row` expects, in order, without a
+    /// WHERE clause.
+    const SELECT: &'static str;
+    /// The ORDER BY clause `get_all` uses.
+    const ORDER_BY: &'static str = "id";
+    /// What a `Filter` can match and sort the records on.
+    const FILTER_COLUMNS: FilterColumns = FilterColumns::NONE;
+
+    fn new_from_row(row: &Row) -> Result<Self, JobSearchError>;
+
+    /// Reads every row selected by `sql`, which has to select the columns of
+    /// `SELECT`. Entities that hold the records they point to fill them in
+    /// here.
+    fn query(
+        conn: &Connection,
+        sql: &str,
+        params: &[&dyn ToSql],
+    ) -> Result<Vec<Self>, JobSearchError> {
+        query(conn, sql, params)
+    }
+
+    /// `None` until the record has been added to the database.
+    fn id(&self) -> Option<i32>;
+
+    fn set_hidden(&mut self, hide: bool);
+
+    fn get_by_id(conn: &Connection, id: i32) -> Result<Self, JobSearchError> {
+        conn.query_row(
+            &format!("{} WHERE {}.id = (?1)", Self::SELECT, Self::TABLE),
+            params![id],
+            |row| Ok(Self::new_from_row(row)),
+        )
+        .map_err(|err| not_found(err, Self::NAME, id))?
+    }
+
+    /// Every record that is not hidden.
+    fn get_all(conn: &Connection) -> Result<Vec<Self>, JobSearchError> {
+        Self::get_all_with(conn, Visibility::Visible)
+    }
+
+    fn get_all_with(
+        conn: &Connection,
+        visibility: Visibility,
+    ) -> Result<Vec<Self>, JobSearchError> {
+        Self::query(
+            conn,
+            &format!(
+                "{} WHERE {} ORDER BY {}",
+                Self::SELECT,
+                visibility.condition(Self::TABLE),
+                Self::ORDER_BY
+            ),
+            NO_PARAMS,
+        )
+    }
+
+    /// The page of records matching `filter`. Fails with `InvalidInput` if
+    /// the filter uses a condition or sort order the entity does not
+    /// support.
+    fn list(conn: &Connection, filter: &Filter) -> Result<Page<Self>, JobSearchError> {
+        filter::list(conn, filter)
+    }
+
+    /// Inserts the record and fills in the id and the columns set by the
+    /// database. Creation and update times that are already set are kept,
+    /// so records copied from another database keep them.
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError>;
+
+    /// Writes the record back and refreshes the columns set by the database.
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError>;
+
+    /// Removes the record and clears its id. Fails with a
+    /// `ForeignKeyViolation` while other records still point to it.
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError>;
+
+    /// Hides the record from lists. Other unsaved changes to it are not
+    /// written.
+    fn archive(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        write_hidden::<Self>(conn, self.id(), true)?;
+        self.set_hidden(true);
+
+        Ok(())
+    }
+
+    fn unarchive(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        write_hidden::<Self>(conn, self.id(), false)?;
+        self.set_hidden(false);
+
+        Ok(())
+    }
+
+    /// The number of records that are not hidden.
+    fn count(conn: &Connection) -> Result<i64, JobSearchError> {
+        Self::count_with(conn, Visibility::Visible)
+    }
+
+    fn count_with(conn: &Connection, visibility: Visibility) -> Result<i64, JobSearchError> {
+        let count = conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM {} WHERE {}",
+                Self::TABLE,
+                visibility.condition(Self::TABLE)
+            ),
+            NO_PARAMS,
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+
+    /// The number of records matching `filter`, across all pages.
+    fn count_matching(conn: &Connection, filter: &Filter) -> Result<i64, JobSearchError> {
+        filter::count::<Self>(conn, filter)
+    }
+}
+
+/// Runs `sql`, which has to select the columns of `T::SELECT`, and reads
+/// every row it returns. This is what `Entity::query` does by default.
+pub(crate) fn query<T: Entity>(
+    conn: &Connection,
+    sql: &str,
+    params: &[&dyn ToSql],
+) -> Result<Vec<T>, JobSearchError> {
+    let mut stmt = conn.prepare(sql)?;
+
+    let entities_iter = stmt.query_map(params, |row| Ok(T::new_from_row(row)))?;
+
+    let mut entities = Vec::new();
+    for entity in entities_iter {
+        entities.push(entity??);
+    }
+
+    Ok(entities)
+}
+
+/// Deletes the row of `T::TABLE` with the given id.
+pub(crate) fn delete<T: Entity>(conn: &Connection, id: Option<i32>) -> Result<(), JobSearchError> {
+    let id = require_id(id, T::NAME)?;
+
+    let deleted = conn.execute(
+        &format!("DELETE FROM {} WHERE id = (?1)", T::TABLE),
+        params![id],
+    )?;
+
+    if deleted == 0 {
+        return Err(JobSearchError::NotFound {
+            entity: T::NAME,
+            id,
+        });
+    }
+
+    Ok(())
+}
+
+fn write_hidden<T: Entity>(
+    conn: &Connection,
+    id: Option<i32>,
+    hide: bool,
+) -> Result<(), JobSearchError> {
+    let id = require_id(id, T::NAME)?;
+
+    let updated = conn.execute(
+        &format!("UPDATE {} SET hide = (?1) WHERE id = (?2)", T::TABLE),
+        params![hide, id],
+    )?;
+
+    if updated == 0 {
+        return Err(JobSearchError::NotFound {
+            entity: T::NAME,
+            id,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::company::Company;
+    use crate::create_in_memory_db;
+    use crate::job_posting::JobPosting;
+
+    // Exercises the trait the way generic tooling would, without naming the
+    // concrete type
+    fn round_trip<T: Entity>(conn: &Connection, mut entity: T) -> T {
+        entity.add_to_db(conn).unwrap();
+        entity.update_db(conn).unwrap();
+
+        T::get_by_id(conn, entity.id().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let conn = create_in_memory_db().unwrap();
+
+        let company = round_trip(&conn, Company::new("google".to_string(), None, None, None));
+        let job_posting = round_trip(&conn, JobPosting::new("https://google.com".to_string()));
+
+        assert_eq!(company.name, "google");
+        assert_eq!(job_posting.link, "https://google.com");
+        assert_eq!(Company::count(&conn).unwrap(), 1);
+        assert_eq!(JobPosting::count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_delete() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let id = company.id.unwrap();
+
+        company.delete_from_db(&conn).unwrap();
+
+        assert_eq!(company.id, None);
+        assert_eq!(Company::count(&conn).unwrap(), 0);
+        assert!(matches!(
+            delete::<Company>(&conn, Some(id)),
+            Err(JobSearchError::NotFound { .. })
+        ));
+        assert!(matches!(
+            company.delete_from_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_archive() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut google = Company::new("google".to_string(), None, None, None);
+        google.add_to_db(&conn).unwrap();
+        Company::new("mozilla".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        google.archive(&conn).unwrap();
+
+        assert!(google.hide);
+        assert_eq!(Company::count(&conn).unwrap(), 1);
+        assert_eq!(Company::get_all(&conn).unwrap()[0].name, "mozilla");
+        assert_eq!(
+            Company::count_with(&conn, Visibility::IncludeHidden).unwrap(),
+            2
+        );
+
+        let hidden = Company::get_all_with(&conn, Visibility::OnlyHidden).unwrap();
+        assert_eq!(hidden.len(), 1);
+        assert!(hidden[0].hide);
+
+        // Still found when asked for directly
+        assert!(Company::get_by_id(&conn, google.id.unwrap()).unwrap().hide);
+
+        google.unarchive(&conn).unwrap();
+
+        assert!(!google.hide);
+        assert_eq!(Company::count(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_null_hide_is_visible() {
+        let conn = create_in_memory_db().unwrap();
+
+        conn.execute(
+            "INSERT INTO job_postings (link, hide) VALUES ('https://example.com', NULL)",
+            NO_PARAMS,
+        )
+        .unwrap();
+
+        let job_postings = JobPosting::get_all(&conn).unwrap();
+
+        assert_eq!(job_postings.len(), 1);
+        assert!(!job_postings[0].hide);
+    }
+}
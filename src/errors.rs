@@ -1,13 +1,144 @@
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use rusqlite::ffi;
+
+use crate::application_status::ApplicationStatus;
 
 #[derive(Debug)]
 pub enum JobSearchError {
+    /// There is no `entity` with the given id.
+    NotFound {
+        entity: &'static str,
+        id: i32,
+    },
+    /// A UNIQUE constraint failed, such as a company name or a job posting
+    /// link that is already in the database.
+    Duplicate {
+        table: String,
+        column: String,
+    },
+    /// A record points to a row that does not exist, or a row that is still
+    /// referenced was removed.
+    ForeignKeyViolation(String),
+    /// The caller passed something that can never be stored.
+    InvalidInput(String),
+    InvalidTransition {
+        from: ApplicationStatus,
+        to: ApplicationStatus,
+    },
+    SchemaTooNew {
+        found: u32,
+        latest: u32,
+    },
+    DatabaseExists(PathBuf),
+    DatabaseNotFound(PathBuf),
+    IOError(io::Error),
     DBError(rusqlite::Error),
     SQLError(rusqlite::types::FromSqlError),
 }
 
+/// Turns a failed lookup of `entity` by id into `NotFound`.
+pub(crate) fn not_found(error: rusqlite::Error, entity: &'static str, id: i32) -> JobSearchError {
+    match error {
+        rusqlite::Error::QueryReturnedNoRows => JobSearchError::NotFound { entity, id },
+        error => JobSearchError::from(error),
+    }
+}
+
+/// Records have to be added to the database before they can be changed.
+pub(crate) fn require_id(id: Option<i32>, entity: &'static str) -> Result<i32, JobSearchError> {
+    id.ok_or_else(|| {
+        JobSearchError::InvalidInput(format!("{} has not been added to the database", entity))
+    })
+}
+
+pub(crate) fn require_text(value: &str, field: &'static str) -> Result<(), JobSearchError> {
+    if value.trim().is_empty() {
+        return Err(JobSearchError::InvalidInput(format!(
+            "{} can not be empty",
+            field
+        )));
+    }
+
+    Ok(())
+}
+
+// SQLite reports these as "UNIQUE constraint failed: companies.name"
+fn parse_unique_violation(message: &str) -> Option<(String, String)> {
+    let columns = message.split(": ").nth(1)?;
+    let first = columns.split(", ").next()?;
+    let mut parts = first.splitn(2, '.');
+
+    Some((parts.next()?.to_string(), parts.next()?.to_string()))
+}
+
+impl fmt::Display for JobSearchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JobSearchError::NotFound { entity, id } => write!(f, "no {} with id {}", entity, id),
+            JobSearchError::Duplicate { table, column } => {
+                write!(f, "{} with that {} already exists", table, column)
+            }
+            JobSearchError::ForeignKeyViolation(message) => {
+                write!(f, "a referenced record does not exist: {}", message)
+            }
+            JobSearchError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            JobSearchError::InvalidTransition { from, to } => {
+                write!(f, "an application can not move from {} to {}", from, to)
+            }
+            JobSearchError::SchemaTooNew { found, latest } => write!(
+                f,
+                "database schema version {} is newer than the latest supported version {}",
+                found, latest
+            ),
+            JobSearchError::DatabaseExists(path) => {
+                write!(f, "{} already exists", path.display())
+            }
+            JobSearchError::DatabaseNotFound(path) => {
+                write!(f, "no database found at {}", path.display())
+            }
+            JobSearchError::IOError(error) => write!(f, "I/O error: {}", error),
+            JobSearchError::DBError(error) => write!(f, "database error: {}", error),
+            JobSearchError::SQLError(error) => write!(f, "unexpected value in database: {}", error),
+        }
+    }
+}
+
+impl Error for JobSearchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JobSearchError::IOError(error) => Some(error),
+            JobSearchError::DBError(error) => Some(error),
+            JobSearchError::SQLError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
 impl From<rusqlite::Error> for JobSearchError {
     fn from(error: rusqlite::Error) -> JobSearchError {
+        if let rusqlite::Error::SqliteFailure(ref failure, ref message) = error {
+            let message = message.clone().unwrap_or_default();
+
+            match failure.extended_code {
+                ffi::SQLITE_CONSTRAINT_UNIQUE | ffi::SQLITE_CONSTRAINT_PRIMARYKEY => {
+                    if let Some((table, column)) = parse_unique_violation(&message) {
+                        return JobSearchError::Duplicate { table, column };
+                    }
+                }
+                ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {
+                    return JobSearchError::ForeignKeyViolation(message);
+                }
+                ffi::SQLITE_CONSTRAINT_NOTNULL => {
+                    return JobSearchError::InvalidInput(message);
+                }
+                _ => {}
+            }
+        }
+
         JobSearchError::DBError(error)
     }
 }
@@ -17,3 +148,88 @@ impl From<rusqlite::types::FromSqlError> for JobSearchError {
         JobSearchError::SQLError(error)
     }
 }
+
+impl From<io::Error> for JobSearchError {
+    fn from(error: io::Error) -> JobSearchError {
+        JobSearchError::IOError(error)
+    }
+}
+
+// Anything but a failure to read or write means the file is not valid CSV
+impl From<csv::Error> for JobSearchError {
+    fn from(error: csv::Error) -> JobSearchError {
+        let message = error.to_string();
+
+        match error.into_kind() {
+            csv::ErrorKind::Io(error) => JobSearchError::IOError(error),
+            _ => JobSearchError::InvalidInput(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::{params, Connection};
+
+    use super::*;
+    use crate::create_in_memory_db;
+
+    fn insert_company(conn: &Connection, name: &str) -> Result<usize, JobSearchError> {
+        Ok(conn.execute("INSERT INTO companies (name) VALUES (?1)", params![name])?)
+    }
+
+    #[test]
+    fn test_unique_violation() {
+        let conn = create_in_memory_db().unwrap();
+
+        insert_company(&conn, "google").unwrap();
+        let result = insert_company(&conn, "google");
+
+        match result {
+            Err(JobSearchError::Duplicate { table, column }) => {
+                assert_eq!(table, "companies");
+                assert_eq!(column, "name");
+            }
+            _ => panic!("expected Duplicate, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_foreign_key_violation() {
+        let conn = create_in_memory_db().unwrap();
+
+        let result: Result<usize, JobSearchError> = conn
+            .execute(
+                "INSERT INTO contacts (name, type_id) VALUES (?1, ?2)",
+                params!["Marcus", 42],
+            )
+            .map_err(JobSearchError::from);
+
+        assert!(matches!(
+            result,
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let error = not_found(rusqlite::Error::QueryReturnedNoRows, "company", 5);
+
+        assert_eq!(error.to_string(), "no company with id 5");
+    }
+
+    #[test]
+    fn test_parse_unique_violation() {
+        let test_cases = vec![
+            (
+                "UNIQUE constraint failed: job_postings.link",
+                Some(("job_postings".to_string(), "link".to_string())),
+            ),
+            ("UNIQUE constraint failed", None),
+        ];
+
+        for (message, expected) in test_cases {
+            assert_eq!(parse_unique_violation(message), expected);
+        }
+    }
+}
@@ -0,0 +1,594 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use rusqlite::{Connection, ToSql};
+
+use crate::application_status::ApplicationStatus;
+use crate::entity::{Entity, Visibility};
+use crate::errors::JobSearchError;
+
+/// How the conditions of a `Filter` apply to the columns of an entity.
+///
+/// A condition is a piece of SQL with a single `?` for the value being
+/// filtered on. Expressions have to be qualified with the entity's table.
+/// Entities leave out what they cannot be filtered or sorted by, and a
+/// `Filter` asking for it fails with `InvalidInput`.
+#[derive(Debug, Clone, Copy)]
+pub struct FilterColumns {
+    /// The date a date range and `SortBy::Date` apply to.
+    pub date: Option<&'static str>,
+    /// Matches the id of a company.
+    pub company: Option<&'static str>,
+    /// Matches an `ApplicationStatus`.
+    pub status: Option<&'static str>,
+    /// Matches the id of a contact type.
+    pub contact_type: Option<&'static str>,
+    /// The columns searched by a text match.
+    pub text: &'static [&'static str],
+    /// What `SortBy::Name` sorts on.
+    pub name: Option<&'static str>,
+    /// Matches the name of a tag on the record, ignoring case.
+    pub tag: Option<&'static str>,
+}
+
+impl FilterColumns {
+    /// Only the hidden flag, ids and the last update can be filtered and
+    /// sorted on.
+    pub const NONE: FilterColumns = FilterColumns {
+        date: None,
+        company: None,
+        status: None,
+        contact_type: None,
+        text: &[],
+        name: None,
+        tag: None,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SortBy {
+    #[default]
+    Id,
+    Date,
+    Name,
+    LastUpdated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Order {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Whether a record needs any or all of the tags of a `Filter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// Where a page of results ended. Passing it to `Filter::after` returns the
+/// next page.
+///
+/// Unlike an offset, a cursor keeps working when records are added or
+/// removed between pages. It points to the last record of the page; once
+/// that record is deleted the pages after it come back empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Cursor(i32);
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Cursor, String> {
+        s.parse()
+            .map(Cursor)
+            .map_err(|_| format!("invalid cursor: {}", s))
+    }
+}
+
+/// One page of the records matching a `Filter`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Set when there are more records after this page.
+    pub next: Option<Cursor>,
+}
+
+/// Which records to list and in what order.
+///
+/// ```
+/// # use job_search::{ApplicationStatus, Database, Filter, Order, SortBy};
+/// # let db = Database::open_in_memory().unwrap();
+/// let filter = Filter::new()
+///     .status(ApplicationStatus::Interviewing)
+///     .sort_by(SortBy::Date, Order::Descending)
+///     .limit(20);
+///
+/// let page = db.applications().list(&filter).unwrap();
+/// if let Some(next) = page.next {
+///     let next_page = db.applications().list(&filter.after(next)).unwrap();
+/// }
+/// ```
+///
+/// Every condition has to match. Without a limit, every matching record is
+/// returned in a single page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    visibility: Visibility,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    company_id: Option<i32>,
+    status: Option<ApplicationStatus>,
+    contact_type_id: Option<i32>,
+    text: Option<String>,
+    tags: Vec<String>,
+    tag_match: TagMatch,
+    sort_by: SortBy,
+    order: Order,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    after: Option<Cursor>,
+}
+
+impl Filter {
+    /// Matches every record that is not hidden, sorted by id.
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    pub fn visibility(mut self, visibility: Visibility) -> Filter {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Records dated on or after `date`. Timestamps are compared by their
+    /// date in UTC.
+    pub fn from(mut self, date: NaiveDate) -> Filter {
+        self.from = Some(date);
+        self
+    }
+
+    /// Records dated on or before `date`.
+    pub fn to(mut self, date: NaiveDate) -> Filter {
+        self.to = Some(date);
+        self
+    }
+
+    /// Both ends of the range are inclusive.
+    pub fn between(self, from: NaiveDate, to: NaiveDate) -> Filter {
+        self.from(from).to(to)
+    }
+
+    pub fn company(mut self, company_id: i32) -> Filter {
+        self.company_id = Some(company_id);
+        self
+    }
+
+    pub fn status(mut self, status: ApplicationStatus) -> Filter {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn contact_type(mut self, contact_type_id: i32) -> Filter {
+        self.contact_type_id = Some(contact_type_id);
+        self
+    }
+
+    /// Records containing `text`, ignoring ASCII case.
+    pub fn text(mut self, text: &str) -> Filter {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    /// Records tagged with any, or all, of `tags`. Tag names are matched
+    /// ignoring case.
+    pub fn tagged<S: AsRef<str>>(mut self, tags: &[S], tag_match: TagMatch) -> Filter {
+        self.tags = tags.iter().map(|tag| tag.as_ref().to_string()).collect();
+        self.tag_match = tag_match;
+        self
+    }
+
+    /// Ties are broken by id, in the same order.
+    pub fn sort_by(mut self, sort_by: SortBy, order: Order) -> Filter {
+        self.sort_by = sort_by;
+        self.order = order;
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Filter {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the first `offset` matching records.
+    pub fn offset(mut self, offset: u32) -> Filter {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Starts after the page that ended at `cursor`.
+    pub fn after(mut self, cursor: Cursor) -> Filter {
+        self.after = Some(cursor);
+        self
+    }
+}
+
+// The conditions and parameters of a query, built up as the filter is
+// applied. Parameters are plain `?`, numbered by SQLite in order.
+struct Query {
+    conditions: Vec<String>,
+    params: Vec<Box<dyn ToSql>>,
+}
+
+impl Query {
+    fn push<V: ToSql + 'static>(&mut self, condition: String, value: V) {
+        self.conditions.push(condition);
+        self.params.push(Box::new(value));
+    }
+
+    fn params(&self) -> Vec<&dyn ToSql> {
+        self.params.iter().map(|param| param.as_ref()).collect()
+    }
+}
+
+fn supported<T: Entity>(
+    column: Option<&'static str>,
+    what: &str,
+) -> Result<&'static str, JobSearchError> {
+    column.ok_or_else(|| {
+        JobSearchError::InvalidInput(format!("cannot filter a {} by {}", T::NAME, what))
+    })
+}
+
+// Escapes the LIKE wildcards so the text is matched literally
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
+// The conditions of the filter, without the cursor
+fn conditions<T: Entity>(filter: &Filter) -> Result<Query, JobSearchError> {
+    let columns = T::FILTER_COLUMNS;
+    let mut query = Query {
+        conditions: vec![filter.visibility.condition(T::TABLE)],
+        params: Vec::new(),
+    };
+
+    if let Some(from) = filter.from {
+        let date = supported::<T>(columns.date, "date")?;
+        query.push(format!("date({}) >= date(?)", date), from);
+    }
+
+    if let Some(to) = filter.to {
+        let date = supported::<T>(columns.date, "date")?;
+        query.push(format!("date({}) <= date(?)", date), to);
+    }
+
+    if let Some(company_id) = filter.company_id {
+        let condition = supported::<T>(columns.company, "company")?;
+        query.push(condition.to_string(), company_id);
+    }
+
+    if let Some(status) = filter.status {
+        let condition = supported::<T>(columns.status, "status")?;
+        query.push(condition.to_string(), status);
+    }
+
+    if let Some(contact_type_id) = filter.contact_type_id {
+        let condition = supported::<T>(columns.contact_type, "contact type")?;
+        query.push(condition.to_string(), contact_type_id);
+    }
+
+    if let Some(ref text) = filter.text {
+        if columns.text.is_empty() {
+            supported::<T>(None, "text")?;
+        }
+
+        let matches: Vec<String> = columns
+            .text
+            .iter()
+            .map(|column| format!("IFNULL({}, '') LIKE ? ESCAPE '\\'", column))
+            .collect();
+
+        query.conditions.push(format!("({})", matches.join(" OR ")));
+        for _ in columns.text {
+            query.params.push(Box::new(like_pattern(text)));
+        }
+    }
+
+    if !filter.tags.is_empty() {
+        let condition = supported::<T>(columns.tag, "tag")?;
+        let separator = match filter.tag_match {
+            TagMatch::Any => " OR ",
+            TagMatch::All => " AND ",
+        };
+
+        let matches = vec![condition; filter.tags.len()];
+        query
+            .conditions
+            .push(format!("({})", matches.join(separator)));
+        for tag in &filter.tags {
+            query.params.push(Box::new(tag.clone()));
+        }
+    }
+
+    Ok(query)
+}
+
+fn sort_expression<T: Entity>(sort_by: SortBy) -> Result<String, JobSearchError> {
+    let columns = T::FILTER_COLUMNS;
+
+    // datetime() normalizes the stored timestamps so they compare correctly
+    // regardless of the format they were written in. Missing values sort
+    // first and still compare in a cursor.
+    let expression = match sort_by {
+        SortBy::Id => return Ok(format!("{}.id", T::TABLE)),
+        SortBy::Date => format!(
+            "datetime({})",
+            columns.date.ok_or_else(|| unsortable::<T>("date"))?
+        ),
+        SortBy::Name => columns
+            .name
+            .ok_or_else(|| unsortable::<T>("name"))?
+            .to_string(),
+        SortBy::LastUpdated => format!("datetime({}.last_updated)", T::TABLE),
+    };
+
+    Ok(format!("IFNULL({}, '')", expression))
+}
+
+fn unsortable<T: Entity>(what: &str) -> JobSearchError {
+    JobSearchError::InvalidInput(format!("cannot sort a {} by {}", T::NAME, what))
+}
+
+/// Reads the page of records matching `filter`.
+pub(crate) fn list<T: Entity>(
+    conn: &Connection,
+    filter: &Filter,
+) -> Result<Page<T>, JobSearchError> {
+    let mut query = conditions::<T>(filter)?;
+    let sort = sort_expression::<T>(filter.sort_by)?;
+
+    let (direction, comparison) = match filter.order {
+        Order::Ascending => ("ASC", ">"),
+        Order::Descending => ("DESC", "<"),
+    };
+
+    // The sort value of the cursor record is looked up in the subquery,
+    // where the columns refer to that record
+    if let Some(Cursor(id)) = filter.after {
+        query.conditions.push(format!(
+            "({sort}, {table}.id) {comparison} \
+             (SELECT {sort}, {table}.id FROM {table} WHERE {table}.id = ?)",
+            sort = sort,
+            table = T::TABLE,
+            comparison = comparison
+        ));
+        query.params.push(Box::new(id));
+    }
+
+    // One more record than asked for tells whether there is a next page.
+    // A negative LIMIT means no limit in SQLite.
+    let limit = filter.limit.map(|limit| i64::from(limit) + 1).unwrap_or(-1);
+    let sql = format!(
+        "{} WHERE {} ORDER BY {sort} {direction}, {table}.id {direction} LIMIT {limit} OFFSET {offset}",
+        T::SELECT,
+        query.conditions.join(" AND "),
+        sort = sort,
+        direction = direction,
+        table = T::TABLE,
+        limit = limit,
+        offset = filter.offset.unwrap_or(0)
+    );
+
+    let mut items = T::query(conn, &sql, &query.params())?;
+
+    let mut next = None;
+    if let Some(limit) = filter.limit {
+        if items.len() > limit as usize {
+            items.truncate(limit as usize);
+            next = items.last().and_then(Entity::id).map(Cursor);
+        }
+    }
+
+    Ok(Page { items, next })
+}
+
+/// The number of records matching `filter`, ignoring its sorting, limit,
+/// offset and cursor.
+pub(crate) fn count<T: Entity>(conn: &Connection, filter: &Filter) -> Result<i64, JobSearchError> {
+    let query = conditions::<T>(filter)?;
+
+    let sql = format!(
+        "SELECT COUNT(*) FROM ({} WHERE {})",
+        T::SELECT,
+        query.conditions.join(" AND ")
+    );
+
+    let count = conn.query_row(&sql, query.params(), |row| row.get(0))?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::Application;
+    use crate::company::Company;
+    use crate::contact::Contact;
+    use crate::contact_type::ContactType;
+    use crate::create_in_memory_db;
+    use crate::job_posting::JobPosting;
+
+    fn add_postings(conn: &Connection, count: usize) {
+        for n in 0..count {
+            let mut job_posting = JobPosting::new(format!("https://example.com/{}", n));
+            job_posting.description =
+                Some(if n % 2 == 0 { "remote" } else { "on site" }.to_string());
+            job_posting.add_to_db(conn).unwrap();
+        }
+    }
+
+    fn links(job_postings: &[JobPosting]) -> Vec<&str> {
+        job_postings
+            .iter()
+            .map(|job_posting| job_posting.link.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_cursor_pagination() {
+        let conn = create_in_memory_db().unwrap();
+        add_postings(&conn, 5);
+
+        let filter = Filter::new()
+            .sort_by(SortBy::Name, Order::Descending)
+            .limit(2);
+
+        let mut seen = Vec::new();
+        let mut page = list::<JobPosting>(&conn, &filter).unwrap();
+        loop {
+            seen.extend(
+                page.items
+                    .iter()
+                    .map(|job_posting| job_posting.link.clone()),
+            );
+
+            match page.next {
+                Some(cursor) => page = list(&conn, &filter.clone().after(cursor)).unwrap(),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            seen,
+            [4, 3, 2, 1, 0]
+                .iter()
+                .map(|n| format!("https://example.com/{}", n))
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_limit_and_offset() {
+        let conn = create_in_memory_db().unwrap();
+        add_postings(&conn, 5);
+
+        let page = list::<JobPosting>(&conn, &Filter::new().offset(3)).unwrap();
+        assert_eq!(
+            links(&page.items),
+            ["https://example.com/3", "https://example.com/4"]
+        );
+        assert_eq!(page.next, None);
+
+        let page = list::<JobPosting>(&conn, &Filter::new().offset(1).limit(1)).unwrap();
+        assert_eq!(links(&page.items), ["https://example.com/1"]);
+        assert!(page.next.is_some());
+    }
+
+    #[test]
+    fn test_text() {
+        let conn = create_in_memory_db().unwrap();
+        add_postings(&conn, 4);
+
+        let filter = Filter::new().text("REMOTE");
+
+        assert_eq!(count::<JobPosting>(&conn, &filter).unwrap(), 2);
+        // Wildcards are matched literally
+        assert_eq!(
+            count::<JobPosting>(&conn, &Filter::new().text("%")).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_application_filters() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut google = Company::new("google".to_string(), None, None, None);
+        google.add_to_db(&conn).unwrap();
+        let mut mozilla = Company::new("mozilla".to_string(), None, None, None);
+        mozilla.add_to_db(&conn).unwrap();
+
+        let mut recruiter = ContactType::new("recruiter".to_string());
+        recruiter.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Marcus".to_string(),
+            None,
+            None,
+            None,
+            recruiter.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+
+        for (company, date, contact_id) in [
+            (&google, "2020-05-01", contact.id),
+            (&google, "2020-06-01", None),
+            (&mozilla, "2020-05-15", None),
+        ] {
+            let mut application = Application::new(company.id.unwrap(), None, contact_id);
+            application.date_applied = Some(date.parse().unwrap());
+            application.add_to_db(&conn).unwrap();
+        }
+
+        let in_may = Filter::new().between(
+            NaiveDate::from_ymd_opt(2020, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2020, 5, 31).unwrap(),
+        );
+
+        let page = list::<Application>(&conn, &in_may.clone().company(google.id.unwrap())).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(
+            count::<Application>(&conn, &in_may.sort_by(SortBy::Date, Order::Descending)).unwrap(),
+            2
+        );
+        assert_eq!(
+            count::<Application>(&conn, &Filter::new().contact_type(recruiter.id.unwrap()))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            count::<Company>(&conn, &Filter::new().status(ApplicationStatus::Applied)).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let conn = create_in_memory_db().unwrap();
+
+        assert!(matches!(
+            list::<Application>(&conn, &Filter::new().text("google")),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            list::<Application>(
+                &conn,
+                &Filter::new().sort_by(SortBy::Name, Order::Ascending)
+            ),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,1343 @@
+use std::io::Write;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, NO_PARAMS};
+use url::Url;
+
+use crate::application::Application;
+use crate::application_status::ApplicationStatus;
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::entity::Entity;
+use crate::errors::JobSearchError;
+use crate::interview::Interview;
+use crate::interview_type::InterviewType;
+use crate::reminder::due_after;
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+// Interviews have no end time, so events are given this length
+const INTERVIEW_LENGTH: &str = "PT1H";
+
+/// What `Database::export_ics` writes besides the scheduled interviews.
+#[derive(Debug, Clone, Default)]
+pub struct IcsOptions {
+    /// Also write a to-do for every open application, due this long after
+    /// its last status change.
+    pub follow_up_after: Option<Duration>,
+}
+
+// Writes content lines, folded at 75 octets and ended with CRLF as RFC 5545
+// requires
+struct ContentWriter<'a, W: Write> {
+    out: &'a mut W,
+}
+
+impl<'a, W: Write> ContentWriter<'a, W> {
+    fn line(&mut self, name: &str, value: &str) -> Result<(), JobSearchError> {
+        let line = format!("{}:{}", name, value);
+
+        let mut start = 0;
+        let mut limit = 75;
+        while line.len() - start > limit {
+            // Never split a UTF-8 sequence
+            let mut end = start + limit;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            write!(self.out, "{}\r\n ", &line[start..end])?;
+            start = end;
+            // The leading space of a continuation line counts too
+            limit = 74;
+        }
+
+        write!(self.out, "{}\r\n", &line[start..])?;
+
+        Ok(())
+    }
+
+    fn text(&mut self, name: &str, value: &str) -> Result<(), JobSearchError> {
+        self.line(name, &escape(value))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Parameter values are quoted, and cannot contain quotes at all
+fn param(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+fn date_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn date(date: &NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// The UID of the event exported for an interview that was not imported
+/// from a calendar. It stays the same across exports so calendars update the
+/// event instead of adding another.
+fn interview_uid(id: i32) -> String {
+    format!("interview-{}@job-search", id)
+}
+
+fn follow_up_uid(application_id: i32) -> String {
+    format!("follow-up-{}@job-search", application_id)
+}
+
+fn write_interview<W: Write>(
+    writer: &mut ContentWriter<W>,
+    interview: &Interview,
+    start: &DateTime<Utc>,
+    now: &DateTime<Utc>,
+) -> Result<(), JobSearchError> {
+    let company = interview
+        .company
+        .as_ref()
+        .map(|company| company.name.as_str());
+    let interview_type = interview
+        .interview_type
+        .as_ref()
+        .map(|interview_type| interview_type.name.as_str());
+
+    let summary = match (interview_type, company) {
+        (Some(interview_type), Some(company)) => {
+            format!("{} interview with {}", interview_type, company)
+        }
+        (None, Some(company)) => format!("Interview with {}", company),
+        (_, None) => "Interview".to_string(),
+    };
+
+    let mut description = Vec::new();
+    if let Some(company) = company {
+        description.push(format!("Company: {}", company));
+    }
+    if let Some(interview_type) = interview_type {
+        description.push(format!("Type: {}", interview_type));
+    }
+    if let Some(ref contact) = interview.contact {
+        match contact.email {
+            Some(ref email) => description.push(format!("Contact: {} <{}>", contact.name, email)),
+            None => description.push(format!("Contact: {}", contact.name)),
+        }
+    }
+    if let Some(ref job_posting) = interview.job_posting {
+        description.push(format!("Posting: {}", job_posting.link));
+    }
+    if let Some(ref notes) = interview.description {
+        description.push(String::new());
+        description.push(notes.clone());
+    }
+
+    writer.line("BEGIN", "VEVENT")?;
+    let uid = match interview.ical_uid {
+        Some(ref uid) => uid.clone(),
+        None => interview_uid(interview.id.unwrap_or_default()),
+    };
+    writer.text("UID", &uid)?;
+    writer.line("DTSTAMP", &date_time(now))?;
+    writer.line("DTSTART", &date_time(start))?;
+    writer.line("DURATION", INTERVIEW_LENGTH)?;
+    writer.text("SUMMARY", &summary)?;
+    writer.text("DESCRIPTION", &description.join("\n"))?;
+    if let Some(interview_type) = interview_type {
+        writer.text("CATEGORIES", interview_type)?;
+    }
+    if let Some(ref job_posting) = interview.job_posting {
+        writer.line("URL", &job_posting.link)?;
+    }
+    if let Some(ref contact) = interview.contact {
+        if let Some(ref email) = contact.email {
+            writer.line(
+                &format!("ATTENDEE;CN={}", param(&contact.name)),
+                &format!("mailto:{}", email),
+            )?;
+        }
+    }
+    writer.line(
+        "STATUS",
+        if interview.cancelled {
+            "CANCELLED"
+        } else {
+            "CONFIRMED"
+        },
+    )?;
+    if let Some(ref created_date) = interview.created_date {
+        writer.line("CREATED", &date_time(created_date))?;
+    }
+    if let Some(ref last_updated) = interview.last_updated {
+        writer.line("LAST-MODIFIED", &date_time(last_updated))?;
+    }
+    writer.line("END", "VEVENT")?;
+
+    Ok(())
+}
+
+// A visible application with the name of its company and the time its
+// status last changed, in the order applications are listed
+const SELECT_FOLLOW_UPS: &str = "SELECT applied_to.id, applied_to.status, \
+     applied_to.date_applied, companies.name, \
+     (SELECT changed_date FROM application_status_history \
+      WHERE application_status_history.application_id = applied_to.id \
+      ORDER BY datetime(changed_date) DESC, id DESC LIMIT 1) \
+     FROM applied_to \
+     LEFT JOIN companies ON companies.id = applied_to.company_id \
+     WHERE IFNULL(applied_to.hide, 0) = 0 \
+     ORDER BY applied_to.date_applied, applied_to.id";
+
+// What the to-do for an application is written from
+struct FollowUpApplication {
+    id: i32,
+    status: ApplicationStatus,
+    date_applied: Option<NaiveDate>,
+    company: Option<String>,
+    last_change: Option<DateTime<Utc>>,
+}
+
+fn get_follow_up_applications(
+    conn: &Connection,
+) -> Result<Vec<FollowUpApplication>, JobSearchError> {
+    let mut stmt = conn.prepare(SELECT_FOLLOW_UPS)?;
+
+    let applications_iter = stmt.query_map(NO_PARAMS, |row| {
+        Ok(FollowUpApplication {
+            id: row.get(0)?,
+            status: row.get(1)?,
+            date_applied: row.get(2)?,
+            company: row.get(3)?,
+            last_change: timestamp::get(row, 4)?,
+        })
+    })?;
+
+    let mut applications = Vec::new();
+    for application in applications_iter {
+        applications.push(application?);
+    }
+
+    Ok(applications)
+}
+
+fn write_follow_up<W: Write>(
+    writer: &mut ContentWriter<W>,
+    application: &FollowUpApplication,
+    after: Duration,
+    now: &DateTime<Utc>,
+) -> Result<(), JobSearchError> {
+    let last_change = application
+        .last_change
+        .map(|changed_date| changed_date.date_naive())
+        .or(application.date_applied);
+
+    let due = match last_change {
+        Some(last_change) => due_after(last_change, after)?,
+        None => return Ok(()),
+    };
+
+    let summary = match application.company {
+        Some(ref company) => format!("Follow up with {}", company),
+        None => "Follow up on application".to_string(),
+    };
+
+    let mut description = vec![format!("Status: {}", application.status)];
+    if let Some(ref date_applied) = application.date_applied {
+        description.push(format!("Applied: {}", date_applied));
+    }
+
+    writer.line("BEGIN", "VTODO")?;
+    writer.text("UID", &follow_up_uid(application.id))?;
+    writer.line("DTSTAMP", &date_time(now))?;
+    writer.line("DUE;VALUE=DATE", &date(&due))?;
+    writer.text("SUMMARY", &summary)?;
+    writer.text("DESCRIPTION", &description.join("\n"))?;
+    writer.line("STATUS", "NEEDS-ACTION")?;
+    writer.line("END", "VTODO")?;
+
+    Ok(())
+}
+
+/// Writes the scheduled interviews that are not hidden as an RFC 5545
+/// calendar, and the follow-ups asked for in `options`.
+pub(crate) fn export<W: Write>(
+    conn: &Connection,
+    out: &mut W,
+    options: &IcsOptions,
+    now: DateTime<Utc>,
+) -> Result<(), JobSearchError> {
+    let mut writer = ContentWriter { out };
+
+    writer.line("BEGIN", "VCALENDAR")?;
+    writer.line("VERSION", "2.0")?;
+    writer.line(
+        "PRODID",
+        &format!(
+            "-//job-search//job_search {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ),
+    )?;
+    writer.line("CALSCALE", "GREGORIAN")?;
+
+    for interview in Interview::get_all(conn)? {
+        if let Some(ref start) = interview.date {
+            write_interview(&mut writer, &interview, start, &now)?;
+        }
+    }
+
+    if let Some(after) = options.follow_up_after {
+        for application in get_follow_up_applications(conn)? {
+            // Nothing to follow up on before applying or once it is over
+            if application.status == ApplicationStatus::Saved || application.status.is_final() {
+                continue;
+            }
+
+            write_follow_up(&mut writer, &application, after, &now)?;
+        }
+    }
+
+    writer.line("END", "VCALENDAR")?;
+
+    Ok(())
+}
+
+/// How `Database::import_ics` fills in what an event does not say.
+#[derive(Debug, Clone, Default)]
+pub struct IcsImportOptions {
+    /// The interview type of events whose type is not named in their
+    /// categories or summary. Without it, those events are skipped.
+    pub default_interview_type_id: Option<i32>,
+}
+
+/// An event that could not be imported, or only in part.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportIssue {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub message: String,
+}
+
+/// What `Database::import_ics` did with the events of a calendar.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IcsImport {
+    /// The ids of the interviews that were added.
+    pub created: Vec<i32>,
+    /// The ids of the interviews that were changed to match their event.
+    pub updated: Vec<i32>,
+    /// The ids of the interviews that already matched their event.
+    pub unchanged: Vec<i32>,
+    /// Events that were left out, and why.
+    pub skipped: Vec<ImportIssue>,
+    /// Events that were imported, but with something in them that did not
+    /// match, such as an attendee who is not a contact.
+    pub unmatched: Vec<ImportIssue>,
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // The address of an ATTENDEE or ORGANIZER
+    fn email(&self) -> Option<String> {
+        let value = self.value.trim();
+
+        if value.len() > 7 && value[..7].eq_ignore_ascii_case("mailto:") {
+            Some(value[7..].to_string())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Event {
+    properties: Vec<Property>,
+}
+
+impl Event {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Property> {
+        self.properties
+            .iter()
+            .filter(move |property| property.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name)
+            .map(|property| unescape(&property.value).trim().to_string())
+            .filter(|text| !text.is_empty())
+    }
+
+    fn issue(&self, message: String) -> ImportIssue {
+        ImportIssue {
+            uid: self.text("UID"),
+            summary: self.text("SUMMARY"),
+            message,
+        }
+    }
+}
+
+// Long lines are folded by inserting a line break and a space or tab. Other
+// programs fold at octets, possibly inside a UTF-8 sequence, so this has to
+// happen before decoding.
+fn unfold(input: &[u8]) -> String {
+    let mut unfolded = Vec::with_capacity(input.len());
+
+    let mut index = 0;
+    while index < input.len() {
+        let rest = &input[index..];
+
+        if rest.starts_with(b"\r\n ") || rest.starts_with(b"\r\n\t") {
+            index += 3;
+        } else if rest.starts_with(b"\n ") || rest.starts_with(b"\n\t") {
+            index += 2;
+        } else {
+            unfolded.push(input[index]);
+            index += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&unfolded).into_owned()
+}
+
+// Splits on `separator` where it is not inside a quoted parameter value
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == separator && !quoted {
+            parts.push(&text[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+
+    parts
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+
+        if c == ':' && !quoted {
+            Some(index)
+        } else {
+            None
+        }
+    })?;
+
+    let mut head = split_unquoted(&line[..colon], ';').into_iter();
+    let name = head.next()?.trim().to_uppercase();
+
+    let params = head
+        .filter_map(|param| {
+            let mut parts = param.splitn(2, '=');
+            let name = parts.next()?.trim().to_uppercase();
+            let value = parts.next()?.trim().trim_matches('"').to_string();
+
+            Some((name, value))
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+// Reads the components of a calendar with the given name, such as VEVENT.
+// Components inside them, such as alarms, are left out.
+fn parse_components(calendar: &str, name: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    let mut nested = 0;
+
+    for property in calendar.lines().filter_map(parse_property) {
+        let value = property.value.trim().to_uppercase();
+
+        match current {
+            None => {
+                if property.name == "BEGIN" && value == name {
+                    current = Some(Event::default());
+                }
+            }
+            Some(ref mut event) => match property.name.as_str() {
+                "BEGIN" => nested += 1,
+                "END" if nested > 0 => nested -= 1,
+                "END" => {
+                    events.extend(current.take());
+                }
+                _ if nested == 0 => event.properties.push(property),
+                _ => {}
+            },
+        }
+    }
+
+    events
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+
+    text
+}
+
+fn is_utc(tzid: &str) -> bool {
+    ["UTC", "Etc/UTC", "GMT", "Etc/GMT", "Z"]
+        .iter()
+        .any(|name| tzid.eq_ignore_ascii_case(name))
+}
+
+// The zone a TZID names, either directly or through the X-LIC-LOCATION of
+// the calendar's VTIMEZONE with that id, as Thunderbird and others write.
+// The rules in a VTIMEZONE are not read otherwise.
+fn time_zone(tzid: &str, zones: &[Event]) -> Option<Tz> {
+    tzid.parse().ok().or_else(|| {
+        zones
+            .iter()
+            .filter(|zone| zone.text("TZID").as_deref() == Some(tzid))
+            .find_map(|zone| zone.text("X-LIC-LOCATION")?.parse().ok())
+    })
+}
+
+fn parse_start(property: &Property, zones: &[Event]) -> Result<DateTime<Utc>, String> {
+    let value = property.value.trim();
+
+    let all_day = property
+        .param("VALUE")
+        .is_some_and(|value| value.eq_ignore_ascii_case("DATE"));
+    if all_day || value.len() == 8 {
+        return Err("all-day events are not imported".to_string());
+    }
+
+    let invalid = |_| format!("{:?} is not a valid start time", value);
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(invalid)?;
+
+        return Ok(Utc.from_utc_datetime(&naive));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(invalid)?;
+
+    match property.param("TZID") {
+        Some(tzid) if is_utc(tzid) => Ok(Utc.from_utc_datetime(&naive)),
+        Some(tzid) => match time_zone(tzid, zones) {
+            Some(tz) => timestamp::from_local(naive, &tz).map_err(|err| err.to_string()),
+            None => Err(format!("unknown time zone {:?}", tzid)),
+        },
+        // Times without a zone are local by definition
+        None => timestamp::from_local(naive, &Local).map_err(|err| err.to_string()),
+    }
+}
+
+// Our own exports describe the interview before its notes
+fn notes(description: Option<String>) -> Option<String> {
+    match description {
+        Some(ref text) if text.starts_with("Company: ") => text
+            .split_once("\n\n")
+            .map(|(_, notes)| notes.trim().to_string())
+            .filter(|notes| !notes.is_empty()),
+        description => description,
+    }
+}
+
+fn exported_id(uid: &str) -> Option<i32> {
+    uid.strip_prefix("interview-")?
+        .strip_suffix("@job-search")?
+        .parse()
+        .ok()
+}
+
+// Whether `needle` is in `haystack` as whole words, ignoring case
+fn contains_words(haystack: &str, needle: &str) -> bool {
+    let haystack = haystack.to_lowercase();
+    let needle = needle.trim().to_lowercase();
+
+    if needle.is_empty() {
+        return false;
+    }
+
+    haystack.match_indices(&needle).any(|(index, _)| {
+        let before = haystack[..index].chars().next_back();
+        let after = haystack[index + needle.len()..].chars().next();
+
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn website_host(website: &str) -> Option<String> {
+    let url = if website.contains("://") {
+        Url::parse(website)
+    } else {
+        Url::parse(&format!("https://{}", website))
+    }
+    .ok()?;
+
+    let host = url.host_str()?.to_lowercase();
+
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+// The records events are matched against, read once per import
+struct Matcher {
+    companies: Vec<Company>,
+    contacts: Vec<Contact>,
+    interview_types: Vec<InterviewType>,
+}
+
+impl Matcher {
+    fn new(conn: &Connection) -> Result<Matcher, JobSearchError> {
+        Ok(Matcher {
+            companies: Company::get_all(conn)?,
+            contacts: Contact::get_all(conn)?,
+            interview_types: InterviewType::get_all(conn)?,
+        })
+    }
+
+    fn contact(&self, event: &Event) -> Option<&Contact> {
+        let people: Vec<&Property> = event
+            .all("ATTENDEE")
+            .chain(event.all("ORGANIZER"))
+            .collect();
+
+        let by_email = people.iter().find_map(|person| {
+            let email = person.email()?;
+
+            self.contacts.iter().find(|contact| {
+                contact
+                    .email
+                    .as_ref()
+                    .is_some_and(|address| address.eq_ignore_ascii_case(&email))
+            })
+        });
+
+        by_email.or_else(|| {
+            people.iter().find_map(|person| {
+                let name = person.param("CN")?;
+
+                self.contacts
+                    .iter()
+                    .find(|contact| contact.name.eq_ignore_ascii_case(name.trim()))
+            })
+        })
+    }
+
+    fn company(
+        &self,
+        conn: &Connection,
+        event: &Event,
+        contact: Option<&Contact>,
+    ) -> Result<Option<i32>, JobSearchError> {
+        // Our own exports name the company in the description
+        let named = event.text("DESCRIPTION").and_then(|description| {
+            let name = description
+                .lines()
+                .next()?
+                .strip_prefix("Company: ")?
+                .to_string();
+
+            self.companies
+                .iter()
+                .find(|company| company.name.eq_ignore_ascii_case(name.trim()))
+        });
+        if let Some(company) = named {
+            return Ok(company.id);
+        }
+
+        // The longest name wins, so "Google Cloud" is preferred over "Google"
+        let text = [event.text("SUMMARY"), event.text("LOCATION")]
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n");
+        let mentioned = self
+            .companies
+            .iter()
+            .filter(|company| contains_words(&text, &company.name))
+            .max_by_key(|company| company.name.len());
+        if let Some(company) = mentioned {
+            return Ok(company.id);
+        }
+
+        let domains: Vec<String> = event
+            .all("ATTENDEE")
+            .chain(event.all("ORGANIZER"))
+            .filter_map(|person| {
+                let email = person.email()?;
+                Some(email.rsplit('@').next()?.to_lowercase())
+            })
+            .collect();
+        let by_domain = self.companies.iter().find(|company| {
+            let host = match company.website.as_deref().and_then(website_host) {
+                Some(host) => host,
+                None => return false,
+            };
+
+            domains
+                .iter()
+                .any(|domain| *domain == host || domain.ends_with(&format!(".{}", host)))
+        });
+        if let Some(company) = by_domain {
+            return Ok(company.id);
+        }
+
+        // The company the contact was last applied to through
+        match contact.and_then(|contact| contact.id) {
+            Some(contact_id) => {
+                let company_id = conn.query_row(
+                    "SELECT company_id FROM applied_to WHERE contact_id = (?1) \
+                     ORDER BY date_applied DESC, id DESC LIMIT 1",
+                    params![contact_id],
+                    |row| row.get(0),
+                );
+
+                match company_id {
+                    Ok(company_id) => Ok(company_id),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn interview_type(&self, event: &Event) -> Option<i32> {
+        let categories: Vec<String> = event
+            .all("CATEGORIES")
+            .flat_map(|property| {
+                split_unescaped_commas(&property.value)
+                    .into_iter()
+                    .map(|category| category.trim().to_string())
+            })
+            .collect();
+
+        let by_category = self.interview_types.iter().find(|interview_type| {
+            categories
+                .iter()
+                .any(|category| category.eq_ignore_ascii_case(&interview_type.name))
+        });
+
+        let summary = event.text("SUMMARY").unwrap_or_default();
+
+        by_category
+            .or_else(|| {
+                self.interview_types
+                    .iter()
+                    .filter(|interview_type| contains_words(&summary, &interview_type.name))
+                    .max_by_key(|interview_type| interview_type.name.len())
+            })
+            .and_then(|interview_type| interview_type.id)
+    }
+}
+
+// CATEGORIES is a list of text values, separated by commas that are not
+// escaped
+fn split_unescaped_commas(value: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut escaped = false;
+
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => values.push(String::new()),
+            '\\' if !escaped => {
+                escaped = true;
+                continue;
+            }
+            c => {
+                if let Some(current) = values.last_mut() {
+                    if escaped {
+                        current.push('\\');
+                    }
+                    current.push(c);
+                }
+            }
+        }
+        escaped = false;
+    }
+
+    values.iter().map(|value| unescape(value)).collect()
+}
+
+fn get_by_uid(conn: &Connection, uid: &str) -> Result<Option<Interview>, JobSearchError> {
+    let interviews = Interview::query(
+        conn,
+        &format!("{} WHERE interviews.ical_uid = (?1)", Interview::SELECT),
+        params![uid],
+    )?;
+
+    Ok(interviews.into_iter().next())
+}
+
+fn import_event(
+    conn: &Connection,
+    matcher: &Matcher,
+    event: &Event,
+    zones: &[Event],
+    options: &IcsImportOptions,
+    report: &mut IcsImport,
+) -> Result<(), JobSearchError> {
+    let uid = match event.text("UID") {
+        Some(uid) => uid,
+        None => {
+            let issue = event.issue("the event has no UID".to_string());
+            report.skipped.push(issue);
+            return Ok(());
+        }
+    };
+
+    let start = match event.get("DTSTART").map(|start| parse_start(start, zones)) {
+        Some(Ok(start)) => start,
+        Some(Err(message)) => {
+            report.skipped.push(event.issue(message));
+            return Ok(());
+        }
+        None => {
+            let issue = event.issue("the event has no start time".to_string());
+            report.skipped.push(issue);
+            return Ok(());
+        }
+    };
+
+    let contact = matcher.contact(event);
+    let company_id = matcher.company(conn, event, contact)?;
+
+    let mut existing = get_by_uid(conn, &uid)?;
+    if existing.is_none() {
+        // Events exported from this database carry the interview's id.
+        // Another database may have exported the same id for a different
+        // company, so the company has to agree.
+        if let Some(id) = exported_id(&uid) {
+            existing = match Interview::get_by_id(conn, id) {
+                Ok(interview)
+                    if interview.ical_uid.is_none() && Some(interview.company_id) == company_id =>
+                {
+                    Some(interview)
+                }
+                Ok(_) | Err(JobSearchError::NotFound { .. }) => None,
+                Err(err) => return Err(err),
+            };
+        }
+    }
+
+    let company_id =
+        match company_id.or_else(|| existing.as_ref().map(|interview| interview.company_id)) {
+            Some(company_id) => company_id,
+            None => {
+                let issue = event.issue("no company matches the event".to_string());
+                report.skipped.push(issue);
+                return Ok(());
+            }
+        };
+
+    let interview_type_id = match matcher
+        .interview_type(event)
+        .or_else(|| {
+            existing
+                .as_ref()
+                .map(|interview| interview.interview_type_id)
+        })
+        .or(options.default_interview_type_id)
+    {
+        Some(interview_type_id) => interview_type_id,
+        None => {
+            let issue = event.issue("no interview type matches the event".to_string());
+            report.skipped.push(issue);
+            return Ok(());
+        }
+    };
+
+    let has_people = event.get("ATTENDEE").is_some() || event.get("ORGANIZER").is_some();
+    if contact.is_none() && has_people {
+        let issue = event.issue("none of the attendees is a contact".to_string());
+        report.unmatched.push(issue);
+    }
+
+    let cancelled = event
+        .text("STATUS")
+        .is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED"));
+    let description = notes(event.text("DESCRIPTION"));
+
+    match existing {
+        Some(mut interview) => {
+            let contact_id = contact
+                .and_then(|contact| contact.id)
+                .or(interview.contact_id);
+            let id = interview.id.unwrap_or_default();
+
+            let unchanged = interview.date == Some(start)
+                && interview.cancelled == cancelled
+                && interview.description == description
+                && interview.company_id == company_id
+                && interview.interview_type_id == interview_type_id
+                && interview.contact_id == contact_id;
+
+            if unchanged {
+                report.unchanged.push(id);
+            } else {
+                interview.date = Some(start);
+                interview.cancelled = cancelled;
+                interview.description = description;
+                interview.company_id = company_id;
+                interview.interview_type_id = interview_type_id;
+                interview.contact_id = contact_id;
+                interview.update_db(conn)?;
+
+                report.updated.push(id);
+            }
+        }
+        None => {
+            let mut interview = Interview::new(interview_type_id, company_id, Some(start));
+            interview.contact_id = contact.and_then(|contact| contact.id);
+            interview.application_id = Application::get_by_company(conn, company_id)?
+                .pop()
+                .and_then(|application| application.id);
+            interview.description = description;
+            interview.cancelled = cancelled;
+            interview.ical_uid = Some(uid);
+            interview.add_to_db(conn)?;
+
+            report.created.push(interview.id.unwrap_or_default());
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds or updates an interview for every event of `calendar` that can be
+/// matched to a company and an interview type.
+///
+/// Events are found again by their UID, so importing the same calendar
+/// twice changes nothing. Nothing is imported if reading or writing the
+/// database fails.
+pub(crate) fn import(
+    conn: &Connection,
+    calendar: &[u8],
+    options: &IcsImportOptions,
+) -> Result<IcsImport, JobSearchError> {
+    let calendar = unfold(calendar);
+    let events = parse_components(&calendar, "VEVENT");
+    let zones = parse_components(&calendar, "VTIMEZONE");
+    let matcher = Matcher::new(conn)?;
+
+    with_savepoint(conn, || {
+        let mut report = IcsImport::default();
+
+        for event in &events {
+            import_event(conn, &matcher, event, &zones, options, &mut report)?;
+        }
+
+        Ok(report)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contact::Contact;
+    use crate::contact_type::ContactType;
+    use crate::create_in_memory_db;
+    use crate::interview_type::InterviewType;
+    use crate::job_posting::JobPosting;
+
+    fn export_to_string(conn: &Connection, options: &IcsOptions) -> String {
+        let now = "2020-05-01T12:00:00Z".parse().unwrap();
+
+        let mut out = Vec::new();
+        export(conn, &mut out, options, now).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    // Undoes the line folding
+    fn unfold(calendar: &str) -> String {
+        calendar.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn test_export_interview() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Google, Inc.".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Marcus".to_string(),
+            Some("marcus@google.com".to_string()),
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+        let mut job_posting =
+            JobPosting::new(format!("https://google.com/jobs/{}", "x".repeat(80)));
+        job_posting.add_to_db(&conn).unwrap();
+
+        let mut interview = Interview::new(
+            interview_type.id.unwrap(),
+            company.id.unwrap(),
+            Some("2020-05-08T10:00:00Z".parse().unwrap()),
+        );
+        interview.contact_id = contact.id;
+        interview.job_posting_id = job_posting.id;
+        interview.add_to_db(&conn).unwrap();
+
+        // Not scheduled yet, so left out
+        Interview::new(interview_type.id.unwrap(), company.id.unwrap(), None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let calendar = export_to_string(&conn, &IcsOptions::default());
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 1);
+
+        let calendar = unfold(&calendar);
+        assert!(calendar.contains("UID:interview-1@job-search\r\n"));
+        assert!(calendar.contains("DTSTAMP:20200501T120000Z\r\n"));
+        assert!(calendar.contains("DTSTART:20200508T100000Z\r\n"));
+        assert!(calendar.contains("SUMMARY:Phone interview with Google\\, Inc.\r\n"));
+        assert!(calendar.contains("\\nContact: Marcus <marcus@google.com>\\n"));
+        assert!(calendar.contains(&format!("URL:{}\r\n", job_posting.link)));
+        assert!(calendar.contains("ATTENDEE;CN=\"Marcus\":mailto:marcus@google.com\r\n"));
+        assert!(calendar.contains("STATUS:CONFIRMED\r\n"));
+
+        // Exporting again gives the same events
+        assert_eq!(
+            unfold(&export_to_string(&conn, &IcsOptions::default())),
+            calendar
+        );
+    }
+
+    #[test]
+    fn test_export_follow_ups() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+
+        let mut open = Application::new(company.id.unwrap(), None, None);
+        open.add_to_db(&conn).unwrap();
+        let mut closed = Application::new(company.id.unwrap(), None, None);
+        closed.add_to_db(&conn).unwrap();
+        closed
+            .change_status(&conn, ApplicationStatus::Rejected)
+            .unwrap();
+
+        let calendar = export_to_string(&conn, &IcsOptions::default());
+        assert!(!calendar.contains("VTODO"));
+
+        let options = IcsOptions {
+            follow_up_after: Some(Duration::days(7)),
+        };
+        let calendar = export_to_string(&conn, &options);
+        let due = Utc::now().date_naive() + Duration::days(7);
+
+        assert_eq!(calendar.matches("BEGIN:VTODO").count(), 1);
+        assert!(calendar.contains("UID:follow-up-1@job-search\r\n"));
+        assert!(calendar.contains(&format!("DUE;VALUE=DATE:{}\r\n", due.format("%Y%m%d"))));
+        assert!(calendar.contains("SUMMARY:Follow up with mozilla\r\n"));
+
+        // Due after the latest status change
+        conn.execute(
+            "UPDATE application_status_history SET changed_date = '2020-02-01 10:00:00' \
+             WHERE application_id = (?1)",
+            params![open.id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO application_status_history (application_id, status, changed_date) \
+             VALUES (?1, 'applied', '2020-01-01 10:00:00')",
+            params![open.id],
+        )
+        .unwrap();
+        let calendar = export_to_string(&conn, &options);
+        assert!(
+            calendar.contains("DUE;VALUE=DATE:20200208\r\n"),
+            "{}",
+            calendar
+        );
+    }
+
+    #[test]
+    fn test_fold_multibyte() {
+        let mut out = Vec::new();
+        ContentWriter { out: &mut out }
+            .text("SUMMARY", &"é".repeat(60))
+            .unwrap();
+
+        let calendar = String::from_utf8(out).unwrap();
+
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(unfold(&calendar), format!("SUMMARY:{}\r\n", "é".repeat(60)));
+    }
+
+    fn import_str(conn: &Connection, calendar: &str, options: &IcsImportOptions) -> IcsImport {
+        import(conn, calendar.as_bytes(), options).unwrap()
+    }
+
+    fn calendar(events: &[&str]) -> String {
+        let events: Vec<String> = events
+            .iter()
+            .map(|event| format!("BEGIN:VEVENT\r\n{}END:VEVENT\r\n", event))
+            .collect();
+
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+            events.concat()
+        )
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let mut calendar = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\n"
+            .as_bytes()
+            .to_vec();
+        // Folded in the middle of "é"
+        calendar.extend(b"SUMMARY:Caf\xc3\r\n \xa9 chat\\, then lunch\r\n");
+        calendar
+            .extend(b"ATTENDEE;CN=\"Doe: Jane\";ROLE=REQ-PARTICIPANT:mailto:jane@example.com\n");
+        calendar.extend(b"BEGIN:VALARM\r\nSUMMARY:Reminder\r\nEND:VALARM\r\n");
+        calendar.extend(b"DESCRIPTION:one\\ntwo\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n");
+
+        let events = parse_components(&super::unfold(&calendar), "VEVENT");
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.text("SUMMARY").unwrap(), "Café chat, then lunch");
+        assert_eq!(event.text("DESCRIPTION").unwrap(), "one\ntwo");
+
+        let attendee = event.get("ATTENDEE").unwrap();
+        assert_eq!(attendee.param("CN"), Some("Doe: Jane"));
+        assert_eq!(attendee.email().unwrap(), "jane@example.com");
+    }
+
+    #[test]
+    fn test_import_and_reimport() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        application.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Onsite".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Allen".to_string(),
+            Some("allen@mozilla.org".to_string()),
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+
+        let event = "UID:abc@example.com\r\n\
+                     DTSTART:20200508T100000Z\r\n\
+                     SUMMARY:Onsite at Mozilla\r\n\
+                     DESCRIPTION:Bring a laptop\r\n\
+                     ATTENDEE;CN=Allen:MAILTO:Allen@Mozilla.org\r\n";
+        let options = IcsImportOptions::default();
+
+        let report = import_str(&conn, &calendar(&[event]), &options);
+        assert_eq!(report.created, vec![1]);
+        assert!(report.skipped.is_empty());
+        assert!(report.unmatched.is_empty());
+
+        let interview = Interview::get_by_id(&conn, 1).unwrap();
+        assert_eq!(interview.company_id, company.id.unwrap());
+        assert_eq!(interview.interview_type_id, interview_type.id.unwrap());
+        assert_eq!(interview.contact_id, contact.id);
+        assert_eq!(interview.application_id, application.id);
+        assert_eq!(
+            interview.date,
+            Some("2020-05-08T10:00:00Z".parse().unwrap())
+        );
+        assert_eq!(interview.description.as_deref(), Some("Bring a laptop"));
+        assert_eq!(interview.ical_uid.as_deref(), Some("abc@example.com"));
+
+        let report = import_str(&conn, &calendar(&[event]), &options);
+        assert!(report.created.is_empty());
+        assert_eq!(report.unchanged, vec![1]);
+
+        let cancelled = format!("{}STATUS:CANCELLED\r\n", event);
+        let report = import_str(&conn, &calendar(&[&cancelled]), &options);
+        assert_eq!(report.updated, vec![1]);
+        assert!(Interview::get_by_id(&conn, 1).unwrap().cancelled);
+        assert_eq!(Interview::count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Google, Inc.".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+
+        let mut interview = Interview::new(
+            interview_type.id.unwrap(),
+            company.id.unwrap(),
+            Some("2020-05-08T10:00:00Z".parse().unwrap()),
+        );
+        interview.description = Some("Ask about the team".to_string());
+        interview.add_to_db(&conn).unwrap();
+
+        let calendar = export_to_string(&conn, &IcsOptions::default());
+        let report = import_str(&conn, &calendar, &IcsImportOptions::default());
+
+        assert_eq!(report.unchanged, vec![interview.id.unwrap()]);
+        assert!(report.created.is_empty());
+        assert_eq!(Interview::count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_import_time_zones() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Onsite".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+
+        let calendar = calendar(&[
+            "UID:1\r\nDTSTART;TZID=America/New_York:20200508T100000\r\n\
+             SUMMARY:Onsite at Mozilla\r\n",
+            // Named through the VTIMEZONE, as Outlook names zones
+            "UID:2\r\nDTSTART;TZID=\"Eastern Standard Time\":20200108T100000\r\n\
+             SUMMARY:Onsite at Mozilla\r\n",
+            "UID:3\r\nDTSTART;TZID=Mars/Olympus_Mons:20200508T100000\r\n\
+             SUMMARY:Onsite at Mozilla\r\n",
+        ])
+        .replacen(
+            "BEGIN:VEVENT",
+            "BEGIN:VTIMEZONE\r\nTZID:Eastern Standard Time\r\n\
+             X-LIC-LOCATION:America/New_York\r\nBEGIN:STANDARD\r\n\
+             TZOFFSETFROM:-0400\r\nTZOFFSETTO:-0500\r\nEND:STANDARD\r\n\
+             END:VTIMEZONE\r\nBEGIN:VEVENT",
+            1,
+        );
+
+        let report = import_str(&conn, &calendar, &IcsImportOptions::default());
+        assert_eq!(report.created, vec![1, 2]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].uid.as_deref(), Some("3"));
+        assert_eq!(
+            report.skipped[0].message,
+            "unknown time zone \"Mars/Olympus_Mons\""
+        );
+
+        // Daylight saving time in May, standard time in January
+        assert_eq!(
+            Interview::get_by_id(&conn, 1).unwrap().date,
+            Some("2020-05-08T14:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            Interview::get_by_id(&conn, 2).unwrap().date,
+            Some("2020-01-08T15:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_import_reports_problems() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new(
+            "Gitlab".to_string(),
+            None,
+            Some("https://www.gitlab.com".to_string()),
+            None,
+        );
+        company.add_to_db(&conn).unwrap();
+        let mut interview_type = InterviewType::new("Other".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+
+        let calendar = calendar(&[
+            // Matched by the attendee's domain, but the attendee is unknown
+            "UID:1\r\nDTSTART;TZID=UTC:20200508T100000\r\nSUMMARY:Chat\r\n\
+             ATTENDEE:mailto:sid@gitlab.com\r\n",
+            "UID:2\r\nDTSTART:20200508T100000Z\r\nSUMMARY:Dentist\r\n",
+            "UID:3\r\nDTSTART;VALUE=DATE:20200508\r\nSUMMARY:Gitlab offsite\r\n",
+            "DTSTART:20200508T100000Z\r\nSUMMARY:Gitlab\r\n",
+        ]);
+
+        let report = import_str(&conn, &calendar, &IcsImportOptions::default());
+        assert!(report.created.is_empty());
+        assert_eq!(report.skipped.len(), 4);
+        assert_eq!(
+            report.skipped[0].message,
+            "no interview type matches the event"
+        );
+        assert_eq!(report.skipped[1].message, "no company matches the event");
+        assert_eq!(report.skipped[1].summary.as_deref(), Some("Dentist"));
+
+        let options = IcsImportOptions {
+            default_interview_type_id: interview_type.id,
+        };
+        let report = import_str(&conn, &calendar, &options);
+        assert_eq!(report.created, vec![1]);
+        assert_eq!(report.skipped.len(), 3);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(report.unmatched[0].uid.as_deref(), Some("1"));
+
+        let interview = Interview::get_by_id(&conn, 1).unwrap();
+        assert_eq!(interview.company_id, company.id.unwrap());
+        assert_eq!(interview.contact_id, None);
+    }
+}
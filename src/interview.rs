@@ -0,0 +1,637 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::contact_type::ContactType;
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::interview_type::InterviewType;
+use crate::job_posting::JobPosting;
+use crate::reminder::{FollowUp, Reminder};
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+// The records the interview points to are joined in, in the column order
+// their own SELECTs use
+const SELECT_INTERVIEWS: &str = "SELECT interviews.id, interviews.interview_type_id, \
+     interviews.created_date, interviews.last_updated, interviews.date, interviews.company_id, \
+     interviews.contact_id, interviews.job_posting_id, interviews.application_id, \
+     interviews.description, interviews.cancelled, interviews.hide, interviews.ical_uid, \
+     interviews.completed, \
+     interview_types.id, interview_types.name, interview_types.last_updated, \
+     interview_types.hide, \
+     companies.id, companies.name, companies.address, companies.website, companies.phone, \
+     companies.created_date, companies.last_updated, companies.hide, \
+     contacts.id, contacts.name, contacts.created_date, contacts.last_updated, contacts.email, \
+     contacts.phone, contacts.description, contacts.type_id, contacts.hide, \
+     contact_types.id, contact_types.name, contact_types.last_updated, contact_types.hide, \
+     job_postings.id, job_postings.link, job_postings.created_date, job_postings.last_updated, \
+     job_postings.description, job_postings.hide \
+     FROM interviews \
+     LEFT JOIN interview_types ON interview_types.id = interviews.interview_type_id \
+     LEFT JOIN companies ON companies.id = interviews.company_id \
+     LEFT JOIN contacts ON contacts.id = interviews.contact_id \
+     LEFT JOIN contact_types ON contact_types.id = contacts.type_id \
+     LEFT JOIN job_postings ON job_postings.id = interviews.job_posting_id";
+
+// interview_type_id has no foreign key in the schema, so the type is looked
+// up here instead
+fn check_interview_type(conn: &Connection, id: i32) -> Result<(), JobSearchError> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM interview_types WHERE id = (?1))",
+        params![id],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        return Err(JobSearchError::ForeignKeyViolation(format!(
+            "no interview type with id {}",
+            id
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interview {
+    pub id: Option<i32>,
+    pub interview_type_id: i32,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    /// When the interview takes place, if it has been scheduled.
+    pub date: Option<DateTime<Utc>>,
+    pub company_id: i32,
+    pub contact_id: Option<i32>,
+    pub job_posting_id: Option<i32>,
+    pub application_id: Option<i32>,
+    pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cancelled: bool,
+    /// Set once the interview has taken place.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub completed: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+    /// The UID of the calendar event the interview was imported from.
+    pub ical_uid: Option<String>,
+
+    // The records the ids above point to. Filled in when the interview is
+    // read from the database.
+    pub interview_type: Option<InterviewType>,
+    pub company: Option<Company>,
+    pub contact: Option<Contact>,
+    pub job_posting: Option<JobPosting>,
+}
+
+fn interview_type_at(row: &Row, idx: usize) -> Result<Option<InterviewType>, JobSearchError> {
+    let interview_type = match row.get::<_, Option<i32>>(idx)? {
+        Some(id) => Some(InterviewType {
+            id: Some(id),
+            name: row.get(idx + 1)?,
+            last_updated: timestamp::get(row, idx + 2)?,
+            hide: row.get::<_, Option<bool>>(idx + 3)?.unwrap_or(false),
+        }),
+        None => None,
+    };
+
+    Ok(interview_type)
+}
+
+fn company_at(row: &Row, idx: usize) -> Result<Option<Company>, JobSearchError> {
+    let company = match row.get::<_, Option<i32>>(idx)? {
+        Some(id) => Some(Company {
+            id: Some(id),
+            name: row.get(idx + 1)?,
+            address: row.get(idx + 2)?,
+            website: row.get(idx + 3)?,
+            phone: row.get(idx + 4)?,
+            created_date: timestamp::get(row, idx + 5)?,
+            last_updated: timestamp::get(row, idx + 6)?,
+            hide: row.get::<_, Option<bool>>(idx + 7)?.unwrap_or(false),
+        }),
+        None => None,
+    };
+
+    Ok(company)
+}
+
+fn contact_at(row: &Row, idx: usize) -> Result<Option<Contact>, JobSearchError> {
+    let id = match row.get::<_, Option<i32>>(idx)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+
+    let contact_type = match row.get::<_, Option<i32>>(idx + 9)? {
+        Some(type_id) => Some(ContactType::new_from_db(
+            type_id,
+            row.get(idx + 10)?,
+            timestamp::get(row, idx + 11)?,
+            row.get::<_, Option<bool>>(idx + 12)?.unwrap_or(false),
+        )),
+        None => None,
+    };
+
+    Ok(Some(Contact {
+        id: Some(id),
+        name: row.get(idx + 1)?,
+        created_date: timestamp::get(row, idx + 2)?,
+        last_updated: timestamp::get(row, idx + 3)?,
+        email: row.get(idx + 4)?,
+        phone: row.get(idx + 5)?,
+        description: row.get(idx + 6)?,
+        type_id: row.get(idx + 7)?,
+        contact_type,
+        hide: row.get::<_, Option<bool>>(idx + 8)?.unwrap_or(false),
+    }))
+}
+
+fn job_posting_at(row: &Row, idx: usize) -> Result<Option<JobPosting>, JobSearchError> {
+    let job_posting = match row.get::<_, Option<i32>>(idx)? {
+        Some(id) => Some(JobPosting {
+            id: Some(id),
+            link: row.get(idx + 1)?,
+            created_date: timestamp::get(row, idx + 2)?,
+            last_updated: timestamp::get(row, idx + 3)?,
+            description: row.get(idx + 4)?,
+            hide: row.get::<_, Option<bool>>(idx + 5)?.unwrap_or(false),
+        }),
+        None => None,
+    };
+
+    Ok(job_posting)
+}
+
+impl Interview {
+    pub fn new(interview_type_id: i32, company_id: i32, date: Option<DateTime<Utc>>) -> Interview {
+        Interview {
+            id: None,
+            interview_type_id,
+            created_date: None,
+            last_updated: None,
+            date,
+            company_id,
+            contact_id: None,
+            job_posting_id: None,
+            application_id: None,
+            description: None,
+            cancelled: false,
+            completed: false,
+            hide: false,
+            ical_uid: None,
+            interview_type: None,
+            company: None,
+            contact: None,
+            job_posting: None,
+        }
+    }
+
+    pub(crate) fn get_by_application(
+        conn: &Connection,
+        application_id: i32,
+    ) -> Result<Vec<Interview>, JobSearchError> {
+        Interview::query(
+            conn,
+            &format!(
+                "{} WHERE interviews.application_id = (?1) AND {} ORDER BY {}",
+                SELECT_INTERVIEWS,
+                Visibility::Visible.condition(Interview::TABLE),
+                Interview::ORDER_BY
+            ),
+            params![application_id],
+        )
+    }
+
+    /// Interviews that have not been cancelled and take place between `from`
+    /// and `days` days later.
+    pub(crate) fn get_upcoming(
+        conn: &Connection,
+        from: DateTime<Utc>,
+        days: i64,
+    ) -> Result<Vec<Interview>, JobSearchError> {
+        let to = from + Duration::days(days);
+
+        // datetime() normalizes the stored timestamps so they compare
+        // correctly regardless of the format they were written in
+        Interview::query(
+            conn,
+            &format!(
+                "{} WHERE IFNULL(interviews.cancelled, 0) = 0 \
+                 AND datetime(interviews.date) >= datetime(?1) \
+                 AND datetime(interviews.date) < datetime(?2) AND {} ORDER BY {}",
+                SELECT_INTERVIEWS,
+                Visibility::Visible.condition(Interview::TABLE),
+                Interview::ORDER_BY
+            ),
+            params![from, to],
+        )
+    }
+
+    pub(crate) fn reschedule(
+        &mut self,
+        conn: &Connection,
+        date: DateTime<Utc>,
+    ) -> Result<(), JobSearchError> {
+        self.date = Some(date);
+        self.cancelled = false;
+        self.completed = false;
+
+        self.update_db(conn)
+    }
+
+    pub(crate) fn cancel(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        self.cancelled = true;
+
+        self.update_db(conn)
+    }
+
+    pub(crate) fn complete(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        self.completed = true;
+
+        self.update_db(conn)
+    }
+}
+
+impl Entity for Interview {
+    const TABLE: &'static str = "interviews";
+    const NAME: &'static str = "interview";
+    const SELECT: &'static str = SELECT_INTERVIEWS;
+    const ORDER_BY: &'static str = "datetime(interviews.date), interviews.id";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("interviews.date"),
+        company: Some("interviews.company_id = ?"),
+        status: Some("interviews.application_id IN (SELECT id FROM applied_to WHERE status = ?)"),
+        contact_type: Some("interviews.contact_id IN (SELECT id FROM contacts WHERE type_id = ?)"),
+        text: &["interviews.description"],
+        name: None,
+        // The tags of the application or the company
+        tag: Some(
+            "EXISTS (SELECT 1 FROM tags WHERE tags.name = ? AND (\
+             tags.id IN (SELECT tag_id FROM application_tags \
+             WHERE application_id = interviews.application_id) \
+             OR tags.id IN (SELECT tag_id FROM company_tags \
+             WHERE company_id = interviews.company_id)))",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Interview, JobSearchError> {
+        let interview = Interview {
+            id: row.get(0)?,
+            interview_type_id: row.get(1)?,
+            created_date: timestamp::get(row, 2)?,
+            last_updated: timestamp::get(row, 3)?,
+            date: timestamp::get(row, 4)?,
+            company_id: row.get(5)?,
+            contact_id: row.get(6)?,
+            job_posting_id: row.get(7)?,
+            application_id: row.get(8)?,
+            description: row.get(9)?,
+            cancelled: row.get::<_, Option<bool>>(10)?.unwrap_or(false),
+            completed: row.get::<_, Option<bool>>(13)?.unwrap_or(false),
+            hide: row.get::<_, Option<bool>>(11)?.unwrap_or(false),
+            ical_uid: row.get(12)?,
+            interview_type: interview_type_at(row, 14)?,
+            company: company_at(row, 18)?,
+            contact: contact_at(row, 26)?,
+            job_posting: job_posting_at(row, 39)?,
+        };
+
+        Ok(interview)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        check_interview_type(conn, self.interview_type_id)?;
+
+        let _ = conn.execute(
+            "INSERT INTO interviews (interview_type_id, date, company_id, contact_id, job_posting_id, \
+             application_id, description, cancelled, hide, ical_uid, created_date, last_updated, \
+             completed) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, COALESCE(?11, CURRENT_TIMESTAMP), ?12, ?13)",
+            params![
+                self.interview_type_id,
+                self.date,
+                self.company_id,
+                self.contact_id,
+                self.job_posting_id,
+                self.application_id,
+                self.description,
+                self.cancelled,
+                self.hide,
+                self.ical_uid,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated),
+                self.completed
+            ],
+        )?;
+
+        let id = conn.last_insert_rowid() as i32;
+
+        *self = Interview::get_by_id(conn, id)?;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "interview")?;
+        check_interview_type(conn, self.interview_type_id)?;
+
+        let _ = conn.execute(
+            "UPDATE interviews SET interview_type_id = (?1), date = (?2), company_id = (?3), \
+             contact_id = (?4), job_posting_id = (?5), application_id = (?6), description = (?7), \
+             cancelled = (?8), hide = (?9), ical_uid = (?10), completed = (?11) WHERE id = (?12)",
+            params![
+                self.interview_type_id,
+                self.date,
+                self.company_id,
+                self.contact_id,
+                self.job_posting_id,
+                self.application_id,
+                self.description,
+                self.cancelled,
+                self.hide,
+                self.ical_uid,
+                self.completed,
+                id
+            ],
+        )?;
+
+        *self = Interview::get_by_id(conn, id)?;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Interview>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Interview> {
+    /// Moves the interview to `date`, reinstating it if it was cancelled.
+    pub fn reschedule(
+        &self,
+        interview: &mut Interview,
+        date: DateTime<Utc>,
+    ) -> Result<(), JobSearchError> {
+        interview.reschedule(self.conn(), date)
+    }
+
+    pub fn cancel(&self, interview: &mut Interview) -> Result<(), JobSearchError> {
+        interview.cancel(self.conn())
+    }
+
+    /// Marks the interview as having taken place.
+    pub fn complete(&self, interview: &mut Interview) -> Result<(), JobSearchError> {
+        interview.complete(self.conn())
+    }
+
+    /// Marks the interview as having taken place and adds a reminder to
+    /// follow up on it. Neither is kept if the other fails.
+    pub fn complete_with_follow_up(
+        &self,
+        interview: &mut Interview,
+        follow_up: &FollowUp,
+    ) -> Result<Reminder, JobSearchError> {
+        let conn = self.conn();
+
+        with_savepoint(conn, || {
+            interview.complete(conn)?;
+
+            Reminder::add_interview_follow_up(conn, interview, follow_up, Local::now().date_naive())
+        })
+    }
+
+    pub fn get_by_application(
+        &self,
+        application_id: i32,
+    ) -> Result<Vec<Interview>, JobSearchError> {
+        Interview::get_by_application(self.conn(), application_id)
+    }
+
+    /// Interviews that have not been cancelled and take place in the next
+    /// `days` days.
+    pub fn get_upcoming(&self, days: i64) -> Result<Vec<Interview>, JobSearchError> {
+        Interview::get_upcoming(self.conn(), Utc::now(), days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::Application;
+    use crate::contact_type::ContactType;
+    use crate::create_in_memory_db;
+
+    struct TestData {
+        interview_type_id: i32,
+        company_id: i32,
+        contact_id: i32,
+        application_id: i32,
+    }
+
+    fn create_test_data(conn: &Connection) -> TestData {
+        let mut interview_type = InterviewType::new("phone".to_string());
+        interview_type.add_to_db(conn).unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(conn).unwrap();
+
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(conn).unwrap();
+
+        let mut contact = Contact::new(
+            "Marcus".to_string(),
+            None,
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(conn).unwrap();
+
+        let mut application = Application::new(company.id.unwrap(), None, contact.id);
+        application.add_to_db(conn).unwrap();
+
+        TestData {
+            interview_type_id: interview_type.id.unwrap(),
+            company_id: company.id.unwrap(),
+            contact_id: contact.id.unwrap(),
+            application_id: application.id.unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_add_to_db() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview =
+            Interview::new(data.interview_type_id, data.company_id, Some(Utc::now()));
+        interview.contact_id = Some(data.contact_id);
+        interview.application_id = Some(data.application_id);
+        interview.add_to_db(&conn).unwrap();
+
+        assert_ne!(interview.id, None);
+        assert_ne!(interview.created_date, None);
+        assert_eq!(interview.interview_type.unwrap().name, "phone");
+        assert_eq!(interview.company.unwrap().name, "google");
+        let contact = interview.contact.unwrap();
+        assert_eq!(contact.name, "Marcus");
+        assert_eq!(contact.contact_type.unwrap().name, "recruiter");
+        assert!(interview.job_posting.is_none());
+
+        let mut job_posting = JobPosting::new("https://google.com/jobs/1".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+        let mut interview = Interview::new(data.interview_type_id, data.company_id, None);
+        interview.job_posting_id = job_posting.id;
+        interview.add_to_db(&conn).unwrap();
+
+        // Listing fills in the records in the same query
+        let interviews = Interview::get_all(&conn).unwrap();
+        assert_eq!(interviews.len(), 2);
+        assert!(interviews
+            .iter()
+            .all(|interview| interview.company.as_ref().unwrap().name == "google"));
+        assert_eq!(
+            interviews[0].job_posting.as_ref().unwrap().link,
+            "https://google.com/jobs/1"
+        );
+        assert!(interviews[0].contact.is_none());
+    }
+
+    #[test]
+    fn test_add_to_db_with_unknown_company() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview = Interview::new(data.interview_type_id, 42, None);
+
+        assert!(interview.add_to_db(&conn).is_err());
+    }
+
+    #[test]
+    fn test_add_to_db_with_unknown_interview_type() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview = Interview::new(42, data.company_id, None);
+        assert!(matches!(
+            interview.add_to_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+        assert_eq!(Interview::get_all(&conn).unwrap().len(), 0);
+
+        let mut interview = Interview::new(data.interview_type_id, data.company_id, None);
+        interview.add_to_db(&conn).unwrap();
+        interview.interview_type_id = 42;
+        assert!(matches!(
+            interview.update_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+        assert_eq!(
+            Interview::get_by_id(&conn, interview.id.unwrap())
+                .unwrap()
+                .interview_type_id,
+            data.interview_type_id
+        );
+    }
+
+    #[test]
+    fn test_reschedule() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview = Interview::new(data.interview_type_id, data.company_id, None);
+        interview.add_to_db(&conn).unwrap();
+        interview.cancel(&conn).unwrap();
+
+        let date = Utc::now() + Duration::days(3);
+        interview.reschedule(&conn, date).unwrap();
+
+        assert_eq!(interview.date.unwrap().timestamp(), date.timestamp());
+        assert!(!interview.cancelled);
+        assert_ne!(interview.last_updated, None);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview =
+            Interview::new(data.interview_type_id, data.company_id, Some(Utc::now()));
+        interview.add_to_db(&conn).unwrap();
+
+        interview.cancel(&conn).unwrap();
+
+        assert!(
+            Interview::get_by_id(&conn, interview.id.unwrap())
+                .unwrap()
+                .cancelled
+        );
+    }
+
+    #[test]
+    fn test_get_upcoming() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+        let now = Utc::now();
+
+        for days in [-1, 1, 6, 8].iter() {
+            Interview::new(
+                data.interview_type_id,
+                data.company_id,
+                Some(now + Duration::days(*days)),
+            )
+            .add_to_db(&conn)
+            .unwrap();
+        }
+
+        let mut cancelled = Interview::new(
+            data.interview_type_id,
+            data.company_id,
+            Some(now + Duration::days(2)),
+        );
+        cancelled.add_to_db(&conn).unwrap();
+        cancelled.cancel(&conn).unwrap();
+
+        let interviews = Interview::get_upcoming(&conn, now, 7).unwrap();
+
+        assert_eq!(interviews.len(), 2);
+        assert!(interviews[0].date < interviews[1].date);
+        assert_eq!(Interview::get_all(&conn).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_get_by_application() {
+        let conn = create_in_memory_db().unwrap();
+        let data = create_test_data(&conn);
+
+        let mut interview = Interview::new(data.interview_type_id, data.company_id, None);
+        interview.application_id = Some(data.application_id);
+        interview.add_to_db(&conn).unwrap();
+
+        Interview::new(data.interview_type_id, data.company_id, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let interviews = Interview::get_by_application(&conn, data.application_id).unwrap();
+
+        assert_eq!(interviews.len(), 1);
+        assert_eq!(interviews[0].id, interview.id);
+    }
+}
@@ -1,20 +1,24 @@
-use chrono::{DateTime, Local};
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
 
-use crate::errors::JobSearchError;
-use crate::utils::convert_option_string_to_option_date;
+use crate::database::Table;
+use crate::entity::{self, Entity};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::timestamp;
 
 #[derive(Debug)]
-struct InterviewType {
-    id: Option<i32>,
-    name: String,
-    last_updated: Option<DateTime<Local>>,
-    hide: bool,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterviewType {
+    pub id: Option<i32>,
+    pub name: String,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
 }
 
-#[allow(dead_code)]
 impl InterviewType {
-    fn new(name: String) -> InterviewType {
+    pub fn new(name: String) -> InterviewType {
         InterviewType {
             id: None,
             name,
@@ -23,114 +27,114 @@ impl InterviewType {
         }
     }
 
-    fn new_from_db(
-        id: i32,
-        name: String,
-        last_updated: Option<DateTime<Local>>,
-        hide: i32,
-    ) -> InterviewType {
-        let hide = match hide {
-            0 => false,
-            _ => true,
-        };
+    /// Names are unique, so there is at most one match.
+    pub(crate) fn get_by_name(
+        conn: &Connection,
+        name: &str,
+    ) -> Result<Option<InterviewType>, JobSearchError> {
+        let interview_types: Vec<InterviewType> = entity::query(
+            conn,
+            &format!("{} WHERE name = (?1)", InterviewType::SELECT),
+            params![name],
+        )?;
 
-        InterviewType {
-            id: Some(id),
-            name,
-            last_updated,
-            hide,
-        }
+        Ok(interview_types.into_iter().next())
     }
+}
 
-    fn get_by_id(conn: &Connection, id: i32) -> Result<InterviewType, JobSearchError> {
-        let interview_type = conn.query_row(
-            "SELECT id, name, last_updated, hide FROM interview_types WHERE id = (?1)",
-            params![id],
-            |row| {
-                let id: i32 = row.get(0)?;
-                let name: String = row.get(1)?;
-                let last_updated: Option<String> = match row.get(2)? {
-                    Some(time) => Some(time),
-                    None => None,
-                };
-                let last_updated = convert_option_string_to_option_date(last_updated);
-                let hide: i32 = row.get(3)?;
-
-                Ok(InterviewType::new_from_db(id, name, last_updated, hide))
-            },
-        )?;
+impl Entity for InterviewType {
+    const TABLE: &'static str = "interview_types";
+    const NAME: &'static str = "interview type";
+    const SELECT: &'static str = "SELECT id, name, last_updated, hide FROM interview_types";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        text: &["interview_types.name"],
+        name: Some("interview_types.name"),
+        ..FilterColumns::NONE
+    };
+
+    fn new_from_row(row: &Row) -> Result<InterviewType, JobSearchError> {
+        let interview_type = InterviewType {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            last_updated: timestamp::get(row, 2)?,
+            hide: row.get::<_, Option<bool>>(3)?.unwrap_or(false),
+        };
 
         Ok(interview_type)
     }
 
-    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let _ = conn.execute(
-            "INSERT INTO interview_types (name) VALUES (?1)",
-            params![self.name],
-        )?;
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
 
-        // Update the id
-        let id = conn.last_insert_rowid() as i32;
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
 
-        let mut stmt =
-            conn.prepare("SELECT name, last_updated, hide FROM interview_types WHERE id=(?1)")?;
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.name, "interview type name")?;
 
-        let row = stmt.query_row(params![id], |row| {
-            let name: String = row.get(0)?;
-            let last_updated: Option<String> = match row.get(1)? {
-                Some(time) => Some(time),
-                None => None,
-            };
-            let last_updated = convert_option_string_to_option_date(last_updated);
-            let hide = match row.get(2)? {
-                0 => false,
-                _ => true,
-            };
+        let _ = conn.execute(
+            "INSERT INTO interview_types (name, hide, last_updated) VALUES (?1, ?2, ?3)",
+            params![self.name, self.hide, timestamp::to_sql(&self.last_updated)],
+        )?;
 
-            Ok((name, last_updated, hide))
-        })?;
+        let interview_type = InterviewType::get_by_id(conn, conn.last_insert_rowid() as i32)?;
 
-        self.id = Some(id);
-        self.name = row.0;
-        self.last_updated = row.1;
-        self.hide = row.2;
+        self.id = interview_type.id;
+        self.last_updated = interview_type.last_updated;
 
         Ok(())
     }
 
     fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let hide = if self.hide { 1 } else { 0 };
+        let id = require_id(self.id, "interview type")?;
+        require_text(&self.name, "interview type name")?;
 
         let _ = conn.execute(
             "UPDATE interview_types SET name = (?1), hide = (?2) WHERE id = (?3)",
-            params![self.name, hide, self.id],
+            params![self.name, self.hide, id],
         )?;
 
-        //need to update the last_updated field
-        let last_updated = conn.query_row(
-            "SELECT last_updated FROM interview_types WHERE id = (?1)",
+        self.last_updated = InterviewType::get_by_id(conn, id)?.last_updated;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        // interviews.interview_type_id has no foreign key to refuse this
+        let used: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM interviews WHERE interview_type_id = (?1))",
             params![self.id],
-            |row| {
-                let last_updated: Option<String> = match row.get(0)? {
-                    Some(time) => Some(time),
-                    None => None,
-                };
-                let last_updated = convert_option_string_to_option_date(last_updated);
-
-                Ok(last_updated)
-            },
+            |row| row.get(0),
         )?;
 
-        self.last_updated = last_updated;
+        if used {
+            return Err(JobSearchError::ForeignKeyViolation(
+                "the interview type is used by interviews".to_string(),
+            ));
+        }
+
+        entity::delete::<InterviewType>(conn, self.id)?;
+
+        self.id = None;
 
         Ok(())
     }
 }
 
+impl<'a> Table<'a, InterviewType> {
+    pub fn get_by_name(&self, name: &str) -> Result<Option<InterviewType>, JobSearchError> {
+        InterviewType::get_by_name(self.conn(), name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::company::Company;
     use crate::create_in_memory_db;
+    use crate::interview::Interview;
 
     #[test]
     fn test_new() {
@@ -147,11 +151,11 @@ mod tests {
 
         let name = "testing".to_string();
         let mut interview_type = InterviewType::new(name.clone());
-        let _ = interview_type.add_to_db(&conn).unwrap();
+        interview_type.add_to_db(&conn).unwrap();
 
         let result = InterviewType::get_by_id(&conn, 1);
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -161,14 +165,14 @@ mod tests {
         let mut name = "testing".to_string();
         let mut interview_type = InterviewType::new(name.clone());
 
-        let _ = interview_type.add_to_db(&conn).unwrap();
+        interview_type.add_to_db(&conn).unwrap();
 
-        let last_updated = interview_type.last_updated.clone();
+        let last_updated = interview_type.last_updated;
 
         name = "new_name".to_string();
         interview_type.name = name.clone();
 
-        let _ = interview_type.update_db(&conn).unwrap();
+        interview_type.update_db(&conn).unwrap();
 
         //Inserting an item in the DB does not populate the last_updated
         //field. As such the `last_updated` variable is None.
@@ -186,4 +190,57 @@ mod tests {
 
         assert_ne!(interview_type.id, None);
     }
+
+    #[test]
+    fn test_get_all() {
+        let conn = create_in_memory_db().unwrap();
+
+        InterviewType::new("phone".to_string())
+            .add_to_db(&conn)
+            .unwrap();
+        InterviewType::new("onsite".to_string())
+            .add_to_db(&conn)
+            .unwrap();
+
+        let interview_types = InterviewType::get_all(&conn).unwrap();
+
+        assert_eq!(interview_types.len(), 2);
+    }
+
+    #[test]
+    fn test_get_by_name() {
+        let conn = create_in_memory_db().unwrap();
+
+        InterviewType::new("phone".to_string())
+            .add_to_db(&conn)
+            .unwrap();
+
+        let found = InterviewType::get_by_name(&conn, "phone").unwrap();
+
+        assert_eq!(found.unwrap().name, "phone");
+        assert!(InterviewType::get_by_name(&conn, "onsite")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_delete_used() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut interview = Interview::new(interview_type.id.unwrap(), company.id.unwrap(), None);
+        interview.add_to_db(&conn).unwrap();
+
+        assert!(matches!(
+            interview_type.delete_from_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+
+        interview.delete_from_db(&conn).unwrap();
+        interview_type.delete_from_db(&conn).unwrap();
+        assert!(InterviewType::get_all(&conn).unwrap().is_empty());
+    }
 }
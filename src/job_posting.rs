@@ -1,21 +1,25 @@
-use chrono::{DateTime, Local};
-use rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
 
-use crate::errors::JobSearchError;
-use crate::utils::convert_option_string_to_option_date;
+use crate::database::Table;
+use crate::entity::{self, Entity};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::timestamp;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JobPosting {
     pub id: Option<i32>,
     pub link: String,
-    pub created_date: Option<DateTime<Local>>,
-    pub last_updated: Option<DateTime<Local>>,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
     pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub hide: bool,
 }
 
 impl JobPosting {
-    #[allow(dead_code)]
     pub fn new(link: String) -> JobPosting {
         JobPosting {
             id: None,
@@ -27,107 +31,122 @@ impl JobPosting {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn new_from_row(row: &rusqlite::Row) -> Result<JobPosting, JobSearchError> {
-        let hide = match row.get(5)? {
-            0 => false,
-            _ => true,
-        };
+    /// Links are unique, so there is at most one match. Hidden postings are
+    /// found too.
+    pub(crate) fn get_by_link(
+        conn: &Connection,
+        link: &str,
+    ) -> Result<Option<JobPosting>, JobSearchError> {
+        let job_postings: Vec<JobPosting> = entity::query(
+            conn,
+            &format!("{} WHERE link = (?1)", JobPosting::SELECT),
+            params![link.trim()],
+        )?;
+
+        Ok(job_postings.into_iter().next())
+    }
+}
 
+impl Entity for JobPosting {
+    const TABLE: &'static str = "job_postings";
+    const NAME: &'static str = "job posting";
+    const SELECT: &'static str =
+        "SELECT id, link, created_date, last_updated, description, hide FROM job_postings";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("job_postings.created_date"),
+        company: Some(
+            "job_postings.id IN (SELECT job_posting_id FROM applied_to WHERE company_id = ?)",
+        ),
+        status: Some("job_postings.id IN (SELECT job_posting_id FROM applied_to WHERE status = ?)"),
+        contact_type: None,
+        text: &["job_postings.link", "job_postings.description"],
+        name: Some("job_postings.link"),
+        tag: Some(
+            "job_postings.id IN (SELECT job_posting_tags.job_posting_id FROM job_posting_tags \
+             JOIN tags ON tags.id = job_posting_tags.tag_id WHERE tags.name = ?)",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<JobPosting, JobSearchError> {
         let job_posting = JobPosting {
             id: row.get(0)?,
             link: row.get(1)?,
-            created_date: row.get(2)?,
-            last_updated: row.get(3)?,
+            created_date: timestamp::get(row, 2)?,
+            last_updated: timestamp::get(row, 3)?,
             description: row.get(4)?,
-            hide,
+            hide: row.get::<_, Option<bool>>(5)?.unwrap_or(false),
         };
 
         Ok(job_posting)
     }
 
-    #[allow(dead_code)]
-    pub fn get_all_job_postings(conn: &Connection) -> Result<Vec<JobPosting>, JobSearchError> {
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, link, created_date, last_updated, description, hide FROM job_postings;",
-            )
-            .unwrap();
-
-        let job_postings_iter = stmt
-            .query_map(params![], |row| Ok(JobPosting::new_from_row(row).unwrap()))
-            .unwrap();
-
-        let mut job_postings = Vec::new();
-
-        for posting in job_postings_iter {
-            job_postings.push(posting.unwrap());
-        }
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
 
-        Ok(job_postings)
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
     }
 
-    #[allow(dead_code)]
-    pub fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.link, "job posting link")?;
+
         //If it has an id, do not add it to the database
         // because it already exists
-        let mut result = 0;
-        match self.id {
-            Some(_id) => {
-                //Could add logging
-                // Do nothing
-            }
-            None => {
-                let mut stmt = conn.prepare(
-                    "INSERT INTO job_postings\
-                 (link, last_updated, description, hide) \
-                 VALUES (?1, ?2, ?3, ?4);",
-                )?;
-
-                result = stmt.insert(params![
-                    self.link,
-                    self.last_updated,
-                    self.description,
-                    self.hide
-                ])?;
-            }
+        if self.id.is_some() {
+            return Ok(());
         }
 
-        self.id = Some(result as i32);
+        let _ = conn.execute(
+            "INSERT INTO job_postings (link, description, hide, created_date, last_updated) \
+             VALUES (?1, ?2, ?3, COALESCE(?4, CURRENT_TIMESTAMP), ?5)",
+            params![
+                self.link,
+                self.description,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        let job_posting = JobPosting::get_by_id(conn, conn.last_insert_rowid() as i32)?;
+
+        self.id = job_posting.id;
+        self.created_date = job_posting.created_date;
+        self.last_updated = job_posting.last_updated;
 
         Ok(())
     }
 
-    #[allow(dead_code)]
     fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
-        let hide = if self.hide { 1 } else { 0 };
+        let id = require_id(self.id, "job posting")?;
+        require_text(&self.link, "job posting link")?;
 
         let _ = conn.execute(
             "UPDATE job_postings SET link = (?1), description = (?2), hide = (?3) WHERE id = (?4)",
-            params![self.link, self.description, hide, self.id],
+            params![self.link, self.description, self.hide, id],
         )?;
 
-        //need to update the last_updated field
-        let last_updated = conn.query_row(
-            "SELECT last_updated FROM job_postings WHERE id = (?1)",
-            params![self.id],
-            |row| {
-                let last_updated: Option<String> = match row.get(0)? {
-                    Some(time) => Some(time),
-                    None => None,
-                };
-                let last_updated = convert_option_string_to_option_date(last_updated);
-
-                Ok(last_updated)
-            },
-        )?;
+        self.last_updated = JobPosting::get_by_id(conn, id)?.last_updated;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<JobPosting>(conn, self.id)?;
 
-        self.last_updated = last_updated;
+        self.id = None;
 
         Ok(())
     }
 }
 
+impl<'a> Table<'a, JobPosting> {
+    pub fn get_by_link(&self, link: &str) -> Result<Option<JobPosting>, JobSearchError> {
+        JobPosting::get_by_link(self.conn(), link)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,7 +172,7 @@ mod tests {
 
         create_job_posting_test_data(&conn);
 
-        let job_postings = JobPosting::get_all_job_postings(&conn).unwrap();
+        let job_postings = JobPosting::get_all(&conn).unwrap();
 
         assert_eq!(job_postings.len(), 3);
     }
@@ -164,7 +183,7 @@ mod tests {
 
         create_job_posting_test_data(&conn);
 
-        let mut job_postings = JobPosting::get_all_job_postings(&conn).unwrap();
+        let mut job_postings = JobPosting::get_all(&conn).unwrap();
 
         let job_posting = job_postings.first_mut().unwrap();
 
@@ -176,4 +195,34 @@ mod tests {
         assert_ne!(job_posting.last_updated, None);
         assert_ne!(job_posting.description, None);
     }
+
+    #[test]
+    fn test_get_by_id() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut job_posting = JobPosting::new(String::from("google"));
+        job_posting.add_to_db(&conn).unwrap();
+
+        let job_posting_from_db = JobPosting::get_by_id(&conn, job_posting.id.unwrap()).unwrap();
+
+        assert_eq!(job_posting_from_db.link, job_posting.link);
+        assert_ne!(job_posting_from_db.created_date, None);
+    }
+
+    #[test]
+    fn test_get_by_link() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut job_posting = JobPosting::new("https://example.com/1".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+
+        let found = JobPosting::get_by_link(&conn, "https://example.com/1 ")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(found.id, job_posting.id);
+        assert!(JobPosting::get_by_link(&conn, "https://example.com/2")
+            .unwrap()
+            .is_none());
+    }
 }
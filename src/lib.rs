@@ -1,163 +1,138 @@
-use std::fs;
-use std::path;
-
 use rusqlite::config::DbConfig;
 use rusqlite::{Connection, Error};
+mod application;
+mod application_status;
+mod audit;
 mod company;
+mod contact;
 mod contact_type;
+mod csv_io;
+mod database;
+mod entity;
 mod errors;
+mod filter;
+mod ical;
+mod interview;
 mod interview_type;
 mod job_posting;
-mod models;
+pub mod migrations;
+mod note;
+mod reminder;
+mod search;
+mod snapshot;
+mod tag;
+pub mod timestamp;
 mod utils;
 
+pub use application::Application;
+pub use application_status::{ApplicationStatus, StageDuration, StatusChange};
+pub use audit::{AuditAction, AuditEntry, FieldChange};
+pub use company::Company;
+pub use contact::Contact;
+pub use contact_type::ContactType;
+pub use csv_io::{CsvImport, CsvImportOptions, CsvTable, RowError};
+pub use database::{Database, Schema, Table};
+pub use entity::{Entity, Visibility};
+pub use errors::JobSearchError;
+pub use filter::{Cursor, Filter, FilterColumns, Order, Page, SortBy, TagMatch};
+pub use ical::{IcsImport, IcsImportOptions, IcsOptions, ImportIssue};
+pub use interview::Interview;
+pub use interview_type::InterviewType;
+pub use job_posting::JobPosting;
+pub use note::{Note, NoteTarget};
+pub use reminder::{FollowUp, Recurrence, Reminder};
+pub use search::{SearchHit, SearchKind};
+pub use snapshot::{MergeCounts, SnapshotImport, SNAPSHOT_VERSION};
+pub use tag::{Tag, TagCount, Taggable};
+
 fn enable_config_options(conn: &Connection) -> Result<(), Error> {
-    let db_options = vec![
+    let db_options = [
         DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY,
         DbConfig::SQLITE_DBCONFIG_ENABLE_TRIGGER,
     ];
 
     for option in db_options {
-        if !conn.db_config(option).unwrap() {
-            let _ = conn.set_db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY, true)?;
-        }
+        conn.set_db_config(option, true)?;
     }
     Ok(())
 }
 
-fn get_path_to_sql_init_file() -> String {
-    vec!["experimental", "testing.sql"].join(path::MAIN_SEPARATOR.to_string().as_str())
-}
-
-fn read_sql_file(path: &str) -> String {
-    let sql_init_file_path = get_path_to_sql_init_file();
-
-    // If path is an empty string, I want to use a default path
-    let sql_file_path = if path.is_empty() {
-        sql_init_file_path
-    } else if file_exist(path) {
-        path.to_string()
-    } else {
-        sql_init_file_path
-    };
-
-    fs::read_to_string(&sql_file_path).unwrap()
-}
-
-fn file_exist(path: &str) -> bool {
-    let metadata = if let Ok(data) = fs::metadata(path) {
-        Some(data)
-    } else {
-        None
-    };
-
-    match metadata {
-        Some(data) => data.is_file(),
-        None => false,
-    }
-}
-
-#[allow(dead_code)]
-fn create_in_memory_db() -> Result<Connection, Error> {
-    let conn = Connection::open_in_memory()?;
-    let sql_stmts = read_sql_file("");
-    let _ = conn.execute_batch(sql_stmts.as_str());
+#[cfg(test)]
+fn create_in_memory_db() -> Result<Connection, JobSearchError> {
+    let mut conn = Connection::open_in_memory()?;
+    migrations::migrate(&mut conn)?;
 
     enable_config_options(&conn)?;
 
     Ok(conn)
 }
 
-pub fn get_db_connection(path: &str) -> Result<Connection, Error> {
-    let conn = if file_exist(path) {
-        Connection::open(path)?
-    } else {
-        let _conn = Connection::open(path)?;
-
-        // Get the sql needed to create the database
-        let sql_stmts = read_sql_file("");
-        let _ = _conn.execute_batch(sql_stmts.as_str());
-
-        _conn
-    };
-
-    let _ = enable_config_options(&conn);
-
-    Ok(conn)
-}
-
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::path;
 
     use rusqlite::params;
 
     use super::*;
 
     #[test]
-    fn test_get_database_connection_with_existing_db() {
+    fn test_open_existing_db() {
         let path_to_db =
-            vec!["experimental", "test.db"].join(path::MAIN_SEPARATOR.to_string().as_str());
-
-        let conn = get_db_connection(&path_to_db).unwrap();
-        let mut stmt = conn
-            .prepare("SELECT name FROM contacts where id=?;")
+            ["experimental", "test.db"].join(path::MAIN_SEPARATOR.to_string().as_str());
+
+        // Opening the database migrates it, so work on a copy of the fixture
+        let file_name = "existing_test_db.db";
+        fs::copy(&path_to_db, file_name).unwrap();
+
+        let db = Database::open(file_name).unwrap();
+        let name: String = db
+            .conn()
+            .query_row("SELECT name FROM contacts where id=?;", params![1], |row| {
+                row.get(0)
+            })
             .unwrap();
 
-        let mut rows = stmt.query(params![1]).unwrap();
+        assert_eq!(name, "Marcus");
+        assert_eq!(db.version().unwrap(), migrations::latest_version());
 
-        while let Some(row) = rows.next().unwrap() {
-            let name: String = row.get(0).unwrap();
-            assert_eq!(name, "Marcus");
-        }
+        drop(db);
+
+        // Cleaning up the created file
+        fs::remove_file(file_name).unwrap()
     }
 
     #[test]
     fn test_enable_config_options() {
-        let conn = get_db_connection("").unwrap();
-        let _ = enable_config_options(&conn).unwrap();
-
-        assert_eq!(
-            conn.db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY)
-                .unwrap(),
-            true
-        )
+        let conn = Connection::open_in_memory().unwrap();
+        enable_config_options(&conn).unwrap();
+
+        assert!(conn
+            .db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY)
+            .unwrap());
+        assert!(conn
+            .db_config(DbConfig::SQLITE_DBCONFIG_ENABLE_TRIGGER)
+            .unwrap());
     }
 
     #[test]
-    fn test_get_database_connection_with_new_db() {
+    fn test_create_new_db() {
         let file_name = "new_test_db.db";
 
-        let conn = get_db_connection(file_name).unwrap();
+        let db = Database::create(file_name).unwrap();
+
+        let count: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM contacts;", params![], |row| {
+                row.get(0)
+            })
+            .unwrap();
 
-        let mut stmt = conn.prepare("SELECT name FROM contacts;").unwrap();
+        assert_eq!(count, 0);
 
-        let _rows = stmt.query(params![]).unwrap();
+        drop(db);
 
         // Cleaning up the created file
         fs::remove_file(file_name).unwrap()
     }
-
-    #[test]
-    fn test_read_sql_file() {
-        let real_sql_path = get_path_to_sql_init_file();
-
-        let test_cases = vec!["", "src", real_sql_path.as_str()];
-
-        for path in test_cases {
-            let result = read_sql_file(path);
-            assert_eq!(result.is_empty(), false);
-        }
-    }
-
-    #[test]
-    fn test_file_exist() {
-        let test_cases = vec![("", false), ("src", false), ("Cargo.toml", true)];
-
-        for (path, expected) in test_cases {
-            let result = file_exist(path);
-
-            assert_eq!(result, expected);
-        }
-    }
 }
@@ -0,0 +1,260 @@
+use rusqlite::{Connection, NO_PARAMS};
+
+use crate::errors::JobSearchError;
+
+/// A single, ordered step of the database schema.
+///
+/// The version a database is on is stored in `PRAGMA user_version`. A brand
+/// new database starts at version 0 and every migration with a higher version
+/// is applied, in order, inside its own transaction.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    sql: &'static str,
+}
+
+// The first migration only uses `IF NOT EXISTS` statements so databases that
+// were created from `testing.sql` (which are at user_version 0) are adopted
+// without touching their data.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        sql: include_str!("migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "foreign keys on applied_to",
+        sql: include_str!("migrations/0002_applied_to_foreign_keys.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "interview scheduling",
+        sql: include_str!("migrations/0003_interview_scheduling.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "application status history",
+        sql: include_str!("migrations/0004_application_status.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "full-text search index",
+        sql: include_str!("migrations/0005_search_index.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "calendar event ids on interviews",
+        sql: include_str!("migrations/0006_interview_calendar_uid.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "reminders and completed interviews",
+        sql: include_str!("migrations/0007_reminders.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "notes",
+        sql: include_str!("migrations/0008_notes.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "tags",
+        sql: include_str!("migrations/0009_tags.sql"),
+    },
+    Migration {
+        version: 10,
+        description: "audit log",
+        sql: include_str!("migrations/0010_audit_log.sql"),
+    },
+    Migration {
+        version: 11,
+        description: "records archived with their company",
+        sql: include_str!("migrations/0011_archived_with_company.sql"),
+    },
+];
+
+/// The schema version this build of the library knows how to work with.
+pub fn latest_version() -> u32 {
+    MIGRATIONS
+        .last()
+        .map(|migration| migration.version)
+        .unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<u32, JobSearchError> {
+    let version: i64 = conn.query_row("PRAGMA user_version", NO_PARAMS, |row| row.get(0))?;
+
+    Ok(version as u32)
+}
+
+/// Returns the migrations that have not been applied to the database yet.
+///
+/// Fails if the database was written by a newer version of the library.
+pub fn pending_migrations(conn: &Connection) -> Result<Vec<&'static Migration>, JobSearchError> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(JobSearchError::SchemaTooNew {
+            found: current,
+            latest,
+        });
+    }
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > current)
+        .collect())
+}
+
+/// Brings the database up to `latest_version`, returning the versions that
+/// were applied.
+pub fn migrate(conn: &mut Connection) -> Result<Vec<u32>, JobSearchError> {
+    let pending = pending_migrations(conn)?;
+
+    // Rebuilding a table drops the old one, which must not cascade or fail
+    // on foreign keys. This pragma is a no-op inside a transaction, so it is
+    // set around them.
+    let foreign_keys: i64 = conn.query_row("PRAGMA foreign_keys", NO_PARAMS, |row| row.get(0))?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+
+    let applied = apply(conn, pending);
+
+    conn.execute_batch(&format!("PRAGMA foreign_keys = {};", foreign_keys))?;
+
+    applied
+}
+
+fn apply(conn: &mut Connection, pending: Vec<&Migration>) -> Result<Vec<u32>, JobSearchError> {
+    let mut applied = Vec::new();
+
+    for migration in pending {
+        let tx = conn.transaction()?;
+
+        tx.execute_batch(migration.sql)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {};", migration.version))?;
+        tx.commit()?;
+
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::params;
+
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<u32> = MIGRATIONS
+            .iter()
+            .map(|migration| migration.version)
+            .collect();
+
+        for (index, version) in versions.iter().enumerate() {
+            assert_eq!(*version, index as u32 + 1);
+        }
+    }
+
+    #[test]
+    fn test_migrate_new_db() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert_eq!(pending_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+
+        let applied = migrate(&mut conn).unwrap();
+
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(pending_migrations(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_is_idempotent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        migrate(&mut conn).unwrap();
+        let applied = migrate(&mut conn).unwrap();
+
+        assert!(applied.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_keeps_legacy_data() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../experimental/testing.sql"))
+            .unwrap();
+        conn.execute(
+            "INSERT INTO companies (name) VALUES (?1)",
+            params!["legacy"],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let name: String = conn
+            .query_row(
+                "SELECT name FROM companies WHERE id = 1",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(name, "legacy");
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_refuses_newer_db() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let newer = latest_version() + 1;
+        conn.execute_batch(&format!("PRAGMA user_version = {};", newer))
+            .unwrap();
+
+        let result = migrate(&mut conn);
+
+        match result {
+            Err(JobSearchError::SchemaTooNew { found, latest }) => {
+                assert_eq!(found, newer);
+                assert_eq!(latest, latest_version());
+            }
+            _ => panic!("expected SchemaTooNew, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_migrate_clears_dangling_applied_to_references() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../experimental/testing.sql"))
+            .unwrap();
+        conn.execute(
+            "INSERT INTO companies (name) VALUES (?1)",
+            params!["legacy"],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO applied_to (company_id, job_posting_id) VALUES (?1, ?2)",
+            params![1, 42],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let (company_id, job_posting_id): (Option<i32>, Option<i32>) = conn
+            .query_row(
+                "SELECT company_id, job_posting_id FROM applied_to WHERE id = 1",
+                NO_PARAMS,
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+
+        assert_eq!(company_id, Some(1));
+        assert_eq!(job_posting_id, None);
+    }
+}
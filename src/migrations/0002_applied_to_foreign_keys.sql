@@ -0,0 +1,31 @@
+CREATE TABLE "applied_to_new" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"date_applied"	TEXT DEFAULT CURRENT_DATE,
+	"last_updated"	TEXT,
+	"company_id"	INTEGER,
+	"job_posting_id"	INTEGER,
+	"contact_id"	INTEGER,
+	"hide"	INTEGER DEFAULT 0,
+	FOREIGN KEY("company_id") REFERENCES "companies"("id"),
+	FOREIGN KEY("job_posting_id") REFERENCES "job_postings"("id"),
+	FOREIGN KEY("contact_id") REFERENCES "contacts"("id")
+);
+-- References to rows that no longer exist are cleared rather than dropping
+-- the application itself.
+INSERT INTO "applied_to_new" (id, date_applied, last_updated, company_id, job_posting_id, contact_id, hide)
+SELECT id, date_applied, last_updated,
+	(SELECT id FROM companies WHERE companies.id = applied_to.company_id),
+	(SELECT id FROM job_postings WHERE job_postings.id = applied_to.job_posting_id),
+	(SELECT id FROM contacts WHERE contacts.id = applied_to.contact_id),
+	hide
+FROM applied_to;
+DROP TABLE "applied_to";
+ALTER TABLE "applied_to_new" RENAME TO "applied_to";
+CREATE INDEX "applied_to_company_id" ON "applied_to" ("company_id");
+CREATE INDEX "applied_to_job_posting_id" ON "applied_to" ("job_posting_id");
+CREATE INDEX "applied_to_contact_id" ON "applied_to" ("contact_id");
+CREATE TRIGGER "applied_to_last_update" AFTER UPDATE ON applied_to
+BEGIN
+	UPDATE applied_to SET last_updated=CURRENT_TIMESTAMP
+	WHERE id = NEW.id;
+END;
@@ -0,0 +1,13 @@
+ALTER TABLE "interviews" ADD COLUMN "application_id" INTEGER REFERENCES "applied_to"("id");
+ALTER TABLE "interviews" ADD COLUMN "cancelled" INTEGER DEFAULT 0;
+-- Link existing interviews to the latest application for the same company
+-- (and posting, when the interview has one).
+UPDATE "interviews" SET "application_id" = (
+	SELECT applied_to.id FROM applied_to
+	WHERE applied_to.company_id = interviews.company_id
+	AND (interviews.job_posting_id IS NULL OR applied_to.job_posting_id = interviews.job_posting_id)
+	ORDER BY applied_to.date_applied DESC, applied_to.id DESC
+	LIMIT 1
+);
+CREATE INDEX "interviews_application_id" ON "interviews" ("application_id");
+CREATE INDEX "interviews_date" ON "interviews" ("date");
@@ -0,0 +1,23 @@
+ALTER TABLE "applied_to" ADD COLUMN "status" TEXT NOT NULL DEFAULT 'applied';
+CREATE TABLE IF NOT EXISTS "application_status_history" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"application_id"	INTEGER NOT NULL,
+	"status"	TEXT NOT NULL,
+	"changed_date"	TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	FOREIGN KEY("application_id") REFERENCES "applied_to"("id") ON DELETE CASCADE
+);
+CREATE INDEX "application_status_history_application_id" ON "application_status_history" ("application_id");
+-- Existing applications have been in the applied stage since they were made
+INSERT INTO "application_status_history" (application_id, status, changed_date)
+SELECT id, 'applied', COALESCE(datetime(date_applied), CURRENT_TIMESTAMP) FROM applied_to;
+CREATE TRIGGER "applied_to_status_insert" AFTER INSERT ON applied_to
+BEGIN
+	INSERT INTO application_status_history (application_id, status)
+	VALUES (NEW.id, NEW.status);
+END;
+CREATE TRIGGER "applied_to_status_update" AFTER UPDATE OF status ON applied_to
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+	INSERT INTO application_status_history (application_id, status)
+	VALUES (NEW.id, NEW.status);
+END;
@@ -0,0 +1,81 @@
+-- One row per searchable record. kind and record_id point back to the record,
+-- title and body hold its text.
+CREATE VIRTUAL TABLE "search_index" USING fts5(
+	kind UNINDEXED,
+	record_id UNINDEXED,
+	title,
+	body,
+	tokenize = 'porter unicode61'
+);
+INSERT INTO "search_index" (kind, record_id, title, body)
+SELECT 'company', id, name, IFNULL(website, '') || ' ' || IFNULL(address, '') || ' ' || IFNULL(phone, '') FROM companies;
+INSERT INTO "search_index" (kind, record_id, title, body)
+SELECT 'job_posting', id, link, description FROM job_postings;
+INSERT INTO "search_index" (kind, record_id, title, body)
+SELECT 'contact', id, name, IFNULL(email, '') || ' ' || IFNULL(phone, '') || ' ' || IFNULL(description, '') FROM contacts;
+INSERT INTO "search_index" (kind, record_id, title, body)
+SELECT 'interview', interviews.id, companies.name, interviews.description
+FROM interviews LEFT JOIN companies ON companies.id = interviews.company_id;
+
+CREATE TRIGGER "companies_search_insert" AFTER INSERT ON companies
+BEGIN
+	INSERT INTO search_index (kind, record_id, title, body)
+	VALUES ('company', NEW.id, NEW.name, IFNULL(NEW.website, '') || ' ' || IFNULL(NEW.address, '') || ' ' || IFNULL(NEW.phone, ''));
+END;
+CREATE TRIGGER "companies_search_update" AFTER UPDATE OF name, address, website, phone ON companies
+BEGIN
+	UPDATE search_index SET title = NEW.name, body = IFNULL(NEW.website, '') || ' ' || IFNULL(NEW.address, '') || ' ' || IFNULL(NEW.phone, '')
+	WHERE kind = 'company' AND record_id = NEW.id;
+	-- Interviews are found by the name of their company
+	UPDATE search_index SET title = NEW.name
+	WHERE kind = 'interview' AND record_id IN (SELECT id FROM interviews WHERE company_id = NEW.id);
+END;
+CREATE TRIGGER "companies_search_delete" AFTER DELETE ON companies
+BEGIN
+	DELETE FROM search_index WHERE kind = 'company' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER "job_postings_search_insert" AFTER INSERT ON job_postings
+BEGIN
+	INSERT INTO search_index (kind, record_id, title, body)
+	VALUES ('job_posting', NEW.id, NEW.link, NEW.description);
+END;
+CREATE TRIGGER "job_postings_search_update" AFTER UPDATE OF link, description ON job_postings
+BEGIN
+	UPDATE search_index SET title = NEW.link, body = NEW.description
+	WHERE kind = 'job_posting' AND record_id = NEW.id;
+END;
+CREATE TRIGGER "job_postings_search_delete" AFTER DELETE ON job_postings
+BEGIN
+	DELETE FROM search_index WHERE kind = 'job_posting' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER "contacts_search_insert" AFTER INSERT ON contacts
+BEGIN
+	INSERT INTO search_index (kind, record_id, title, body)
+	VALUES ('contact', NEW.id, NEW.name, IFNULL(NEW.email, '') || ' ' || IFNULL(NEW.phone, '') || ' ' || IFNULL(NEW.description, ''));
+END;
+CREATE TRIGGER "contacts_search_update" AFTER UPDATE OF name, email, phone, description ON contacts
+BEGIN
+	UPDATE search_index SET title = NEW.name, body = IFNULL(NEW.email, '') || ' ' || IFNULL(NEW.phone, '') || ' ' || IFNULL(NEW.description, '')
+	WHERE kind = 'contact' AND record_id = NEW.id;
+END;
+CREATE TRIGGER "contacts_search_delete" AFTER DELETE ON contacts
+BEGIN
+	DELETE FROM search_index WHERE kind = 'contact' AND record_id = OLD.id;
+END;
+
+CREATE TRIGGER "interviews_search_insert" AFTER INSERT ON interviews
+BEGIN
+	INSERT INTO search_index (kind, record_id, title, body)
+	VALUES ('interview', NEW.id, (SELECT name FROM companies WHERE id = NEW.company_id), NEW.description);
+END;
+CREATE TRIGGER "interviews_search_update" AFTER UPDATE OF company_id, description ON interviews
+BEGIN
+	UPDATE search_index SET title = (SELECT name FROM companies WHERE id = NEW.company_id), body = NEW.description
+	WHERE kind = 'interview' AND record_id = NEW.id;
+END;
+CREATE TRIGGER "interviews_search_delete" AFTER DELETE ON interviews
+BEGIN
+	DELETE FROM search_index WHERE kind = 'interview' AND record_id = OLD.id;
+END;
@@ -0,0 +1,4 @@
+-- The UID of the calendar event an interview was imported from, so importing
+-- the same calendar again updates the interview instead of adding a copy.
+ALTER TABLE "interviews" ADD COLUMN "ical_uid" TEXT;
+CREATE UNIQUE INDEX "interviews_ical_uid" ON "interviews" ("ical_uid");
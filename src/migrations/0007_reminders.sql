@@ -0,0 +1,33 @@
+-- Things to do by a date, about an application, contact, company or
+-- interview. Completing a recurring reminder adds the next one.
+CREATE TABLE IF NOT EXISTS "reminders" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"title"	TEXT NOT NULL,
+	"due_date"	TEXT NOT NULL,
+	"recurrence"	TEXT,
+	"done"	INTEGER NOT NULL DEFAULT 0,
+	"done_date"	TEXT,
+	"application_id"	INTEGER,
+	"contact_id"	INTEGER,
+	"company_id"	INTEGER,
+	"interview_id"	INTEGER,
+	"created_date"	TEXT DEFAULT CURRENT_TIMESTAMP,
+	"last_updated"	TEXT,
+	"hide"	INTEGER DEFAULT 0,
+	FOREIGN KEY("application_id") REFERENCES "applied_to"("id") ON DELETE CASCADE,
+	FOREIGN KEY("contact_id") REFERENCES "contacts"("id") ON DELETE CASCADE,
+	FOREIGN KEY("company_id") REFERENCES "companies"("id") ON DELETE CASCADE,
+	FOREIGN KEY("interview_id") REFERENCES "interviews"("id") ON DELETE CASCADE
+);
+CREATE INDEX "reminders_due_date" ON "reminders" ("due_date");
+CREATE INDEX "reminders_application_id" ON "reminders" ("application_id");
+CREATE INDEX "reminders_contact_id" ON "reminders" ("contact_id");
+CREATE INDEX "reminders_company_id" ON "reminders" ("company_id");
+CREATE INDEX "reminders_interview_id" ON "reminders" ("interview_id");
+CREATE TRIGGER "reminders_last_update" AFTER UPDATE ON reminders
+BEGIN
+	UPDATE reminders SET last_updated=CURRENT_TIMESTAMP
+	WHERE id = NEW.id;
+END;
+-- Interviews that took place, as opposed to cancelled ones
+ALTER TABLE "interviews" ADD COLUMN "completed" INTEGER DEFAULT 0;
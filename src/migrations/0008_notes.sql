@@ -0,0 +1,96 @@
+-- Timestamped notes about exactly one company, posting, contact, application
+-- or interview. Adding a note never touches the earlier ones.
+CREATE TABLE IF NOT EXISTS "notes" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"body"	TEXT NOT NULL,
+	"company_id"	INTEGER,
+	"job_posting_id"	INTEGER,
+	"contact_id"	INTEGER,
+	"application_id"	INTEGER,
+	"interview_id"	INTEGER,
+	"created_date"	TEXT DEFAULT CURRENT_TIMESTAMP,
+	"last_updated"	TEXT,
+	"hide"	INTEGER DEFAULT 0,
+	CHECK (("company_id" IS NOT NULL) + ("job_posting_id" IS NOT NULL) + ("contact_id" IS NOT NULL)
+		+ ("application_id" IS NOT NULL) + ("interview_id" IS NOT NULL) = 1),
+	FOREIGN KEY("company_id") REFERENCES "companies"("id") ON DELETE CASCADE,
+	FOREIGN KEY("job_posting_id") REFERENCES "job_postings"("id") ON DELETE CASCADE,
+	FOREIGN KEY("contact_id") REFERENCES "contacts"("id") ON DELETE CASCADE,
+	FOREIGN KEY("application_id") REFERENCES "applied_to"("id") ON DELETE CASCADE,
+	FOREIGN KEY("interview_id") REFERENCES "interviews"("id") ON DELETE CASCADE
+);
+CREATE INDEX "notes_company_id" ON "notes" ("company_id");
+CREATE INDEX "notes_job_posting_id" ON "notes" ("job_posting_id");
+CREATE INDEX "notes_contact_id" ON "notes" ("contact_id");
+CREATE INDEX "notes_application_id" ON "notes" ("application_id");
+CREATE INDEX "notes_interview_id" ON "notes" ("interview_id");
+CREATE TRIGGER "notes_last_update" AFTER UPDATE ON notes
+BEGIN
+	UPDATE notes SET last_updated=CURRENT_TIMESTAMP
+	WHERE id = NEW.id;
+END;
+
+-- Notes are found by the name of the company or contact, or the link of the
+-- posting, they are about. Applications and interviews go by their company.
+CREATE TRIGGER "notes_search_insert" AFTER INSERT ON notes
+BEGIN
+	INSERT INTO search_index (kind, record_id, title, body)
+	VALUES ('note', NEW.id, COALESCE(
+		(SELECT name FROM companies WHERE id = NEW.company_id),
+		(SELECT link FROM job_postings WHERE id = NEW.job_posting_id),
+		(SELECT name FROM contacts WHERE id = NEW.contact_id),
+		(SELECT companies.name FROM applied_to JOIN companies ON companies.id = applied_to.company_id
+			WHERE applied_to.id = NEW.application_id),
+		(SELECT companies.name FROM interviews JOIN companies ON companies.id = interviews.company_id
+			WHERE interviews.id = NEW.interview_id)
+	), NEW.body);
+END;
+CREATE TRIGGER "notes_search_update" AFTER UPDATE OF body, company_id, job_posting_id, contact_id,
+	application_id, interview_id ON notes
+BEGIN
+	UPDATE search_index SET body = NEW.body, title = COALESCE(
+		(SELECT name FROM companies WHERE id = NEW.company_id),
+		(SELECT link FROM job_postings WHERE id = NEW.job_posting_id),
+		(SELECT name FROM contacts WHERE id = NEW.contact_id),
+		(SELECT companies.name FROM applied_to JOIN companies ON companies.id = applied_to.company_id
+			WHERE applied_to.id = NEW.application_id),
+		(SELECT companies.name FROM interviews JOIN companies ON companies.id = interviews.company_id
+			WHERE interviews.id = NEW.interview_id)
+	)
+	WHERE kind = 'note' AND record_id = NEW.id;
+END;
+CREATE TRIGGER "notes_search_delete" AFTER DELETE ON notes
+BEGIN
+	DELETE FROM search_index WHERE kind = 'note' AND record_id = OLD.id;
+END;
+
+-- Keep the titles of notes in step with what they are about
+CREATE TRIGGER "companies_search_update_notes" AFTER UPDATE OF name ON companies
+BEGIN
+	UPDATE search_index SET title = NEW.name
+	WHERE kind = 'note' AND record_id IN (
+		SELECT id FROM notes WHERE company_id = NEW.id
+		OR application_id IN (SELECT id FROM applied_to WHERE company_id = NEW.id)
+		OR interview_id IN (SELECT id FROM interviews WHERE company_id = NEW.id)
+	);
+END;
+CREATE TRIGGER "job_postings_search_update_notes" AFTER UPDATE OF link ON job_postings
+BEGIN
+	UPDATE search_index SET title = NEW.link
+	WHERE kind = 'note' AND record_id IN (SELECT id FROM notes WHERE job_posting_id = NEW.id);
+END;
+CREATE TRIGGER "contacts_search_update_notes" AFTER UPDATE OF name ON contacts
+BEGIN
+	UPDATE search_index SET title = NEW.name
+	WHERE kind = 'note' AND record_id IN (SELECT id FROM notes WHERE contact_id = NEW.id);
+END;
+CREATE TRIGGER "applied_to_search_update_notes" AFTER UPDATE OF company_id ON applied_to
+BEGIN
+	UPDATE search_index SET title = (SELECT name FROM companies WHERE id = NEW.company_id)
+	WHERE kind = 'note' AND record_id IN (SELECT id FROM notes WHERE application_id = NEW.id);
+END;
+CREATE TRIGGER "interviews_search_update_notes" AFTER UPDATE OF company_id ON interviews
+BEGIN
+	UPDATE search_index SET title = (SELECT name FROM companies WHERE id = NEW.company_id)
+	WHERE kind = 'note' AND record_id IN (SELECT id FROM notes WHERE interview_id = NEW.id);
+END;
@@ -0,0 +1,51 @@
+-- Free-form labels such as "fintech" or "remote-only". Names are unique
+-- regardless of case.
+CREATE TABLE IF NOT EXISTS "tags" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"name"	TEXT NOT NULL UNIQUE COLLATE NOCASE,
+	"created_date"	TEXT DEFAULT CURRENT_TIMESTAMP,
+	"last_updated"	TEXT,
+	"hide"	INTEGER DEFAULT 0
+);
+CREATE TRIGGER "tags_last_update" AFTER UPDATE ON tags
+BEGIN
+	UPDATE tags SET last_updated=CURRENT_TIMESTAMP
+	WHERE id = NEW.id;
+END;
+
+-- Which records carry which tags. Deleting either side removes the link.
+CREATE TABLE IF NOT EXISTS "company_tags" (
+	"tag_id"	INTEGER NOT NULL,
+	"company_id"	INTEGER NOT NULL,
+	PRIMARY KEY("tag_id", "company_id"),
+	FOREIGN KEY("tag_id") REFERENCES "tags"("id") ON DELETE CASCADE,
+	FOREIGN KEY("company_id") REFERENCES "companies"("id") ON DELETE CASCADE
+);
+CREATE INDEX "company_tags_company_id" ON "company_tags" ("company_id");
+
+CREATE TABLE IF NOT EXISTS "job_posting_tags" (
+	"tag_id"	INTEGER NOT NULL,
+	"job_posting_id"	INTEGER NOT NULL,
+	PRIMARY KEY("tag_id", "job_posting_id"),
+	FOREIGN KEY("tag_id") REFERENCES "tags"("id") ON DELETE CASCADE,
+	FOREIGN KEY("job_posting_id") REFERENCES "job_postings"("id") ON DELETE CASCADE
+);
+CREATE INDEX "job_posting_tags_job_posting_id" ON "job_posting_tags" ("job_posting_id");
+
+CREATE TABLE IF NOT EXISTS "contact_tags" (
+	"tag_id"	INTEGER NOT NULL,
+	"contact_id"	INTEGER NOT NULL,
+	PRIMARY KEY("tag_id", "contact_id"),
+	FOREIGN KEY("tag_id") REFERENCES "tags"("id") ON DELETE CASCADE,
+	FOREIGN KEY("contact_id") REFERENCES "contacts"("id") ON DELETE CASCADE
+);
+CREATE INDEX "contact_tags_contact_id" ON "contact_tags" ("contact_id");
+
+CREATE TABLE IF NOT EXISTS "application_tags" (
+	"tag_id"	INTEGER NOT NULL,
+	"application_id"	INTEGER NOT NULL,
+	PRIMARY KEY("tag_id", "application_id"),
+	FOREIGN KEY("tag_id") REFERENCES "tags"("id") ON DELETE CASCADE,
+	FOREIGN KEY("application_id") REFERENCES "applied_to"("id") ON DELETE CASCADE
+);
+CREATE INDEX "application_tags_application_id" ON "application_tags" ("application_id");
@@ -0,0 +1,392 @@
+-- Every insert, update, hide and delete, with the values of the record
+-- before and after it as JSON objects. Records that existed before this
+-- migration have no insert. last_updated is left out of the values, so the
+-- update the *_last_update triggers make of it is not recorded again, and
+-- updates that change nothing are not recorded at all. Links between tags
+-- and records have no id of their own and are not recorded.
+CREATE TABLE IF NOT EXISTS "audit_log" (
+	"id"	INTEGER PRIMARY KEY AUTOINCREMENT,
+	"table_name"	TEXT NOT NULL,
+	"record_id"	INTEGER NOT NULL,
+	"action"	TEXT NOT NULL CHECK ("action" IN ('insert', 'update', 'hide', 'unhide', 'delete')),
+	"old_values"	TEXT,
+	"new_values"	TEXT,
+	"changed_date"	TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now'))
+);
+CREATE INDEX "audit_log_record" ON "audit_log" ("table_name", "record_id");
+
+CREATE TRIGGER "companies_audit_insert" AFTER INSERT ON companies
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('companies', NEW.id, 'insert',
+		json_object('id', NEW.id, 'name', NEW.name, 'address', NEW.address,
+		'website', NEW.website, 'phone', NEW.phone, 'created_date', NEW.created_date,
+		'hide', NEW.hide));
+END;
+CREATE TRIGGER "companies_audit_update" AFTER UPDATE ON companies
+WHEN json_object('id', OLD.id, 'name', OLD.name, 'address', OLD.address, 'website', OLD.website,
+	'phone', OLD.phone, 'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'name', NEW.name, 'address', NEW.address, 'website', NEW.website,
+	'phone', NEW.phone, 'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('companies', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'name', OLD.name, 'address', OLD.address,
+		'website', OLD.website, 'phone', OLD.phone, 'created_date', OLD.created_date,
+		'hide', OLD.hide),
+		json_object('id', NEW.id, 'name', NEW.name, 'address', NEW.address,
+		'website', NEW.website, 'phone', NEW.phone, 'created_date', NEW.created_date,
+		'hide', NEW.hide));
+END;
+CREATE TRIGGER "companies_audit_delete" AFTER DELETE ON companies
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('companies', OLD.id, 'delete',
+		json_object('id', OLD.id, 'name', OLD.name, 'address', OLD.address,
+		'website', OLD.website, 'phone', OLD.phone, 'created_date', OLD.created_date,
+		'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "job_postings_audit_insert" AFTER INSERT ON job_postings
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('job_postings', NEW.id, 'insert',
+		json_object('id', NEW.id, 'link', NEW.link, 'description', NEW.description,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "job_postings_audit_update" AFTER UPDATE ON job_postings
+WHEN json_object('id', OLD.id, 'link', OLD.link, 'description', OLD.description,
+	'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'link', NEW.link, 'description', NEW.description,
+	'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('job_postings', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'link', OLD.link, 'description', OLD.description,
+		'created_date', OLD.created_date, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'link', NEW.link, 'description', NEW.description,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "job_postings_audit_delete" AFTER DELETE ON job_postings
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('job_postings', OLD.id, 'delete',
+		json_object('id', OLD.id, 'link', OLD.link, 'description', OLD.description,
+		'created_date', OLD.created_date, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "contact_types_audit_insert" AFTER INSERT ON contact_types
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('contact_types', NEW.id, 'insert',
+		json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "contact_types_audit_update" AFTER UPDATE ON contact_types
+WHEN json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('contact_types', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "contact_types_audit_delete" AFTER DELETE ON contact_types
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('contact_types', OLD.id, 'delete',
+		json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "contacts_audit_insert" AFTER INSERT ON contacts
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('contacts', NEW.id, 'insert',
+		json_object('id', NEW.id, 'name', NEW.name, 'email', NEW.email, 'phone', NEW.phone,
+		'description', NEW.description, 'type_id', NEW.type_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "contacts_audit_update" AFTER UPDATE ON contacts
+WHEN json_object('id', OLD.id, 'name', OLD.name, 'email', OLD.email, 'phone', OLD.phone,
+	'description', OLD.description, 'type_id', OLD.type_id,
+	'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'name', NEW.name, 'email', NEW.email, 'phone', NEW.phone,
+	'description', NEW.description, 'type_id', NEW.type_id,
+	'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('contacts', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'name', OLD.name, 'email', OLD.email, 'phone', OLD.phone,
+		'description', OLD.description, 'type_id', OLD.type_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'name', NEW.name, 'email', NEW.email, 'phone', NEW.phone,
+		'description', NEW.description, 'type_id', NEW.type_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "contacts_audit_delete" AFTER DELETE ON contacts
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('contacts', OLD.id, 'delete',
+		json_object('id', OLD.id, 'name', OLD.name, 'email', OLD.email, 'phone', OLD.phone,
+		'description', OLD.description, 'type_id', OLD.type_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "interview_types_audit_insert" AFTER INSERT ON interview_types
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('interview_types', NEW.id, 'insert',
+		json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "interview_types_audit_update" AFTER UPDATE ON interview_types
+WHEN json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('interview_types', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'name', NEW.name, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "interview_types_audit_delete" AFTER DELETE ON interview_types
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('interview_types', OLD.id, 'delete',
+		json_object('id', OLD.id, 'name', OLD.name, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "applied_to_audit_insert" AFTER INSERT ON applied_to
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('applied_to', NEW.id, 'insert',
+		json_object('id', NEW.id, 'date_applied', NEW.date_applied, 'company_id', NEW.company_id,
+		'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+		'status', NEW.status, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "applied_to_audit_update" AFTER UPDATE ON applied_to
+WHEN json_object('id', OLD.id, 'date_applied', OLD.date_applied, 'company_id', OLD.company_id,
+	'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+	'status', OLD.status, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'date_applied', NEW.date_applied, 'company_id', NEW.company_id,
+	'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+	'status', NEW.status, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('applied_to', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'date_applied', OLD.date_applied, 'company_id', OLD.company_id,
+		'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+		'status', OLD.status, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'date_applied', NEW.date_applied, 'company_id', NEW.company_id,
+		'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+		'status', NEW.status, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "applied_to_audit_delete" AFTER DELETE ON applied_to
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('applied_to', OLD.id, 'delete',
+		json_object('id', OLD.id, 'date_applied', OLD.date_applied, 'company_id', OLD.company_id,
+		'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+		'status', OLD.status, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "application_status_history_audit_insert" AFTER INSERT ON application_status_history
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('application_status_history', NEW.id, 'insert',
+		json_object('id', NEW.id, 'application_id', NEW.application_id, 'status', NEW.status,
+		'changed_date', NEW.changed_date));
+END;
+CREATE TRIGGER "application_status_history_audit_update" AFTER UPDATE ON application_status_history
+WHEN json_object('id', OLD.id, 'application_id', OLD.application_id, 'status', OLD.status,
+	'changed_date', OLD.changed_date)
+	IS NOT json_object('id', NEW.id, 'application_id', NEW.application_id, 'status', NEW.status,
+	'changed_date', NEW.changed_date)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('application_status_history', NEW.id, 'update',
+		json_object('id', OLD.id, 'application_id', OLD.application_id, 'status', OLD.status,
+		'changed_date', OLD.changed_date),
+		json_object('id', NEW.id, 'application_id', NEW.application_id, 'status', NEW.status,
+		'changed_date', NEW.changed_date));
+END;
+CREATE TRIGGER "application_status_history_audit_delete" AFTER DELETE ON application_status_history
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('application_status_history', OLD.id, 'delete',
+		json_object('id', OLD.id, 'application_id', OLD.application_id, 'status', OLD.status,
+		'changed_date', OLD.changed_date));
+END;
+
+CREATE TRIGGER "interviews_audit_insert" AFTER INSERT ON interviews
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('interviews', NEW.id, 'insert',
+		json_object('id', NEW.id, 'interview_type_id', NEW.interview_type_id, 'date', NEW.date,
+		'company_id', NEW.company_id, 'contact_id', NEW.contact_id,
+		'job_posting_id', NEW.job_posting_id, 'application_id', NEW.application_id,
+		'description', NEW.description, 'cancelled', NEW.cancelled,
+		'completed', NEW.completed, 'ical_uid', NEW.ical_uid,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "interviews_audit_update" AFTER UPDATE ON interviews
+WHEN json_object('id', OLD.id, 'interview_type_id', OLD.interview_type_id, 'date', OLD.date,
+	'company_id', OLD.company_id, 'contact_id', OLD.contact_id,
+	'job_posting_id', OLD.job_posting_id, 'application_id', OLD.application_id,
+	'description', OLD.description, 'cancelled', OLD.cancelled,
+	'completed', OLD.completed, 'ical_uid', OLD.ical_uid,
+	'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'interview_type_id', NEW.interview_type_id, 'date', NEW.date,
+	'company_id', NEW.company_id, 'contact_id', NEW.contact_id,
+	'job_posting_id', NEW.job_posting_id, 'application_id', NEW.application_id,
+	'description', NEW.description, 'cancelled', NEW.cancelled,
+	'completed', NEW.completed, 'ical_uid', NEW.ical_uid,
+	'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('interviews', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'interview_type_id', OLD.interview_type_id, 'date', OLD.date,
+		'company_id', OLD.company_id, 'contact_id', OLD.contact_id,
+		'job_posting_id', OLD.job_posting_id, 'application_id', OLD.application_id,
+		'description', OLD.description, 'cancelled', OLD.cancelled,
+		'completed', OLD.completed, 'ical_uid', OLD.ical_uid,
+		'created_date', OLD.created_date, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'interview_type_id', NEW.interview_type_id, 'date', NEW.date,
+		'company_id', NEW.company_id, 'contact_id', NEW.contact_id,
+		'job_posting_id', NEW.job_posting_id, 'application_id', NEW.application_id,
+		'description', NEW.description, 'cancelled', NEW.cancelled,
+		'completed', NEW.completed, 'ical_uid', NEW.ical_uid,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "interviews_audit_delete" AFTER DELETE ON interviews
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('interviews', OLD.id, 'delete',
+		json_object('id', OLD.id, 'interview_type_id', OLD.interview_type_id, 'date', OLD.date,
+		'company_id', OLD.company_id, 'contact_id', OLD.contact_id,
+		'job_posting_id', OLD.job_posting_id, 'application_id', OLD.application_id,
+		'description', OLD.description, 'cancelled', OLD.cancelled,
+		'completed', OLD.completed, 'ical_uid', OLD.ical_uid,
+		'created_date', OLD.created_date, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "reminders_audit_insert" AFTER INSERT ON reminders
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('reminders', NEW.id, 'insert',
+		json_object('id', NEW.id, 'title', NEW.title, 'due_date', NEW.due_date,
+		'recurrence', NEW.recurrence, 'done', NEW.done, 'done_date', NEW.done_date,
+		'application_id', NEW.application_id, 'contact_id', NEW.contact_id,
+		'company_id', NEW.company_id, 'interview_id', NEW.interview_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "reminders_audit_update" AFTER UPDATE ON reminders
+WHEN json_object('id', OLD.id, 'title', OLD.title, 'due_date', OLD.due_date,
+	'recurrence', OLD.recurrence, 'done', OLD.done, 'done_date', OLD.done_date,
+	'application_id', OLD.application_id, 'contact_id', OLD.contact_id,
+	'company_id', OLD.company_id, 'interview_id', OLD.interview_id,
+	'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'title', NEW.title, 'due_date', NEW.due_date,
+	'recurrence', NEW.recurrence, 'done', NEW.done, 'done_date', NEW.done_date,
+	'application_id', NEW.application_id, 'contact_id', NEW.contact_id,
+	'company_id', NEW.company_id, 'interview_id', NEW.interview_id,
+	'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('reminders', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'title', OLD.title, 'due_date', OLD.due_date,
+		'recurrence', OLD.recurrence, 'done', OLD.done, 'done_date', OLD.done_date,
+		'application_id', OLD.application_id, 'contact_id', OLD.contact_id,
+		'company_id', OLD.company_id, 'interview_id', OLD.interview_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'title', NEW.title, 'due_date', NEW.due_date,
+		'recurrence', NEW.recurrence, 'done', NEW.done, 'done_date', NEW.done_date,
+		'application_id', NEW.application_id, 'contact_id', NEW.contact_id,
+		'company_id', NEW.company_id, 'interview_id', NEW.interview_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "reminders_audit_delete" AFTER DELETE ON reminders
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('reminders', OLD.id, 'delete',
+		json_object('id', OLD.id, 'title', OLD.title, 'due_date', OLD.due_date,
+		'recurrence', OLD.recurrence, 'done', OLD.done, 'done_date', OLD.done_date,
+		'application_id', OLD.application_id, 'contact_id', OLD.contact_id,
+		'company_id', OLD.company_id, 'interview_id', OLD.interview_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "notes_audit_insert" AFTER INSERT ON notes
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('notes', NEW.id, 'insert',
+		json_object('id', NEW.id, 'body', NEW.body, 'company_id', NEW.company_id,
+		'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+		'application_id', NEW.application_id, 'interview_id', NEW.interview_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "notes_audit_update" AFTER UPDATE ON notes
+WHEN json_object('id', OLD.id, 'body', OLD.body, 'company_id', OLD.company_id,
+	'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+	'application_id', OLD.application_id, 'interview_id', OLD.interview_id,
+	'created_date', OLD.created_date, 'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'body', NEW.body, 'company_id', NEW.company_id,
+	'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+	'application_id', NEW.application_id, 'interview_id', NEW.interview_id,
+	'created_date', NEW.created_date, 'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('notes', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'body', OLD.body, 'company_id', OLD.company_id,
+		'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+		'application_id', OLD.application_id, 'interview_id', OLD.interview_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide),
+		json_object('id', NEW.id, 'body', NEW.body, 'company_id', NEW.company_id,
+		'job_posting_id', NEW.job_posting_id, 'contact_id', NEW.contact_id,
+		'application_id', NEW.application_id, 'interview_id', NEW.interview_id,
+		'created_date', NEW.created_date, 'hide', NEW.hide));
+END;
+CREATE TRIGGER "notes_audit_delete" AFTER DELETE ON notes
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('notes', OLD.id, 'delete',
+		json_object('id', OLD.id, 'body', OLD.body, 'company_id', OLD.company_id,
+		'job_posting_id', OLD.job_posting_id, 'contact_id', OLD.contact_id,
+		'application_id', OLD.application_id, 'interview_id', OLD.interview_id,
+		'created_date', OLD.created_date, 'hide', OLD.hide));
+END;
+
+CREATE TRIGGER "tags_audit_insert" AFTER INSERT ON tags
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, new_values)
+	VALUES ('tags', NEW.id, 'insert',
+		json_object('id', NEW.id, 'name', NEW.name, 'created_date', NEW.created_date,
+		'hide', NEW.hide));
+END;
+CREATE TRIGGER "tags_audit_update" AFTER UPDATE ON tags
+WHEN json_object('id', OLD.id, 'name', OLD.name, 'created_date', OLD.created_date,
+	'hide', OLD.hide)
+	IS NOT json_object('id', NEW.id, 'name', NEW.name, 'created_date', NEW.created_date,
+	'hide', NEW.hide)
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values, new_values)
+	VALUES ('tags', NEW.id, CASE WHEN IFNULL(OLD.hide, 0) = IFNULL(NEW.hide, 0) THEN 'update'
+			WHEN IFNULL(NEW.hide, 0) THEN 'hide' ELSE 'unhide' END,
+		json_object('id', OLD.id, 'name', OLD.name, 'created_date', OLD.created_date,
+		'hide', OLD.hide),
+		json_object('id', NEW.id, 'name', NEW.name, 'created_date', NEW.created_date,
+		'hide', NEW.hide));
+END;
+CREATE TRIGGER "tags_audit_delete" AFTER DELETE ON tags
+BEGIN
+	INSERT INTO audit_log (table_name, record_id, action, old_values)
+	VALUES ('tags', OLD.id, 'delete',
+		json_object('id', OLD.id, 'name', OLD.name, 'created_date', OLD.created_date,
+		'hide', OLD.hide));
+END;
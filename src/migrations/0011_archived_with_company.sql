@@ -0,0 +1,10 @@
+-- The postings, applications and interviews that archiving a company hid
+-- along with it, so unarchiving shows those again and not the ones that
+-- were archived on their own.
+CREATE TABLE IF NOT EXISTS "archived_with_company" (
+	"company_id"	INTEGER NOT NULL,
+	"table_name"	TEXT NOT NULL,
+	"record_id"	INTEGER NOT NULL,
+	PRIMARY KEY("company_id", "table_name", "record_id"),
+	FOREIGN KEY("company_id") REFERENCES "companies"("id") ON DELETE CASCADE
+);
@@ -0,0 +1,429 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::search::{self, SearchKind};
+use crate::timestamp;
+
+const SELECT_NOTES: &str = "SELECT id, body, company_id, job_posting_id, contact_id, \
+     application_id, interview_id, created_date, last_updated, hide FROM notes";
+
+/// The record a note is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteTarget {
+    Company(i32),
+    JobPosting(i32),
+    Contact(i32),
+    Application(i32),
+    Interview(i32),
+}
+
+impl NoteTarget {
+    // The column of the notes table that points to the record
+    fn column(self) -> &'static str {
+        match self {
+            NoteTarget::Company(_) => "company_id",
+            NoteTarget::JobPosting(_) => "job_posting_id",
+            NoteTarget::Contact(_) => "contact_id",
+            NoteTarget::Application(_) => "application_id",
+            NoteTarget::Interview(_) => "interview_id",
+        }
+    }
+
+    fn id(self) -> i32 {
+        match self {
+            NoteTarget::Company(id)
+            | NoteTarget::JobPosting(id)
+            | NoteTarget::Contact(id)
+            | NoteTarget::Application(id)
+            | NoteTarget::Interview(id) => id,
+        }
+    }
+}
+
+/// A timestamped entry about one company, posting, contact, application or
+/// interview, such as what was said in a phone screen. Each note is kept
+/// separately, so adding one never loses the earlier ones.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    pub id: Option<i32>,
+    pub body: String,
+    /// Exactly one of the ids is set, see `target`.
+    pub company_id: Option<i32>,
+    pub job_posting_id: Option<i32>,
+    pub contact_id: Option<i32>,
+    pub application_id: Option<i32>,
+    pub interview_id: Option<i32>,
+    /// When the note was written. Editing it only moves `last_updated`.
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+}
+
+impl Note {
+    pub fn new(target: NoteTarget, body: String) -> Note {
+        let mut note = Note {
+            id: None,
+            body,
+            company_id: None,
+            job_posting_id: None,
+            contact_id: None,
+            application_id: None,
+            interview_id: None,
+            created_date: None,
+            last_updated: None,
+            hide: false,
+        };
+        note.set_target(target);
+
+        note
+    }
+
+    /// The record the note is about, or `None` if not exactly one of the
+    /// ids is set.
+    pub fn target(&self) -> Option<NoteTarget> {
+        let targets: Vec<NoteTarget> = vec![
+            self.company_id.map(NoteTarget::Company),
+            self.job_posting_id.map(NoteTarget::JobPosting),
+            self.contact_id.map(NoteTarget::Contact),
+            self.application_id.map(NoteTarget::Application),
+            self.interview_id.map(NoteTarget::Interview),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match targets[..] {
+            [target] => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Moves the note to another record.
+    pub fn set_target(&mut self, target: NoteTarget) {
+        self.company_id = None;
+        self.job_posting_id = None;
+        self.contact_id = None;
+        self.application_id = None;
+        self.interview_id = None;
+
+        match target {
+            NoteTarget::Company(id) => self.company_id = Some(id),
+            NoteTarget::JobPosting(id) => self.job_posting_id = Some(id),
+            NoteTarget::Contact(id) => self.contact_id = Some(id),
+            NoteTarget::Application(id) => self.application_id = Some(id),
+            NoteTarget::Interview(id) => self.interview_id = Some(id),
+        }
+    }
+
+    fn validate(&self) -> Result<(), JobSearchError> {
+        require_text(&self.body, "note")?;
+
+        if self.target().is_none() {
+            return Err(JobSearchError::InvalidInput(
+                "a note has to be about exactly one record".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The notes about `target`, oldest first.
+    pub(crate) fn get_by_target(
+        conn: &Connection,
+        target: NoteTarget,
+    ) -> Result<Vec<Note>, JobSearchError> {
+        entity::query(
+            conn,
+            &format!(
+                "{} WHERE {} = (?1) AND {} ORDER BY {}",
+                SELECT_NOTES,
+                target.column(),
+                Visibility::Visible.condition(Note::TABLE),
+                Note::ORDER_BY
+            ),
+            params![target.id()],
+        )
+    }
+
+    /// The notes whose text matches `query`, oldest first. `query` uses the
+    /// same syntax as `Database::search`.
+    pub(crate) fn search(conn: &Connection, query: &str) -> Result<Vec<Note>, JobSearchError> {
+        let mut notes = search::search(conn, query, None)?
+            .into_iter()
+            .filter(|hit| hit.kind == SearchKind::Note)
+            .map(|hit| Note::get_by_id(conn, hit.id))
+            .collect::<Result<Vec<Note>, JobSearchError>>()?;
+
+        notes.sort_by_key(|note| (note.created_date, note.id));
+
+        Ok(notes)
+    }
+}
+
+impl Entity for Note {
+    const TABLE: &'static str = "notes";
+    const NAME: &'static str = "note";
+    const SELECT: &'static str = SELECT_NOTES;
+    const ORDER_BY: &'static str = "datetime(created_date), id";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("notes.created_date"),
+        company: Some(
+            "? IN (notes.company_id, \
+             (SELECT company_id FROM applied_to WHERE id = notes.application_id), \
+             (SELECT company_id FROM interviews WHERE id = notes.interview_id))",
+        ),
+        status: Some("notes.application_id IN (SELECT id FROM applied_to WHERE status = ?)"),
+        contact_type: Some("notes.contact_id IN (SELECT id FROM contacts WHERE type_id = ?)"),
+        text: &["notes.body"],
+        name: None,
+        // The tags of the record the note is about
+        tag: Some(
+            "EXISTS (SELECT 1 FROM tags WHERE tags.name = ? AND (\
+             tags.id IN (SELECT tag_id FROM company_tags WHERE company_id = notes.company_id) \
+             OR tags.id IN (SELECT tag_id FROM job_posting_tags \
+             WHERE job_posting_id = notes.job_posting_id) \
+             OR tags.id IN (SELECT tag_id FROM contact_tags WHERE contact_id = notes.contact_id) \
+             OR tags.id IN (SELECT tag_id FROM application_tags \
+             WHERE application_id = notes.application_id)))",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Note, JobSearchError> {
+        let note = Note {
+            id: row.get(0)?,
+            body: row.get(1)?,
+            company_id: row.get(2)?,
+            job_posting_id: row.get(3)?,
+            contact_id: row.get(4)?,
+            application_id: row.get(5)?,
+            interview_id: row.get(6)?,
+            created_date: timestamp::get(row, 7)?,
+            last_updated: timestamp::get(row, 8)?,
+            hide: row.get::<_, Option<bool>>(9)?.unwrap_or(false),
+        };
+
+        Ok(note)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        self.validate()?;
+
+        let _ = conn.execute(
+            "INSERT INTO notes (body, company_id, job_posting_id, contact_id, application_id, \
+             interview_id, hide, created_date, last_updated) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, CURRENT_TIMESTAMP), ?9)",
+            params![
+                self.body,
+                self.company_id,
+                self.job_posting_id,
+                self.contact_id,
+                self.application_id,
+                self.interview_id,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        *self = Note::get_by_id(conn, conn.last_insert_rowid() as i32)?;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "note")?;
+        self.validate()?;
+
+        let _ = conn.execute(
+            "UPDATE notes SET body = (?1), company_id = (?2), job_posting_id = (?3), \
+             contact_id = (?4), application_id = (?5), interview_id = (?6), hide = (?7) \
+             WHERE id = (?8)",
+            params![
+                self.body,
+                self.company_id,
+                self.job_posting_id,
+                self.contact_id,
+                self.application_id,
+                self.interview_id,
+                self.hide,
+                id
+            ],
+        )?;
+
+        *self = Note::get_by_id(conn, id)?;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Note>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Note> {
+    /// The notes about `target`, oldest first.
+    pub fn get_by_target(&self, target: NoteTarget) -> Result<Vec<Note>, JobSearchError> {
+        Note::get_by_target(self.conn(), target)
+    }
+
+    /// The notes whose text, or the name of what they are about, matches
+    /// `query`, oldest first. Hidden notes are left out.
+    pub fn search(&self, query: &str) -> Result<Vec<Note>, JobSearchError> {
+        Note::search(self.conn(), query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::Application;
+    use crate::company::Company;
+    use crate::create_in_memory_db;
+
+    fn add(conn: &Connection, target: NoteTarget, body: &str) -> Note {
+        let mut note = Note::new(target, body.to_string());
+        note.add_to_db(conn).unwrap();
+        note
+    }
+
+    fn bodies(notes: &[Note]) -> Vec<&str> {
+        notes.iter().map(|note| note.body.as_str()).collect()
+    }
+
+    #[test]
+    fn test_target() {
+        let mut note = Note::new(NoteTarget::Contact(2), "Likes Rust".to_string());
+
+        assert_eq!(note.contact_id, Some(2));
+        assert_eq!(note.target(), Some(NoteTarget::Contact(2)));
+
+        note.set_target(NoteTarget::Interview(3));
+        assert_eq!(note.contact_id, None);
+        assert_eq!(note.target(), Some(NoteTarget::Interview(3)));
+
+        note.company_id = Some(1);
+        assert_eq!(note.target(), None);
+    }
+
+    #[test]
+    fn test_add_and_edit() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let target = NoteTarget::Company(company.id.unwrap());
+
+        let mut note = add(&conn, target, "Recruiter called");
+        assert_ne!(note.created_date, None);
+        assert_eq!(note.last_updated, None);
+
+        let created_date = note.created_date;
+        note.body = "Recruiter called about the SRE role".to_string();
+        note.update_db(&conn).unwrap();
+
+        assert_eq!(note.created_date, created_date);
+        assert_ne!(note.last_updated, None);
+
+        let mut empty = Note::new(target, " ".to_string());
+        assert!(matches!(
+            empty.add_to_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+
+        note.job_posting_id = Some(1);
+        assert!(matches!(
+            note.update_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+
+        let mut unknown = Note::new(NoteTarget::Contact(42), "Who?".to_string());
+        assert!(matches!(
+            unknown.add_to_db(&conn),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_by_target() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut application = Application::new(company.id.unwrap(), None, None);
+        application.add_to_db(&conn).unwrap();
+        let target = NoteTarget::Application(application.id.unwrap());
+
+        let mut second = Note::new(target, "Phone screen went well".to_string());
+        second.created_date = Some("2020-05-08T10:00:00Z".parse().unwrap());
+        second.add_to_db(&conn).unwrap();
+        let mut first = Note::new(target, "Sent the application".to_string());
+        first.created_date = Some("2020-05-01T10:00:00Z".parse().unwrap());
+        first.add_to_db(&conn).unwrap();
+        add(
+            &conn,
+            NoteTarget::Company(company.id.unwrap()),
+            "Big company",
+        );
+        let mut hidden = add(&conn, target, "Hidden");
+        hidden.archive(&conn).unwrap();
+
+        assert_eq!(
+            bodies(&Note::get_by_target(&conn, target).unwrap()),
+            vec!["Sent the application", "Phone screen went well"]
+        );
+
+        // Filtering by company finds the notes about its applications too
+        let filter = crate::filter::Filter::new().company(company.id.unwrap());
+        assert_eq!(Note::list(&conn, &filter).unwrap().items.len(), 3);
+
+        application.delete_from_db(&conn).unwrap();
+        assert_eq!(Note::count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_search() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let target = NoteTarget::Company(company.id.unwrap());
+
+        add(&conn, target, "They asked about Kubernetes");
+        add(&conn, target, "Salary range discussed");
+
+        assert_eq!(
+            bodies(&Note::search(&conn, "kubernetes").unwrap()),
+            vec!["They asked about Kubernetes"]
+        );
+        // Notes are found by the name of what they are about
+        assert_eq!(Note::search(&conn, "mozilla").unwrap().len(), 2);
+
+        company.name = "Firefox".to_string();
+        company.update_db(&conn).unwrap();
+
+        assert!(Note::search(&conn, "mozilla").unwrap().is_empty());
+        assert_eq!(Note::search(&conn, "firefox").unwrap().len(), 2);
+
+        let hits = search::search(&conn, "salary", None).unwrap();
+        assert_eq!(hits[0].kind, SearchKind::Note);
+        assert_eq!(hits[0].title, "Firefox");
+    }
+}
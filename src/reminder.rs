@@ -0,0 +1,701 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, Row, ToSql};
+
+use crate::application::Application;
+use crate::company::Company;
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::interview::Interview;
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+const SELECT_REMINDERS: &str = "SELECT id, title, due_date, recurrence, done, done_date, \
+     application_id, contact_id, company_id, interview_id, created_date, last_updated, hide \
+     FROM reminders";
+
+/// How often a reminder comes back once it is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    pub const ALL: [Recurrence; 3] = [Recurrence::Daily, Recurrence::Weekly, Recurrence::Monthly];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly => "monthly",
+        }
+    }
+
+    /// The due date after `date`, or `None` past the last date there is.
+    /// Monthly reminders due on a day the next month does not have fall on
+    /// its last day.
+    pub fn next(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => date.checked_add_days(Days::new(1)),
+            Recurrence::Weekly => date.checked_add_days(Days::new(7)),
+            Recurrence::Monthly => date.checked_add_months(Months::new(1)),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Recurrence, String> {
+        Recurrence::ALL
+            .iter()
+            .find(|recurrence| recurrence.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("unknown recurrence: {}", s))
+    }
+}
+
+impl ToSql for Recurrence {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for Recurrence {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err: String| FromSqlError::Other(err.into()))
+    }
+}
+
+/// Something to do by a date, such as pinging a recruiter, optionally about
+/// an application, contact, company or interview.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reminder {
+    pub id: Option<i32>,
+    pub title: String,
+    pub due_date: NaiveDate,
+    /// Completing a recurring reminder adds the next one.
+    pub recurrence: Option<Recurrence>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub done: bool,
+    pub done_date: Option<DateTime<Utc>>,
+    pub application_id: Option<i32>,
+    pub contact_id: Option<i32>,
+    pub company_id: Option<i32>,
+    pub interview_id: Option<i32>,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+}
+
+/// A reminder to add when an interview is completed or an application
+/// changes status.
+#[derive(Debug, Clone)]
+pub struct FollowUp {
+    /// How long from today the reminder is due.
+    pub after: Duration,
+    /// Defaults to a title naming the company.
+    pub title: Option<String>,
+}
+
+impl FollowUp {
+    pub fn new(after: Duration) -> FollowUp {
+        FollowUp { after, title: None }
+    }
+}
+
+/// The date `after` from `date`. Fails with `InvalidInput` if that is past
+/// the last date there is.
+pub(crate) fn due_after(date: NaiveDate, after: Duration) -> Result<NaiveDate, JobSearchError> {
+    date.checked_add_signed(after).ok_or_else(|| {
+        JobSearchError::InvalidInput(format!(
+            "{} days after {} is out of range",
+            after.num_days(),
+            date
+        ))
+    })
+}
+
+// The last day of the week `today` is in, weeks starting on Monday
+fn end_of_week(today: NaiveDate) -> NaiveDate {
+    today + Duration::days(6 - i64::from(today.weekday().num_days_from_monday()))
+}
+
+impl Reminder {
+    pub fn new(title: String, due_date: NaiveDate) -> Reminder {
+        Reminder {
+            id: None,
+            title,
+            due_date,
+            recurrence: None,
+            done: false,
+            done_date: None,
+            application_id: None,
+            contact_id: None,
+            company_id: None,
+            interview_id: None,
+            created_date: None,
+            last_updated: None,
+            hide: false,
+        }
+    }
+
+    // Reminders that are not done, with `condition` on the due date
+    fn get_open(
+        conn: &Connection,
+        condition: &str,
+        params: &[&dyn ToSql],
+    ) -> Result<Vec<Reminder>, JobSearchError> {
+        entity::query(
+            conn,
+            &format!(
+                "{} WHERE done = 0 AND {} AND {} ORDER BY {}",
+                SELECT_REMINDERS,
+                condition,
+                Visibility::Visible.condition(Reminder::TABLE),
+                Reminder::ORDER_BY
+            ),
+            params,
+        )
+    }
+
+    /// Reminders that are not done and were due before `today`.
+    pub(crate) fn get_overdue(
+        conn: &Connection,
+        today: NaiveDate,
+    ) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_open(conn, "date(due_date) < date(?1)", params![today])
+    }
+
+    /// Reminders that are not done and are due between `from` and `to`,
+    /// both inclusive.
+    pub(crate) fn get_due_between(
+        conn: &Connection,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_open(
+            conn,
+            "date(due_date) BETWEEN date(?1) AND date(?2)",
+            params![from, to],
+        )
+    }
+
+    /// Reminders that are not done and are due from `today` to the end of
+    /// its week.
+    pub(crate) fn get_due_this_week(
+        conn: &Connection,
+        today: NaiveDate,
+    ) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_due_between(conn, today, end_of_week(today))
+    }
+
+    /// Marks the reminder as done. A recurring reminder is followed by a new
+    /// one, due on the first date of its recurrence after `today`, which is
+    /// returned.
+    pub(crate) fn complete(
+        &mut self,
+        conn: &Connection,
+        today: NaiveDate,
+    ) -> Result<Option<Reminder>, JobSearchError> {
+        require_id(self.id, "reminder")?;
+
+        if self.done {
+            return Err(JobSearchError::InvalidInput(
+                "reminder is already done".to_string(),
+            ));
+        }
+
+        let (done_date, last_updated) = (self.done_date, self.last_updated);
+
+        let result = with_savepoint(conn, || {
+            self.done = true;
+            self.done_date = Some(Utc::now());
+            self.update_db(conn)?;
+
+            let recurrence = match self.recurrence {
+                Some(recurrence) => recurrence,
+                None => return Ok(None),
+            };
+
+            let next_date = |date| {
+                recurrence.next(date).ok_or_else(|| {
+                    JobSearchError::InvalidInput(format!(
+                        "a {} reminder has no due date after {}",
+                        recurrence, date
+                    ))
+                })
+            };
+
+            // Reminders done late skip the dates that have already passed
+            let mut due_date = next_date(self.due_date)?;
+            while due_date <= today {
+                due_date = next_date(due_date)?;
+            }
+
+            let mut next = Reminder::new(self.title.clone(), due_date);
+            next.recurrence = self.recurrence;
+            next.application_id = self.application_id;
+            next.contact_id = self.contact_id;
+            next.company_id = self.company_id;
+            next.interview_id = self.interview_id;
+            next.add_to_db(conn)?;
+
+            Ok(Some(next))
+        });
+
+        // The savepoint was rolled back, so the reminder is not done
+        if result.is_err() {
+            self.done = false;
+            self.done_date = done_date;
+            self.last_updated = last_updated;
+        }
+
+        result
+    }
+
+    /// Adds a reminder to follow up on `application`, due `follow_up.after`
+    /// from `today`.
+    pub(crate) fn add_application_follow_up(
+        conn: &Connection,
+        application: &Application,
+        follow_up: &FollowUp,
+        today: NaiveDate,
+    ) -> Result<Reminder, JobSearchError> {
+        let application_id = require_id(application.id, "application")?;
+
+        let title = match follow_up.title {
+            Some(ref title) => title.clone(),
+            None => match application.company_id {
+                Some(company_id) => {
+                    format!(
+                        "Follow up with {}",
+                        Company::get_by_id(conn, company_id)?.name
+                    )
+                }
+                None => "Follow up on application".to_string(),
+            },
+        };
+
+        let mut reminder = Reminder::new(title, due_after(today, follow_up.after)?);
+        reminder.application_id = Some(application_id);
+        reminder.company_id = application.company_id;
+        reminder.contact_id = application.contact_id;
+        reminder.add_to_db(conn)?;
+
+        Ok(reminder)
+    }
+
+    /// Adds a reminder to follow up on `interview`, due `follow_up.after`
+    /// from `today`.
+    pub(crate) fn add_interview_follow_up(
+        conn: &Connection,
+        interview: &Interview,
+        follow_up: &FollowUp,
+        today: NaiveDate,
+    ) -> Result<Reminder, JobSearchError> {
+        let interview_id = require_id(interview.id, "interview")?;
+
+        let title = match follow_up.title {
+            Some(ref title) => title.clone(),
+            None => {
+                let company = Company::get_by_id(conn, interview.company_id)?;
+
+                match interview.interview_type {
+                    Some(ref interview_type) => format!(
+                        "Follow up on the {} interview with {}",
+                        interview_type.name, company.name
+                    ),
+                    None => format!("Follow up on the interview with {}", company.name),
+                }
+            }
+        };
+
+        let mut reminder = Reminder::new(title, due_after(today, follow_up.after)?);
+        reminder.interview_id = Some(interview_id);
+        reminder.application_id = interview.application_id;
+        reminder.company_id = Some(interview.company_id);
+        reminder.contact_id = interview.contact_id;
+        reminder.add_to_db(conn)?;
+
+        Ok(reminder)
+    }
+}
+
+impl Entity for Reminder {
+    const TABLE: &'static str = "reminders";
+    const NAME: &'static str = "reminder";
+    const SELECT: &'static str = SELECT_REMINDERS;
+    const ORDER_BY: &'static str = "date(due_date), id";
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("reminders.due_date"),
+        company: Some("reminders.company_id = ?"),
+        status: Some("reminders.application_id IN (SELECT id FROM applied_to WHERE status = ?)"),
+        contact_type: Some("reminders.contact_id IN (SELECT id FROM contacts WHERE type_id = ?)"),
+        text: &["reminders.title"],
+        name: Some("reminders.title"),
+        // The tags of the application, company or contact it is about
+        tag: Some(
+            "EXISTS (SELECT 1 FROM tags WHERE tags.name = ? AND (\
+             tags.id IN (SELECT tag_id FROM application_tags \
+             WHERE application_id = reminders.application_id) \
+             OR tags.id IN (SELECT tag_id FROM company_tags \
+             WHERE company_id = reminders.company_id) \
+             OR tags.id IN (SELECT tag_id FROM contact_tags \
+             WHERE contact_id = reminders.contact_id)))",
+        ),
+    };
+
+    fn new_from_row(row: &Row) -> Result<Reminder, JobSearchError> {
+        let reminder = Reminder {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            due_date: row.get(2)?,
+            recurrence: row.get(3)?,
+            done: row.get(4)?,
+            done_date: timestamp::get(row, 5)?,
+            application_id: row.get(6)?,
+            contact_id: row.get(7)?,
+            company_id: row.get(8)?,
+            interview_id: row.get(9)?,
+            created_date: timestamp::get(row, 10)?,
+            last_updated: timestamp::get(row, 11)?,
+            hide: row.get::<_, Option<bool>>(12)?.unwrap_or(false),
+        };
+
+        Ok(reminder)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.title, "reminder title")?;
+
+        let _ = conn.execute(
+            "INSERT INTO reminders (title, due_date, recurrence, done, done_date, application_id, \
+             contact_id, company_id, interview_id, hide, created_date, last_updated) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, COALESCE(?11, CURRENT_TIMESTAMP), ?12)",
+            params![
+                self.title,
+                self.due_date,
+                self.recurrence,
+                self.done,
+                timestamp::to_sql(&self.done_date),
+                self.application_id,
+                self.contact_id,
+                self.company_id,
+                self.interview_id,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        *self = Reminder::get_by_id(conn, conn.last_insert_rowid() as i32)?;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "reminder")?;
+        require_text(&self.title, "reminder title")?;
+
+        let _ = conn.execute(
+            "UPDATE reminders SET title = (?1), due_date = (?2), recurrence = (?3), done = (?4), \
+             done_date = (?5), application_id = (?6), contact_id = (?7), company_id = (?8), \
+             interview_id = (?9), hide = (?10) WHERE id = (?11)",
+            params![
+                self.title,
+                self.due_date,
+                self.recurrence,
+                self.done,
+                timestamp::to_sql(&self.done_date),
+                self.application_id,
+                self.contact_id,
+                self.company_id,
+                self.interview_id,
+                self.hide,
+                id
+            ],
+        )?;
+
+        *self = Reminder::get_by_id(conn, id)?;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Reminder>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Reminder> {
+    /// Reminders that are not done and were due before today.
+    pub fn overdue(&self) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_overdue(self.conn(), Local::now().date_naive())
+    }
+
+    pub fn due_today(&self) -> Result<Vec<Reminder>, JobSearchError> {
+        let today = Local::now().date_naive();
+
+        Reminder::get_due_between(self.conn(), today, today)
+    }
+
+    /// Reminders that are not done and are due from today to Sunday.
+    pub fn due_this_week(&self) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_due_this_week(self.conn(), Local::now().date_naive())
+    }
+
+    /// Reminders that are not done and are due between `from` and `to`,
+    /// both inclusive.
+    pub fn due_between(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Reminder>, JobSearchError> {
+        Reminder::get_due_between(self.conn(), from, to)
+    }
+
+    /// Marks the reminder as done, returning the next one if it recurs.
+    pub fn complete(&self, reminder: &mut Reminder) -> Result<Option<Reminder>, JobSearchError> {
+        reminder.complete(self.conn(), Local::now().date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contact::Contact;
+    use crate::contact_type::ContactType;
+    use crate::create_in_memory_db;
+    use crate::interview_type::InterviewType;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn add(conn: &Connection, title: &str, due_date: &str) -> Reminder {
+        let mut reminder = Reminder::new(title.to_string(), date(due_date));
+        reminder.add_to_db(conn).unwrap();
+        reminder
+    }
+
+    fn titles(reminders: &[Reminder]) -> Vec<&str> {
+        reminders
+            .iter()
+            .map(|reminder| reminder.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_recurrence() {
+        assert_eq!(
+            Recurrence::Daily.next(date("2024-02-28")),
+            Some(date("2024-02-29"))
+        );
+        assert_eq!(
+            Recurrence::Weekly.next(date("2024-12-30")),
+            Some(date("2025-01-06"))
+        );
+        assert_eq!(
+            Recurrence::Monthly.next(date("2024-01-31")),
+            Some(date("2024-02-29"))
+        );
+        assert_eq!(Recurrence::Monthly.next(NaiveDate::MAX), None);
+
+        assert_eq!("Weekly".parse::<Recurrence>(), Ok(Recurrence::Weekly));
+        assert!("yearly".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn test_add_to_db() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut reminder = Reminder::new("Ping the recruiter".to_string(), date("2024-05-10"));
+        reminder.recurrence = Some(Recurrence::Weekly);
+        reminder.add_to_db(&conn).unwrap();
+
+        let from_db = Reminder::get_by_id(&conn, reminder.id.unwrap()).unwrap();
+
+        assert_eq!(from_db.title, "Ping the recruiter");
+        assert_eq!(from_db.due_date, date("2024-05-10"));
+        assert_eq!(from_db.recurrence, Some(Recurrence::Weekly));
+        assert!(!from_db.done);
+        assert_ne!(from_db.created_date, None);
+
+        let mut untitled = Reminder::new(" ".to_string(), date("2024-05-10"));
+        assert!(matches!(
+            untitled.add_to_db(&conn),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_due_queries() {
+        let conn = create_in_memory_db().unwrap();
+
+        // A Wednesday
+        let today = date("2024-05-08");
+
+        add(&conn, "last week", "2024-05-01");
+        add(&conn, "today", "2024-05-08");
+        add(&conn, "sunday", "2024-05-12");
+        add(&conn, "next monday", "2024-05-13");
+        let mut done = add(&conn, "done", "2024-05-07");
+        done.complete(&conn, today).unwrap();
+        let mut hidden = add(&conn, "hidden", "2024-05-08");
+        hidden.archive(&conn).unwrap();
+
+        assert_eq!(
+            titles(&Reminder::get_overdue(&conn, today).unwrap()),
+            vec!["last week"]
+        );
+        assert_eq!(
+            titles(&Reminder::get_due_between(&conn, today, today).unwrap()),
+            vec!["today"]
+        );
+        assert_eq!(
+            titles(&Reminder::get_due_this_week(&conn, today).unwrap()),
+            vec!["today", "sunday"]
+        );
+        assert_eq!(
+            titles(&Reminder::get_due_this_week(&conn, date("2024-05-12")).unwrap()),
+            vec!["sunday"]
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+
+        let mut once = add(&conn, "once", "2024-05-08");
+        assert!(once.complete(&conn, date("2024-05-08")).unwrap().is_none());
+        assert!(once.done);
+        assert_ne!(once.done_date, None);
+        assert!(matches!(
+            once.complete(&conn, date("2024-05-08")),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+
+        let mut weekly = Reminder::new("weekly".to_string(), date("2024-05-01"));
+        weekly.recurrence = Some(Recurrence::Weekly);
+        weekly.company_id = company.id;
+        weekly.add_to_db(&conn).unwrap();
+
+        // Done two weeks late, the next one is still in the future
+        let next = weekly.complete(&conn, date("2024-05-15")).unwrap().unwrap();
+
+        assert!(Reminder::get_by_id(&conn, weekly.id.unwrap()).unwrap().done);
+        assert_eq!(next.due_date, date("2024-05-22"));
+        assert_eq!(next.recurrence, Some(Recurrence::Weekly));
+        assert_eq!(next.company_id, company.id);
+        assert!(!next.done);
+
+        // There is no date to follow the last one, so nothing is done
+        let mut last = Reminder::new("last".to_string(), NaiveDate::MAX);
+        last.recurrence = Some(Recurrence::Monthly);
+        last.add_to_db(&conn).unwrap();
+
+        assert!(matches!(
+            last.complete(&conn, date("2024-05-08")),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+        assert!(!last.done);
+        assert_eq!(last.done_date, None);
+        assert!(!Reminder::get_by_id(&conn, last.id.unwrap()).unwrap().done);
+    }
+
+    #[test]
+    fn test_follow_ups() {
+        let conn = create_in_memory_db().unwrap();
+        let today = date("2024-05-08");
+
+        let mut company = Company::new("google".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Ada".to_string(),
+            None,
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+        let mut application = Application::new(company.id.unwrap(), None, contact.id);
+        application.add_to_db(&conn).unwrap();
+
+        let follow_up = FollowUp::new(Duration::days(7));
+        let reminder =
+            Reminder::add_application_follow_up(&conn, &application, &follow_up, today).unwrap();
+
+        assert_eq!(reminder.title, "Follow up with google");
+        assert_eq!(reminder.due_date, date("2024-05-15"));
+        assert_eq!(reminder.application_id, application.id);
+        assert_eq!(reminder.company_id, company.id);
+        assert_eq!(reminder.contact_id, contact.id);
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut interview = Interview::new(interview_type.id.unwrap(), company.id.unwrap(), None);
+        interview.application_id = application.id;
+        interview.add_to_db(&conn).unwrap();
+
+        let reminder =
+            Reminder::add_interview_follow_up(&conn, &interview, &follow_up, today).unwrap();
+
+        assert_eq!(
+            reminder.title,
+            "Follow up on the phone interview with google"
+        );
+        assert_eq!(reminder.interview_id, interview.id);
+        assert_eq!(reminder.application_id, application.id);
+
+        let follow_up = FollowUp {
+            after: Duration::days(3),
+            title: Some("Send a thank you note".to_string()),
+        };
+        let reminder =
+            Reminder::add_interview_follow_up(&conn, &interview, &follow_up, today).unwrap();
+
+        assert_eq!(reminder.title, "Send a thank you note");
+        assert_eq!(reminder.due_date, date("2024-05-11"));
+
+        // Reminders go along with what they are about
+        interview.delete_from_db(&conn).unwrap();
+        assert_eq!(Reminder::count(&conn).unwrap(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection, ErrorCode};
+
+use crate::errors::JobSearchError;
+
+// Matches in a snippet are wrapped in these markers
+const HIGHLIGHT_START: &str = "[";
+const HIGHLIGHT_END: &str = "]";
+
+/// The kind of record a search hit points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SearchKind {
+    Company,
+    JobPosting,
+    Contact,
+    Interview,
+    Note,
+}
+
+impl SearchKind {
+    pub const ALL: [SearchKind; 5] = [
+        SearchKind::Company,
+        SearchKind::JobPosting,
+        SearchKind::Contact,
+        SearchKind::Interview,
+        SearchKind::Note,
+    ];
+
+    /// The name stored in the `kind` column of the search index.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchKind::Company => "company",
+            SearchKind::JobPosting => "job_posting",
+            SearchKind::Contact => "contact",
+            SearchKind::Interview => "interview",
+            SearchKind::Note => "note",
+        }
+    }
+}
+
+impl fmt::Display for SearchKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SearchKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SearchKind, String> {
+        SearchKind::ALL
+            .iter()
+            .find(|kind| kind.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown search kind: {}", s))
+    }
+}
+
+impl FromSql for SearchKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err: String| FromSqlError::Other(err.into()))
+    }
+}
+
+/// A record matching a search, best matches first.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    /// The id of the record in the table for `kind`.
+    pub id: i32,
+    /// The name of the company or contact, the link of the posting, or the
+    /// company name for an interview. Notes take the title of the record
+    /// they are about.
+    pub title: String,
+    /// The part of the record that matched, with the matching words in
+    /// square brackets.
+    pub snippet: String,
+    /// Lower is better.
+    pub rank: f64,
+}
+
+// Titles weigh more than the rest of the text. The unindexed kind and
+// record_id columns take the first two weights.
+const SEARCH: &str = "SELECT kind, record_id, title, \
+     snippet(search_index, -1, ?2, ?3, '...', 12), \
+     bm25(search_index, 0.0, 0.0, 5.0, 1.0) AS score \
+     FROM search_index WHERE search_index MATCH ?1 \
+     AND NOT IFNULL(CASE kind \
+         WHEN 'company' THEN (SELECT hide FROM companies WHERE id = record_id) \
+         WHEN 'job_posting' THEN (SELECT hide FROM job_postings WHERE id = record_id) \
+         WHEN 'contact' THEN (SELECT hide FROM contacts WHERE id = record_id) \
+         WHEN 'interview' THEN (SELECT hide FROM interviews WHERE id = record_id) \
+         WHEN 'note' THEN (SELECT hide FROM notes WHERE id = record_id) \
+     END, 0) \
+     ORDER BY score LIMIT ?4";
+
+/// Searches the text of every record that is not hidden.
+///
+/// `query` uses the SQLite FTS5 syntax: words are all required by default,
+/// `OR` and `NOT` combine terms, `"..."` matches a phrase and `remot*` a
+/// prefix. Words are stemmed, so "interviewing" also finds "interview".
+/// At most `limit` hits are returned, when given.
+pub(crate) fn search(
+    conn: &Connection,
+    query: &str,
+    limit: Option<u32>,
+) -> Result<Vec<SearchHit>, JobSearchError> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // A negative LIMIT means no limit in SQLite
+    let limit = limit.map(i64::from).unwrap_or(-1);
+
+    let mut stmt = conn.prepare(SEARCH)?;
+
+    let hits_iter = stmt.query_map(
+        params![query, HIGHLIGHT_START, HIGHLIGHT_END, limit],
+        |row| {
+            Ok(SearchHit {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                title: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                snippet: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                rank: row.get(4)?,
+            })
+        },
+    );
+
+    let mut hits = Vec::new();
+    for hit in hits_iter.map_err(|err| invalid_query(err, query))? {
+        hits.push(hit.map_err(|err| invalid_query(err, query))?);
+    }
+
+    Ok(hits)
+}
+
+// Syntax errors in the query only show up once SQLite starts matching, as
+// plain SQLITE_ERROR failures. The statement itself is known to be valid.
+fn invalid_query(error: rusqlite::Error, query: &str) -> JobSearchError {
+    match error {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error {
+                code: ErrorCode::Unknown,
+                ..
+            },
+            message,
+        ) => JobSearchError::InvalidInput(format!(
+            "invalid search \"{}\": {}",
+            query,
+            message.unwrap_or_default()
+        )),
+        error => JobSearchError::from(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::company::Company;
+    use crate::contact::Contact;
+    use crate::contact_type::ContactType;
+    use crate::create_in_memory_db;
+    use crate::entity::Entity;
+    use crate::job_posting::JobPosting;
+
+    fn add_posting(conn: &Connection, link: &str, description: &str) -> JobPosting {
+        let mut job_posting = JobPosting::new(link.to_string());
+        job_posting.description = Some(description.to_string());
+        job_posting.add_to_db(conn).unwrap();
+
+        job_posting
+    }
+
+    #[test]
+    fn test_search_postings() {
+        let conn = create_in_memory_db().unwrap();
+
+        add_posting(
+            &conn,
+            "https://example.com/1",
+            "Run Kubernetes clusters. This role is fully remote.",
+        );
+        add_posting(&conn, "https://example.com/2", "Kubernetes, on site only.");
+        add_posting(&conn, "https://example.com/3", "Remote frontend work.");
+
+        let hits = search(&conn, "kubernetes AND remote", None).unwrap();
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchKind::JobPosting);
+        assert_eq!(hits[0].title, "https://example.com/1");
+        assert!(hits[0].snippet.contains("[Kubernetes]"));
+        assert!(hits[0].snippet.contains("[remote]"));
+    }
+
+    #[test]
+    fn test_search_kinds_and_ranking() {
+        let conn = create_in_memory_db().unwrap();
+
+        Company::new("Mozilla".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+        add_posting(
+            &conn,
+            "https://example.com/1",
+            "Working on Firefox at Mozilla",
+        );
+
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        Contact::new(
+            "Allen".to_string(),
+            Some("allen@mozilla.org".to_string()),
+            None,
+            None,
+            contact_type.id.unwrap(),
+        )
+        .add_to_db(&conn)
+        .unwrap();
+
+        let hits = search(&conn, "mozilla", None).unwrap();
+        let kinds: Vec<SearchKind> = hits.iter().map(|hit| hit.kind).collect();
+
+        assert_eq!(hits.len(), 3);
+        // The name is the best match
+        assert_eq!(kinds[0], SearchKind::Company);
+        assert!(kinds.contains(&SearchKind::JobPosting));
+        assert!(kinds.contains(&SearchKind::Contact));
+    }
+
+    #[test]
+    fn test_index_follows_changes() {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut job_posting = add_posting(&conn, "https://example.com/1", "Rust");
+
+        job_posting.description = Some("Go".to_string());
+        job_posting.update_db(&conn).unwrap();
+
+        assert!(search(&conn, "rust", None).unwrap().is_empty());
+        assert_eq!(search(&conn, "go", None).unwrap().len(), 1);
+
+        job_posting.archive(&conn).unwrap();
+        assert!(search(&conn, "go", None).unwrap().is_empty());
+
+        job_posting.delete_from_db(&conn).unwrap();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM search_index",
+                rusqlite::NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let conn = create_in_memory_db().unwrap();
+        add_posting(&conn, "https://example.com/1", "Rust");
+
+        assert!(matches!(
+            search(&conn, "\"rust", None),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+        assert!(search(&conn, "  ", None).unwrap().is_empty());
+    }
+}
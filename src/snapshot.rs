@@ -0,0 +1,1223 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde_json::{json, Map, Value};
+
+use crate::application::Application;
+use crate::application_status::{self, ApplicationStatus, StatusChange};
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::contact_type::ContactType;
+use crate::entity::{Entity, Visibility};
+use crate::errors::JobSearchError;
+use crate::interview::Interview;
+use crate::interview_type::InterviewType;
+use crate::job_posting::JobPosting;
+use crate::note::{Note, NoteTarget};
+use crate::reminder::{Recurrence, Reminder};
+use crate::tag::{self, Tag, Taggable};
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+/// The version of the snapshot format written by
+/// `Database::export_snapshot`. Snapshots of later versions are refused.
+pub const SNAPSHOT_VERSION: u64 = 2;
+
+const FORMAT: &str = "job-search-snapshot";
+
+/// How many records of a table an import added, updated or left as they
+/// were.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergeCounts {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+/// What `Database::import_snapshot` did with each table.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotImport {
+    pub contact_types: MergeCounts,
+    pub interview_types: MergeCounts,
+    pub companies: MergeCounts,
+    pub job_postings: MergeCounts,
+    pub contacts: MergeCounts,
+    pub applications: MergeCounts,
+    pub interviews: MergeCounts,
+    pub reminders: MergeCounts,
+    pub notes: MergeCounts,
+    pub tags: MergeCounts,
+}
+
+impl SnapshotImport {
+    /// The counts of every table, by the table's name in the snapshot.
+    pub fn tables(&self) -> [(&'static str, MergeCounts); 10] {
+        [
+            ("contact_types", self.contact_types),
+            ("interview_types", self.interview_types),
+            ("companies", self.companies),
+            ("job_postings", self.job_postings),
+            ("contacts", self.contacts),
+            ("applications", self.applications),
+            ("interviews", self.interviews),
+            ("reminders", self.reminders),
+            ("notes", self.notes),
+            ("tags", self.tags),
+        ]
+    }
+}
+
+fn time(value: &Option<DateTime<Utc>>) -> Value {
+    json!(value.map(|time| time.to_rfc3339()))
+}
+
+fn history_json(history: &[StatusChange]) -> Vec<Value> {
+    history
+        .iter()
+        .map(|change| {
+            json!({
+                "status": change.status.as_str(),
+                "changed_date": change.changed_date.to_rfc3339(),
+            })
+        })
+        .collect()
+}
+
+/// Writes every record, hidden ones included, as a JSON document. Records
+/// refer to each other by their ids in this database.
+pub(crate) fn export<W: Write>(
+    conn: &Connection,
+    mut out: W,
+    now: DateTime<Utc>,
+) -> Result<(), JobSearchError> {
+    let all = Visibility::IncludeHidden;
+
+    let contact_types: Vec<Value> = ContactType::get_all_with(conn, all)?
+        .iter()
+        .map(|contact_type| {
+            json!({
+                "id": contact_type.id,
+                "name": contact_type.name,
+                "last_updated": time(&contact_type.last_updated),
+                "hide": contact_type.hide,
+            })
+        })
+        .collect();
+
+    let interview_types: Vec<Value> = InterviewType::get_all_with(conn, all)?
+        .iter()
+        .map(|interview_type| {
+            json!({
+                "id": interview_type.id,
+                "name": interview_type.name,
+                "last_updated": time(&interview_type.last_updated),
+                "hide": interview_type.hide,
+            })
+        })
+        .collect();
+
+    let companies: Vec<Value> = Company::get_all_with(conn, all)?
+        .iter()
+        .map(|company| {
+            json!({
+                "id": company.id,
+                "name": company.name,
+                "address": company.address,
+                "website": company.website,
+                "phone": company.phone,
+                "created_date": time(&company.created_date),
+                "last_updated": time(&company.last_updated),
+                "hide": company.hide,
+            })
+        })
+        .collect();
+
+    let job_postings: Vec<Value> = JobPosting::get_all_with(conn, all)?
+        .iter()
+        .map(|job_posting| {
+            json!({
+                "id": job_posting.id,
+                "link": job_posting.link,
+                "description": job_posting.description,
+                "created_date": time(&job_posting.created_date),
+                "last_updated": time(&job_posting.last_updated),
+                "hide": job_posting.hide,
+            })
+        })
+        .collect();
+
+    let contacts: Vec<Value> = Contact::get_all_with(conn, all)?
+        .iter()
+        .map(|contact| {
+            json!({
+                "id": contact.id,
+                "name": contact.name,
+                "email": contact.email,
+                "phone": contact.phone,
+                "description": contact.description,
+                "type_id": contact.type_id,
+                "created_date": time(&contact.created_date),
+                "last_updated": time(&contact.last_updated),
+                "hide": contact.hide,
+            })
+        })
+        .collect();
+
+    let histories = application_status::get_all_histories(conn)?;
+    let applications: Vec<Value> = Application::get_all_with(conn, all)?
+        .iter()
+        .map(|application| {
+            let history = application
+                .id
+                .and_then(|id| histories.get(&id))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            json!({
+                "id": application.id,
+                "company_id": application.company_id,
+                "job_posting_id": application.job_posting_id,
+                "contact_id": application.contact_id,
+                "date_applied": application.date_applied.map(|date| date.to_string()),
+                "status": application.status.as_str(),
+                "status_history": history_json(history),
+                "last_updated": time(&application.last_updated),
+                "hide": application.hide,
+            })
+        })
+        .collect();
+
+    let interviews: Vec<Value> = Interview::get_all_with(conn, all)?
+        .iter()
+        .map(|interview| {
+            json!({
+                "id": interview.id,
+                "date": time(&interview.date),
+                "interview_type_id": interview.interview_type_id,
+                "company_id": interview.company_id,
+                "contact_id": interview.contact_id,
+                "job_posting_id": interview.job_posting_id,
+                "application_id": interview.application_id,
+                "description": interview.description,
+                "cancelled": interview.cancelled,
+                "completed": interview.completed,
+                "ical_uid": interview.ical_uid,
+                "created_date": time(&interview.created_date),
+                "last_updated": time(&interview.last_updated),
+                "hide": interview.hide,
+            })
+        })
+        .collect();
+
+    let reminders: Vec<Value> = Reminder::get_all_with(conn, all)?
+        .iter()
+        .map(|reminder| {
+            json!({
+                "id": reminder.id,
+                "title": reminder.title,
+                "due_date": reminder.due_date.to_string(),
+                "recurrence": reminder.recurrence.map(Recurrence::as_str),
+                "done": reminder.done,
+                "done_date": time(&reminder.done_date),
+                "application_id": reminder.application_id,
+                "contact_id": reminder.contact_id,
+                "company_id": reminder.company_id,
+                "interview_id": reminder.interview_id,
+                "created_date": time(&reminder.created_date),
+                "last_updated": time(&reminder.last_updated),
+                "hide": reminder.hide,
+            })
+        })
+        .collect();
+
+    let notes: Vec<Value> = Note::get_all_with(conn, all)?
+        .iter()
+        .map(|note| {
+            json!({
+                "id": note.id,
+                "body": note.body,
+                "company_id": note.company_id,
+                "job_posting_id": note.job_posting_id,
+                "contact_id": note.contact_id,
+                "application_id": note.application_id,
+                "interview_id": note.interview_id,
+                "created_date": time(&note.created_date),
+                "last_updated": time(&note.last_updated),
+                "hide": note.hide,
+            })
+        })
+        .collect();
+
+    let tags = Tag::get_all_with(conn, all)?
+        .iter()
+        .map(|tag| {
+            let id = tag.id.unwrap_or_default();
+
+            Ok(json!({
+                "id": tag.id,
+                "name": tag.name,
+                "created_date": time(&tag.created_date),
+                "last_updated": time(&tag.last_updated),
+                "hide": tag.hide,
+                "companies": tag::get_tagged::<Company>(conn, id)?,
+                "job_postings": tag::get_tagged::<JobPosting>(conn, id)?,
+                "contacts": tag::get_tagged::<Contact>(conn, id)?,
+                "applications": tag::get_tagged::<Application>(conn, id)?,
+            }))
+        })
+        .collect::<Result<Vec<Value>, JobSearchError>>()?;
+
+    let snapshot = json!({
+        "format": FORMAT,
+        "version": SNAPSHOT_VERSION,
+        "exported_at": now.to_rfc3339(),
+        "contact_types": contact_types,
+        "interview_types": interview_types,
+        "companies": companies,
+        "job_postings": job_postings,
+        "contacts": contacts,
+        "applications": applications,
+        "interviews": interviews,
+        "reminders": reminders,
+        "notes": notes,
+        "tags": tags,
+    });
+
+    serde_json::to_writer_pretty(&mut out, &snapshot).map_err(io::Error::from)?;
+    writeln!(out)?;
+
+    Ok(())
+}
+
+// A record of a snapshot. Errors say which field of which record is wrong.
+struct Fields<'a> {
+    table: &'static str,
+    index: usize,
+    map: &'a Map<String, Value>,
+}
+
+impl<'a> Fields<'a> {
+    fn invalid(&self, field: &str, message: &str) -> JobSearchError {
+        JobSearchError::InvalidInput(format!(
+            "{}[{}].{}: {}",
+            self.table, self.index, field, message
+        ))
+    }
+
+    fn get(&self, field: &str) -> Option<&'a Value> {
+        self.map.get(field).filter(|value| !value.is_null())
+    }
+
+    fn text(&self, field: &str) -> Result<Option<String>, JobSearchError> {
+        match self.get(field) {
+            Some(Value::String(text)) => Ok(Some(text.clone())),
+            Some(_) => Err(self.invalid(field, "expected a string")),
+            None => Ok(None),
+        }
+    }
+
+    fn required_text(&self, field: &str) -> Result<String, JobSearchError> {
+        self.text(field)?
+            .ok_or_else(|| self.invalid(field, "expected a string"))
+    }
+
+    fn id(&self, field: &str) -> Result<Option<i64>, JobSearchError> {
+        match self.get(field) {
+            Some(value) => value
+                .as_i64()
+                .map(Some)
+                .ok_or_else(|| self.invalid(field, "expected an id")),
+            None => Ok(None),
+        }
+    }
+
+    fn bool(&self, field: &str) -> Result<bool, JobSearchError> {
+        match self.get(field) {
+            Some(value) => value
+                .as_bool()
+                .ok_or_else(|| self.invalid(field, "expected true or false")),
+            None => Ok(false),
+        }
+    }
+
+    fn time(&self, field: &str) -> Result<Option<DateTime<Utc>>, JobSearchError> {
+        match self.text(field)? {
+            Some(value) => timestamp::parse(&value)
+                .map(Some)
+                .map_err(|_| self.invalid(field, "expected an RFC 3339 timestamp")),
+            None => Ok(None),
+        }
+    }
+
+    fn date(&self, field: &str) -> Result<Option<NaiveDate>, JobSearchError> {
+        match self.text(field)? {
+            Some(value) => NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| self.invalid(field, "expected a YYYY-MM-DD date")),
+            None => Ok(None),
+        }
+    }
+
+    fn status(&self, field: &str) -> Result<Option<ApplicationStatus>, JobSearchError> {
+        match self.text(field)? {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|message: String| self.invalid(field, &message)),
+            None => Ok(None),
+        }
+    }
+
+    fn recurrence(&self, field: &str) -> Result<Option<Recurrence>, JobSearchError> {
+        match self.text(field)? {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|message: String| self.invalid(field, &message)),
+            None => Ok(None),
+        }
+    }
+
+    // The local id of the record that `field` refers to by its snapshot id
+    fn reference(&self, field: &str, ids: &Ids) -> Result<Option<i32>, JobSearchError> {
+        match self.id(field)? {
+            Some(id) => ids.get(&id).copied().map(Some).ok_or_else(|| {
+                self.invalid(field, &format!("no record with id {} in the snapshot", id))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    // A list of ids, such as the records carrying a tag
+    fn references(&self, field: &str, ids: &Ids) -> Result<Vec<i32>, JobSearchError> {
+        let items = match self.get(field) {
+            Some(Value::Array(items)) => items,
+            Some(_) => return Err(self.invalid(field, "expected a list of ids")),
+            None => return Ok(Vec::new()),
+        };
+
+        items
+            .iter()
+            .map(|item| {
+                let id = item
+                    .as_i64()
+                    .ok_or_else(|| self.invalid(field, "expected a list of ids"))?;
+
+                ids.get(&id).copied().ok_or_else(|| {
+                    self.invalid(field, &format!("no record with id {} in the snapshot", id))
+                })
+            })
+            .collect()
+    }
+
+    fn required_reference(&self, field: &str, ids: &Ids) -> Result<i32, JobSearchError> {
+        self.reference(field, ids)?
+            .ok_or_else(|| self.invalid(field, "expected an id"))
+    }
+}
+
+// Snapshot ids to the ids of the same records in this database
+type Ids = HashMap<i64, i32>;
+
+fn records<'a>(
+    snapshot: &'a Map<String, Value>,
+    table: &'static str,
+) -> Result<Vec<Fields<'a>>, JobSearchError> {
+    match snapshot.get(table) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match item {
+                Value::Object(map) => Ok(Fields { table, index, map }),
+                _ => Err(JobSearchError::InvalidInput(format!(
+                    "{}[{}]: expected an object",
+                    table, index
+                ))),
+            })
+            .collect(),
+        Some(Value::Null) | None => Ok(Vec::new()),
+        Some(_) => Err(JobSearchError::InvalidInput(format!(
+            "{}: expected a list",
+            table
+        ))),
+    }
+}
+
+// The copy from the snapshot wins if it was changed after the local one
+fn is_newer(snapshot: Option<DateTime<Utc>>, local: Option<DateTime<Utc>>) -> bool {
+    match (snapshot, local) {
+        (Some(snapshot), Some(local)) => snapshot > local,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+#[derive(PartialEq)]
+enum Merged {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+// Adds `record`, or writes it over `existing` when it is newer. Returns the
+// local id of the record.
+fn merge<T: Entity>(
+    conn: &Connection,
+    counts: &mut MergeCounts,
+    existing: Option<T>,
+    mut record: T,
+    modified: impl Fn(&T) -> Option<DateTime<Utc>>,
+    set_id: impl FnOnce(&mut T, i32),
+) -> Result<(i32, Merged), JobSearchError> {
+    let local = match existing {
+        Some(local) => local,
+        None => {
+            record.add_to_db(conn)?;
+            counts.created += 1;
+
+            return Ok((record.id().unwrap_or_default(), Merged::Created));
+        }
+    };
+
+    let id = local.id().unwrap_or_default();
+
+    if is_newer(modified(&record), modified(&local)) {
+        set_id(&mut record, id);
+        record.update_db(conn)?;
+        counts.updated += 1;
+
+        Ok((id, Merged::Updated))
+    } else {
+        counts.unchanged += 1;
+
+        Ok((id, Merged::Unchanged))
+    }
+}
+
+fn history(record: &Fields) -> Result<Vec<StatusChange>, JobSearchError> {
+    let changes = match record.get("status_history") {
+        Some(Value::Array(changes)) => changes,
+        Some(_) => return Err(record.invalid("status_history", "expected a list")),
+        None => return Ok(Vec::new()),
+    };
+
+    changes
+        .iter()
+        .enumerate()
+        .map(|(index, change)| {
+            let change = match change {
+                Value::Object(map) => Fields {
+                    table: "status_history",
+                    index,
+                    map,
+                },
+                _ => return Err(record.invalid("status_history", "expected objects")),
+            };
+
+            Ok(StatusChange {
+                status: change
+                    .status("status")?
+                    .ok_or_else(|| change.invalid("status", "expected a status"))?,
+                changed_date: change
+                    .time("changed_date")?
+                    .ok_or_else(|| change.invalid("changed_date", "expected a timestamp"))?,
+            })
+        })
+        .collect()
+}
+
+// Gives an application the status and history it has in the snapshot
+fn restore_status(
+    conn: &Connection,
+    application_id: i32,
+    status: ApplicationStatus,
+    history: &[StatusChange],
+) -> Result<(), JobSearchError> {
+    conn.execute(
+        "UPDATE applied_to SET status = (?1) WHERE id = (?2) AND status IS NOT (?1)",
+        params![status, application_id],
+    )?;
+
+    if history.is_empty() {
+        return Ok(());
+    }
+
+    conn.execute(
+        "DELETE FROM application_status_history WHERE application_id = (?1)",
+        params![application_id],
+    )?;
+
+    for change in history {
+        conn.execute(
+            "INSERT INTO application_status_history (application_id, status, changed_date) \
+             VALUES (?1, ?2, ?3)",
+            params![
+                application_id,
+                change.status,
+                timestamp::to_sql(&Some(change.changed_date))
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn import_snapshot(
+    conn: &Connection,
+    snapshot: &Map<String, Value>,
+    version: u64,
+) -> Result<SnapshotImport, JobSearchError> {
+    let mut report = SnapshotImport::default();
+
+    let mut contact_types = Ids::new();
+    for record in records(snapshot, "contact_types")? {
+        let mut contact_type = ContactType::new(record.required_text("name")?);
+        contact_type.last_updated = record.time("last_updated")?;
+        contact_type.hide = record.bool("hide")?;
+
+        let existing = ContactType::get_by_name(conn, &contact_type.name)?;
+        let (id, _) = merge(
+            conn,
+            &mut report.contact_types,
+            existing,
+            contact_type,
+            |contact_type| contact_type.last_updated,
+            |contact_type, id| contact_type.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            contact_types.insert(snapshot_id, id);
+        }
+    }
+
+    let mut interview_types = Ids::new();
+    for record in records(snapshot, "interview_types")? {
+        let mut interview_type = InterviewType::new(record.required_text("name")?);
+        interview_type.last_updated = record.time("last_updated")?;
+        interview_type.hide = record.bool("hide")?;
+
+        let existing = InterviewType::get_by_name(conn, &interview_type.name)?;
+        let (id, _) = merge(
+            conn,
+            &mut report.interview_types,
+            existing,
+            interview_type,
+            |interview_type| interview_type.last_updated,
+            |interview_type, id| interview_type.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            interview_types.insert(snapshot_id, id);
+        }
+    }
+
+    let mut companies = Ids::new();
+    for record in records(snapshot, "companies")? {
+        let mut company = Company::new(
+            record.required_text("name")?,
+            record.text("address")?,
+            record.text("website")?,
+            record.text("phone")?,
+        );
+        company.created_date = record.time("created_date")?;
+        company.last_updated = record.time("last_updated")?;
+        company.hide = record.bool("hide")?;
+
+        let existing = Company::get_by_name(conn, &company.name)?;
+        let (id, _) = merge(
+            conn,
+            &mut report.companies,
+            existing,
+            company,
+            |company| company.last_updated.or(company.created_date),
+            |company, id| company.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            companies.insert(snapshot_id, id);
+        }
+    }
+
+    let mut job_postings = Ids::new();
+    for record in records(snapshot, "job_postings")? {
+        let mut job_posting = JobPosting::new(record.required_text("link")?);
+        job_posting.description = record.text("description")?;
+        job_posting.created_date = record.time("created_date")?;
+        job_posting.last_updated = record.time("last_updated")?;
+        job_posting.hide = record.bool("hide")?;
+
+        let existing = JobPosting::get_by_link(conn, &job_posting.link)?;
+        let (id, _) = merge(
+            conn,
+            &mut report.job_postings,
+            existing,
+            job_posting,
+            |job_posting| job_posting.last_updated.or(job_posting.created_date),
+            |job_posting, id| job_posting.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            job_postings.insert(snapshot_id, id);
+        }
+    }
+
+    // Contacts have no unique key. The same name and email is taken to be
+    // the same person.
+    // Version 1 named a contact's type contact_type_id
+    let type_field = if version < 2 {
+        "contact_type_id"
+    } else {
+        "type_id"
+    };
+    let mut contacts = Ids::new();
+    for record in records(snapshot, "contacts")? {
+        let mut contact = Contact::new(
+            record.required_text("name")?,
+            record.text("email")?,
+            record.text("phone")?,
+            record.text("description")?,
+            record.required_reference(type_field, &contact_types)?,
+        );
+        contact.created_date = record.time("created_date")?;
+        contact.last_updated = record.time("last_updated")?;
+        contact.hide = record.bool("hide")?;
+
+        let existing = Contact::query(
+            conn,
+            &format!(
+                "{} WHERE contacts.name = (?1) COLLATE NOCASE \
+                 AND contacts.email IS (?2) COLLATE NOCASE",
+                Contact::SELECT
+            ),
+            params![contact.name, contact.email],
+        )?
+        .into_iter()
+        .next();
+        let (id, _) = merge(
+            conn,
+            &mut report.contacts,
+            existing,
+            contact,
+            |contact| contact.last_updated.or(contact.created_date),
+            |contact, id| contact.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            contacts.insert(snapshot_id, id);
+        }
+    }
+
+    // Applications have no unique key either. One to the same company, for
+    // the same posting, on the same day is taken to be the same.
+    let mut applications = Ids::new();
+    for record in records(snapshot, "applications")? {
+        let mut application = Application::new(
+            record.required_reference("company_id", &companies)?,
+            record.reference("job_posting_id", &job_postings)?,
+            record.reference("contact_id", &contacts)?,
+        );
+        application.date_applied = record.date("date_applied")?;
+        application.last_updated = record.time("last_updated")?;
+        application.hide = record.bool("hide")?;
+        if let Some(status) = record.status("status")? {
+            application.status = status;
+        }
+        let status = application.status;
+        let history = history(&record)?;
+
+        let existing = match application.date_applied {
+            Some(date_applied) => Application::query(
+                conn,
+                &format!(
+                    "{} WHERE company_id IS (?1) AND job_posting_id IS (?2) AND date_applied = (?3)",
+                    Application::SELECT
+                ),
+                params![application.company_id, application.job_posting_id, date_applied],
+            )?
+            .into_iter()
+            .next(),
+            None => None,
+        };
+        let (id, merged) = merge(
+            conn,
+            &mut report.applications,
+            existing,
+            application,
+            |application| application.last_updated,
+            |application, id| application.id = Some(id),
+        )?;
+
+        if merged != Merged::Unchanged {
+            restore_status(conn, id, status, &history)?;
+        }
+
+        if let Some(snapshot_id) = record.id("id")? {
+            applications.insert(snapshot_id, id);
+        }
+    }
+
+    // Interviews imported from a calendar are matched by their event's UID,
+    // others by their company, type and time
+    let mut interviews = Ids::new();
+    for record in records(snapshot, "interviews")? {
+        let mut interview = Interview::new(
+            record.required_reference("interview_type_id", &interview_types)?,
+            record.required_reference("company_id", &companies)?,
+            record.time("date")?,
+        );
+        interview.contact_id = record.reference("contact_id", &contacts)?;
+        interview.job_posting_id = record.reference("job_posting_id", &job_postings)?;
+        interview.application_id = record.reference("application_id", &applications)?;
+        interview.description = record.text("description")?;
+        interview.cancelled = record.bool("cancelled")?;
+        interview.completed = record.bool("completed")?;
+        interview.ical_uid = record.text("ical_uid")?;
+        interview.created_date = record.time("created_date")?;
+        interview.last_updated = record.time("last_updated")?;
+        interview.hide = record.bool("hide")?;
+
+        let existing = match (&interview.ical_uid, interview.date) {
+            (Some(uid), _) => Interview::query(
+                conn,
+                &format!("{} WHERE interviews.ical_uid = (?1)", Interview::SELECT),
+                params![uid],
+            )?,
+            (None, Some(date)) => Interview::query(
+                conn,
+                &format!(
+                    "{} WHERE interviews.company_id = (?1) AND interviews.interview_type_id = (?2) \
+                     AND datetime(interviews.date) = datetime(?3)",
+                    Interview::SELECT
+                ),
+                params![interview.company_id, interview.interview_type_id, date],
+            )?,
+            (None, None) => Vec::new(),
+        };
+        let (id, _) = merge(
+            conn,
+            &mut report.interviews,
+            existing.into_iter().next(),
+            interview,
+            |interview| interview.last_updated.or(interview.created_date),
+            |interview, id| interview.id = Some(id),
+        )?;
+
+        if let Some(snapshot_id) = record.id("id")? {
+            interviews.insert(snapshot_id, id);
+        }
+    }
+
+    // Reminders are matched by their title and due date
+    for record in records(snapshot, "reminders")? {
+        let mut reminder = Reminder::new(
+            record.required_text("title")?,
+            record
+                .date("due_date")?
+                .ok_or_else(|| record.invalid("due_date", "expected a YYYY-MM-DD date"))?,
+        );
+        reminder.recurrence = record.recurrence("recurrence")?;
+        reminder.done = record.bool("done")?;
+        reminder.done_date = record.time("done_date")?;
+        reminder.application_id = record.reference("application_id", &applications)?;
+        reminder.contact_id = record.reference("contact_id", &contacts)?;
+        reminder.company_id = record.reference("company_id", &companies)?;
+        reminder.interview_id = record.reference("interview_id", &interviews)?;
+        reminder.created_date = record.time("created_date")?;
+        reminder.last_updated = record.time("last_updated")?;
+        reminder.hide = record.bool("hide")?;
+
+        let existing = Reminder::query(
+            conn,
+            &format!(
+                "{} WHERE title = (?1) AND date(due_date) = date(?2)",
+                Reminder::SELECT
+            ),
+            params![reminder.title, reminder.due_date],
+        )?
+        .into_iter()
+        .next();
+        merge(
+            conn,
+            &mut report.reminders,
+            existing,
+            reminder,
+            |reminder| reminder.last_updated.or(reminder.created_date),
+            |reminder, id| reminder.id = Some(id),
+        )?;
+    }
+
+    // Notes are matched by what they are about and when they were written
+    for record in records(snapshot, "notes")? {
+        let targets = vec![
+            record
+                .reference("company_id", &companies)?
+                .map(NoteTarget::Company),
+            record
+                .reference("job_posting_id", &job_postings)?
+                .map(NoteTarget::JobPosting),
+            record
+                .reference("contact_id", &contacts)?
+                .map(NoteTarget::Contact),
+            record
+                .reference("application_id", &applications)?
+                .map(NoteTarget::Application),
+            record
+                .reference("interview_id", &interviews)?
+                .map(NoteTarget::Interview),
+        ];
+        let target = match targets.into_iter().flatten().collect::<Vec<_>>()[..] {
+            [target] => target,
+            _ => return Err(record.invalid("id", "expected a note about exactly one record")),
+        };
+
+        let mut note = Note::new(target, record.required_text("body")?);
+        note.created_date = record.time("created_date")?;
+        note.last_updated = record.time("last_updated")?;
+        note.hide = record.bool("hide")?;
+
+        let existing = Note::query(
+            conn,
+            &format!(
+                "{} WHERE company_id IS (?1) AND job_posting_id IS (?2) AND contact_id IS (?3) \
+                 AND application_id IS (?4) AND interview_id IS (?5) \
+                 AND datetime(created_date) = datetime(?6)",
+                Note::SELECT
+            ),
+            params![
+                note.company_id,
+                note.job_posting_id,
+                note.contact_id,
+                note.application_id,
+                note.interview_id,
+                timestamp::to_sql(&note.created_date)
+            ],
+        )?
+        .into_iter()
+        .next();
+        merge(
+            conn,
+            &mut report.notes,
+            existing,
+            note,
+            |note| note.last_updated.or(note.created_date),
+            |note, id| note.id = Some(id),
+        )?;
+    }
+
+    // Tags are matched by name. Records are only ever added to a tag, so
+    // tagging done in either database is kept.
+    for record in records(snapshot, "tags")? {
+        let mut tag = Tag::new(record.required_text("name")?);
+        tag.created_date = record.time("created_date")?;
+        tag.last_updated = record.time("last_updated")?;
+        tag.hide = record.bool("hide")?;
+
+        let existing = Tag::get_by_name(conn, &tag.name)?;
+        let (id, _) = merge(
+            conn,
+            &mut report.tags,
+            existing,
+            tag,
+            |tag| tag.last_updated.or(tag.created_date),
+            |tag, id| tag.id = Some(id),
+        )?;
+
+        tag_all::<Company>(conn, id, record.references("companies", &companies)?)?;
+        tag_all::<JobPosting>(conn, id, record.references("job_postings", &job_postings)?)?;
+        tag_all::<Contact>(conn, id, record.references("contacts", &contacts)?)?;
+        tag_all::<Application>(conn, id, record.references("applications", &applications)?)?;
+    }
+
+    Ok(report)
+}
+
+fn tag_all<T: Taggable>(
+    conn: &Connection,
+    tag_id: i32,
+    ids: Vec<i32>,
+) -> Result<(), JobSearchError> {
+    let name = Tag::get_by_id(conn, tag_id)?.name;
+
+    for id in ids {
+        tag::add_tag::<T>(conn, id, &name)?;
+    }
+
+    Ok(())
+}
+
+/// Merges a snapshot written by `export` into the database.
+///
+/// Records are matched to existing ones by their unique keys: names for
+/// companies and types, links for postings, event UIDs for interviews,
+/// titles and due dates for reminders, and the record and time they were
+/// written for notes. Tagged records are added to the local tags.
+/// A matched record is overwritten only if the snapshot's copy was changed
+/// more recently. Either the whole snapshot is imported or, if any of it is
+/// invalid, none of it.
+pub(crate) fn import<R: Read>(
+    conn: &Connection,
+    input: R,
+) -> Result<SnapshotImport, JobSearchError> {
+    let snapshot: Value = serde_json::from_reader(input).map_err(|err| {
+        if err.is_io() {
+            JobSearchError::IOError(err.into())
+        } else {
+            JobSearchError::InvalidInput(format!("not a snapshot: {}", err))
+        }
+    })?;
+
+    let snapshot = match snapshot {
+        Value::Object(ref map) if map.get("format") == Some(&json!(FORMAT)) => map,
+        _ => {
+            return Err(JobSearchError::InvalidInput(
+                "not a snapshot: the format is not job-search-snapshot".to_string(),
+            ))
+        }
+    };
+
+    let version = match snapshot.get("version").and_then(Value::as_u64) {
+        Some(version) if version <= SNAPSHOT_VERSION => version,
+        Some(version) => {
+            return Err(JobSearchError::InvalidInput(format!(
+                "snapshot version {} is newer than the latest supported version {}",
+                version, SNAPSHOT_VERSION
+            )))
+        }
+        None => {
+            return Err(JobSearchError::InvalidInput(
+                "the snapshot has no version".to_string(),
+            ))
+        }
+    };
+
+    with_savepoint(conn, || import_snapshot(conn, snapshot, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_in_memory_db;
+
+    fn export_to_value(conn: &Connection) -> Value {
+        let mut out = Vec::new();
+        export(conn, &mut out, "2020-06-01T12:00:00Z".parse().unwrap()).unwrap();
+
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    fn import_value(conn: &Connection, snapshot: &Value) -> Result<SnapshotImport, JobSearchError> {
+        import(conn, snapshot.to_string().as_bytes())
+    }
+
+    // A database with a record in every table
+    fn sample_db() -> Connection {
+        let conn = create_in_memory_db().unwrap();
+
+        let mut company = Company::new("Mozilla".to_string(), None, None, None);
+        company.add_to_db(&conn).unwrap();
+        let mut job_posting = JobPosting::new("https://mozilla.org/jobs/1".to_string());
+        job_posting.add_to_db(&conn).unwrap();
+        let mut contact_type = ContactType::new("recruiter".to_string());
+        contact_type.add_to_db(&conn).unwrap();
+        let mut contact = Contact::new(
+            "Allen".to_string(),
+            Some("allen@mozilla.org".to_string()),
+            None,
+            None,
+            contact_type.id.unwrap(),
+        );
+        contact.add_to_db(&conn).unwrap();
+
+        let mut application = Application::new(company.id.unwrap(), job_posting.id, contact.id);
+        application.date_applied = NaiveDate::from_ymd_opt(2020, 5, 1);
+        application.add_to_db(&conn).unwrap();
+        application
+            .change_status(&conn, ApplicationStatus::Screening)
+            .unwrap();
+
+        let mut interview_type = InterviewType::new("phone".to_string());
+        interview_type.add_to_db(&conn).unwrap();
+        let mut interview = Interview::new(
+            interview_type.id.unwrap(),
+            company.id.unwrap(),
+            Some("2020-05-08T10:00:00Z".parse().unwrap()),
+        );
+        interview.application_id = application.id;
+        interview.contact_id = contact.id;
+        interview.add_to_db(&conn).unwrap();
+
+        let mut reminder = Reminder::new(
+            "Ping Allen".to_string(),
+            NaiveDate::from_ymd_opt(2020, 5, 15).unwrap(),
+        );
+        reminder.recurrence = Some(Recurrence::Weekly);
+        reminder.interview_id = interview.id;
+        reminder.contact_id = contact.id;
+        reminder.add_to_db(&conn).unwrap();
+
+        Note::new(
+            NoteTarget::Application(application.id.unwrap()),
+            "Referred by Allen".to_string(),
+        )
+        .add_to_db(&conn)
+        .unwrap();
+
+        tag::add_tag::<Company>(&conn, company.id.unwrap(), "remote-only").unwrap();
+        tag::add_tag::<Application>(&conn, application.id.unwrap(), "remote-only").unwrap();
+
+        conn
+    }
+
+    #[test]
+    fn test_export() {
+        let conn = sample_db();
+
+        let snapshot = export_to_value(&conn);
+
+        assert_eq!(snapshot["format"], FORMAT);
+        assert_eq!(snapshot["version"], SNAPSHOT_VERSION);
+        assert_eq!(snapshot["companies"][0]["name"], "Mozilla");
+        assert_eq!(snapshot["contacts"][0]["type_id"], 1);
+        assert_eq!(snapshot["applications"][0]["status"], "screening");
+        assert_eq!(
+            snapshot["applications"][0]["status_history"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            snapshot["interviews"][0]["date"],
+            "2020-05-08T10:00:00+00:00"
+        );
+        assert_eq!(snapshot["reminders"][0]["due_date"], "2020-05-15");
+        assert_eq!(snapshot["reminders"][0]["recurrence"], "weekly");
+        assert_eq!(snapshot["tags"][0]["companies"], json!([1]));
+        assert_eq!(snapshot["tags"][0]["contacts"], json!([]));
+    }
+
+    #[test]
+    fn test_import_into_empty_database() {
+        let snapshot = export_to_value(&sample_db());
+
+        let conn = create_in_memory_db().unwrap();
+        // Ids in the snapshot do not have to match the local ones
+        Company::new("Google".to_string(), None, None, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let report = import_value(&conn, &snapshot).unwrap();
+
+        for (table, counts) in report.tables().iter() {
+            assert_eq!(counts.created, 1, "{}", table);
+        }
+
+        let application = Application::get_by_id(&conn, 1).unwrap();
+        assert_eq!(application.company_id, Some(2));
+        assert_eq!(application.status, ApplicationStatus::Screening);
+        let history = application_status::get_history(&conn, 1).unwrap();
+        assert_eq!(history.len(), 2);
+
+        let interview = Interview::get_by_id(&conn, 1).unwrap();
+        assert_eq!(interview.company_id, 2);
+        assert_eq!(interview.application_id, Some(1));
+        assert_eq!(
+            interview.created_date,
+            timestamp::parse(snapshot["interviews"][0]["created_date"].as_str().unwrap()).ok()
+        );
+
+        let reminder = Reminder::get_by_id(&conn, 1).unwrap();
+        assert_eq!(reminder.interview_id, Some(1));
+        assert_eq!(reminder.contact_id, Some(1));
+        assert_eq!(reminder.recurrence, Some(Recurrence::Weekly));
+
+        let note = Note::get_by_id(&conn, 1).unwrap();
+        assert_eq!(note.target(), Some(NoteTarget::Application(1)));
+        assert_eq!(
+            note.created_date,
+            timestamp::parse(snapshot["notes"][0]["created_date"].as_str().unwrap()).ok()
+        );
+
+        let tags = tag::get_tags::<Company>(&conn, 2).unwrap();
+        assert_eq!(tags[0].name, "remote-only");
+        assert_eq!(tag::get_tagged::<Application>(&conn, 1).unwrap(), vec![1]);
+
+        // Importing again finds every record
+        let report = import_value(&conn, &snapshot).unwrap();
+        for (table, counts) in report.tables().iter() {
+            assert_eq!(counts.unchanged, 1, "{}", table);
+        }
+    }
+
+    #[test]
+    fn test_import_newer_records_win() {
+        let conn = sample_db();
+        let mut snapshot = export_to_value(&conn);
+
+        snapshot["companies"][0]["website"] = json!("https://mozilla.org");
+        snapshot["companies"][0]["last_updated"] = json!("2999-01-01T00:00:00Z");
+        snapshot["job_postings"][0]["description"] = json!("Older change");
+        snapshot["job_postings"][0]["last_updated"] = json!("2000-01-01T00:00:00Z");
+
+        let report = import_value(&conn, &snapshot).unwrap();
+
+        assert_eq!(report.companies.updated, 1);
+        assert_eq!(report.job_postings.unchanged, 1);
+        assert_eq!(
+            Company::get_by_id(&conn, 1).unwrap().website.as_deref(),
+            Some("https://mozilla.org")
+        );
+        assert_eq!(JobPosting::get_by_id(&conn, 1).unwrap().description, None);
+    }
+
+    #[test]
+    fn test_import_version_1() {
+        let conn = create_in_memory_db().unwrap();
+
+        let snapshot = json!({
+            "format": FORMAT,
+            "version": 1,
+            "contact_types": [{"id": 7, "name": "recruiter"}],
+            "contacts": [{"id": 1, "name": "Marcus", "contact_type_id": 7}],
+        });
+        import_value(&conn, &snapshot).unwrap();
+
+        let contact = Contact::get_by_id(&conn, 1).unwrap();
+        assert_eq!(contact.contact_type.unwrap().name, "recruiter");
+    }
+
+    #[test]
+    fn test_import_invalid_snapshot() {
+        let conn = create_in_memory_db().unwrap();
+
+        let test_cases = vec![
+            json!({"companies": []}),
+            json!({"format": FORMAT, "version": SNAPSHOT_VERSION + 1}),
+            json!({"format": FORMAT, "version": 1, "companies": [{"id": 1}]}),
+            json!({
+                "format": FORMAT,
+                "version": 1,
+                "companies": [{"id": 1, "name": "Mozilla"}],
+                "applications": [{"id": 1, "company_id": 2}],
+            }),
+        ];
+
+        for snapshot in test_cases {
+            let result = import_value(&conn, &snapshot);
+
+            assert!(
+                matches!(result, Err(JobSearchError::InvalidInput(_))),
+                "{}",
+                snapshot
+            );
+        }
+
+        // Nothing from the failed imports was kept
+        assert_eq!(Company::count(&conn).unwrap(), 0);
+        assert!(import(&conn, "not json".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,556 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row, NO_PARAMS};
+
+use crate::application::Application;
+use crate::company::Company;
+use crate::contact::Contact;
+use crate::database::Table;
+use crate::entity::{self, Entity, Visibility};
+use crate::errors::{require_id, require_text, JobSearchError};
+use crate::filter::FilterColumns;
+use crate::job_posting::JobPosting;
+use crate::timestamp;
+use crate::utils::with_savepoint;
+
+const SELECT_TAGS: &str = "SELECT tags.id, tags.name, tags.created_date, tags.last_updated, \
+     tags.hide FROM tags";
+
+/// A label such as "fintech" or "remote-only", put on companies, postings,
+/// contacts and applications. Names are unique regardless of case.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tag {
+    pub id: Option<i32>,
+    pub name: String,
+    pub created_date: Option<DateTime<Utc>>,
+    pub last_updated: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hide: bool,
+}
+
+/// A record that can be tagged.
+pub trait Taggable: Entity {
+    /// The table linking the records to their tags.
+    const TAGS_TABLE: &'static str;
+    /// The column of `TAGS_TABLE` holding the id of the record.
+    const TAGS_COLUMN: &'static str;
+}
+
+impl Taggable for Company {
+    const TAGS_TABLE: &'static str = "company_tags";
+    const TAGS_COLUMN: &'static str = "company_id";
+}
+
+impl Taggable for JobPosting {
+    const TAGS_TABLE: &'static str = "job_posting_tags";
+    const TAGS_COLUMN: &'static str = "job_posting_id";
+}
+
+impl Taggable for Contact {
+    const TAGS_TABLE: &'static str = "contact_tags";
+    const TAGS_COLUMN: &'static str = "contact_id";
+}
+
+impl Taggable for Application {
+    const TAGS_TABLE: &'static str = "application_tags";
+    const TAGS_COLUMN: &'static str = "application_id";
+}
+
+// Every table linking records to tags, with the column of the record
+const TAGS_TABLES: [(&str, &str); 4] = [
+    (Company::TAGS_TABLE, Company::TAGS_COLUMN),
+    (JobPosting::TAGS_TABLE, JobPosting::TAGS_COLUMN),
+    (Contact::TAGS_TABLE, Contact::TAGS_COLUMN),
+    (Application::TAGS_TABLE, Application::TAGS_COLUMN),
+];
+
+/// How many visible records carry a tag, by type.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagCount {
+    pub tag: Tag,
+    pub companies: i64,
+    pub job_postings: i64,
+    pub contacts: i64,
+    pub applications: i64,
+}
+
+impl TagCount {
+    // The columns of a tag followed by the four counts
+    fn new_from_row(row: &Row) -> Result<TagCount, JobSearchError> {
+        Ok(TagCount {
+            tag: Tag::new_from_row(row)?,
+            companies: row.get(5)?,
+            job_postings: row.get(6)?,
+            contacts: row.get(7)?,
+            applications: row.get(8)?,
+        })
+    }
+
+    pub fn total(&self) -> i64 {
+        self.companies + self.job_postings + self.contacts + self.applications
+    }
+}
+
+impl Tag {
+    pub fn new(name: String) -> Tag {
+        Tag {
+            id: None,
+            name,
+            created_date: None,
+            last_updated: None,
+            hide: false,
+        }
+    }
+
+    /// Names are matched case-insensitively. Hidden tags are found too.
+    pub(crate) fn get_by_name(
+        conn: &Connection,
+        name: &str,
+    ) -> Result<Option<Tag>, JobSearchError> {
+        let tags: Vec<Tag> = entity::query(
+            conn,
+            &format!("{} WHERE name = (?1)", SELECT_TAGS),
+            params![name.trim()],
+        )?;
+
+        Ok(tags.into_iter().next())
+    }
+
+    /// Looks the tag up by name, adding it if there is none yet.
+    pub(crate) fn get_or_add(conn: &Connection, name: &str) -> Result<Tag, JobSearchError> {
+        match Tag::get_by_name(conn, name)? {
+            Some(tag) => Ok(tag),
+            None => {
+                let mut tag = Tag::new(name.trim().to_string());
+                tag.add_to_db(conn)?;
+
+                Ok(tag)
+            }
+        }
+    }
+
+    /// Moves every record tagged with `from` over to `into` and deletes
+    /// `from`. Records that already carry both keep a single `into`.
+    pub(crate) fn merge(
+        conn: &Connection,
+        from: &mut Tag,
+        into: &Tag,
+    ) -> Result<(), JobSearchError> {
+        let from_id = require_id(from.id, "tag")?;
+        let into_id = require_id(into.id, "tag")?;
+
+        if from_id == into_id {
+            return Err(JobSearchError::InvalidInput(
+                "a tag can not be merged into itself".to_string(),
+            ));
+        }
+
+        with_savepoint(conn, || {
+            for (table, column) in TAGS_TABLES.iter() {
+                let _ = conn.execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO {table} (tag_id, {column}) \
+                         SELECT (?2), {column} FROM {table} WHERE tag_id = (?1)",
+                        table = table,
+                        column = column
+                    ),
+                    params![from_id, into_id],
+                )?;
+            }
+
+            from.delete_from_db(conn)
+        })
+    }
+
+    /// Every visible tag with the number of visible records carrying it,
+    /// sorted by name.
+    pub(crate) fn counts(conn: &Connection) -> Result<Vec<TagCount>, JobSearchError> {
+        let count = |table: &str, column: &str, records: &str| {
+            format!(
+                "(SELECT COUNT(*) FROM {table} JOIN {records} ON {records}.id = {table}.{column} \
+                 WHERE {table}.tag_id = tags.id AND {visible})",
+                table = table,
+                column = column,
+                records = records,
+                visible = Visibility::Visible.condition(records)
+            )
+        };
+
+        let sql = format!(
+            "SELECT tags.id, tags.name, tags.created_date, tags.last_updated, tags.hide, \
+             {}, {}, {}, {} FROM tags WHERE {} ORDER BY tags.name, tags.id",
+            count(Company::TAGS_TABLE, Company::TAGS_COLUMN, Company::TABLE),
+            count(
+                JobPosting::TAGS_TABLE,
+                JobPosting::TAGS_COLUMN,
+                JobPosting::TABLE
+            ),
+            count(Contact::TAGS_TABLE, Contact::TAGS_COLUMN, Contact::TABLE),
+            count(
+                Application::TAGS_TABLE,
+                Application::TAGS_COLUMN,
+                Application::TABLE
+            ),
+            Visibility::Visible.condition(Tag::TABLE)
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(NO_PARAMS, |row| Ok(TagCount::new_from_row(row)))?;
+
+        let mut counts = Vec::new();
+        for count in rows {
+            counts.push(count??);
+        }
+
+        Ok(counts)
+    }
+}
+
+/// The tags on the record with the given id, sorted by name.
+pub(crate) fn get_tags<T: Taggable>(
+    conn: &Connection,
+    id: i32,
+) -> Result<Vec<Tag>, JobSearchError> {
+    entity::query(
+        conn,
+        &format!(
+            "{} JOIN {table} ON {table}.tag_id = tags.id WHERE {table}.{column} = (?1) \
+             ORDER BY tags.name",
+            SELECT_TAGS,
+            table = T::TAGS_TABLE,
+            column = T::TAGS_COLUMN
+        ),
+        params![id],
+    )
+}
+
+/// The ids of the records carrying the tag, hidden ones included.
+pub(crate) fn get_tagged<T: Taggable>(
+    conn: &Connection,
+    tag_id: i32,
+) -> Result<Vec<i32>, JobSearchError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {column} FROM {table} WHERE tag_id = (?1) ORDER BY {column}",
+        table = T::TAGS_TABLE,
+        column = T::TAGS_COLUMN
+    ))?;
+
+    let ids = stmt
+        .query_map(params![tag_id], |row| row.get(0))?
+        .collect::<Result<Vec<i32>, rusqlite::Error>>()?;
+
+    Ok(ids)
+}
+
+/// Tags the record with `name`, adding the tag if it is new. Tagging a
+/// record twice with the same tag does nothing.
+pub(crate) fn add_tag<T: Taggable>(
+    conn: &Connection,
+    id: i32,
+    name: &str,
+) -> Result<Tag, JobSearchError> {
+    with_savepoint(conn, || {
+        let tag = Tag::get_or_add(conn, name)?;
+
+        let _ = conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} (tag_id, {}) VALUES (?1, ?2)",
+                T::TAGS_TABLE,
+                T::TAGS_COLUMN
+            ),
+            params![tag.id, id],
+        )?;
+
+        Ok(tag)
+    })
+}
+
+/// Takes the tag `name` off the record. Returns whether it was there.
+pub(crate) fn remove_tag<T: Taggable>(
+    conn: &Connection,
+    id: i32,
+    name: &str,
+) -> Result<bool, JobSearchError> {
+    let removed = conn.execute(
+        &format!(
+            "DELETE FROM {table} WHERE {column} = (?1) \
+             AND tag_id = (SELECT id FROM tags WHERE name = (?2))",
+            table = T::TAGS_TABLE,
+            column = T::TAGS_COLUMN
+        ),
+        params![id, name.trim()],
+    )?;
+
+    Ok(removed > 0)
+}
+
+impl Entity for Tag {
+    const TABLE: &'static str = "tags";
+    const NAME: &'static str = "tag";
+    const SELECT: &'static str = SELECT_TAGS;
+    const FILTER_COLUMNS: FilterColumns = FilterColumns {
+        date: Some("tags.created_date"),
+        text: &["tags.name"],
+        name: Some("tags.name"),
+        ..FilterColumns::NONE
+    };
+
+    fn new_from_row(row: &Row) -> Result<Tag, JobSearchError> {
+        let tag = Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            created_date: timestamp::get(row, 2)?,
+            last_updated: timestamp::get(row, 3)?,
+            hide: row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+        };
+
+        Ok(tag)
+    }
+
+    fn id(&self) -> Option<i32> {
+        self.id
+    }
+
+    fn set_hidden(&mut self, hide: bool) {
+        self.hide = hide;
+    }
+
+    fn add_to_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        require_text(&self.name, "tag name")?;
+
+        let _ = conn.execute(
+            "INSERT INTO tags (name, hide, created_date, last_updated) \
+             VALUES (?1, ?2, COALESCE(?3, CURRENT_TIMESTAMP), ?4)",
+            params![
+                self.name,
+                self.hide,
+                timestamp::to_sql(&self.created_date),
+                timestamp::to_sql(&self.last_updated)
+            ],
+        )?;
+
+        *self = Tag::get_by_id(conn, conn.last_insert_rowid() as i32)?;
+
+        Ok(())
+    }
+
+    fn update_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        let id = require_id(self.id, "tag")?;
+        require_text(&self.name, "tag name")?;
+
+        let _ = conn.execute(
+            "UPDATE tags SET name = (?1), hide = (?2) WHERE id = (?3)",
+            params![self.name, self.hide, id],
+        )?;
+
+        *self = Tag::get_by_id(conn, id)?;
+
+        Ok(())
+    }
+
+    fn delete_from_db(&mut self, conn: &Connection) -> Result<(), JobSearchError> {
+        entity::delete::<Tag>(conn, self.id)?;
+
+        self.id = None;
+
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a, Tag> {
+    pub fn get_by_name(&self, name: &str) -> Result<Option<Tag>, JobSearchError> {
+        Tag::get_by_name(self.conn(), name)
+    }
+
+    /// Fails with `Duplicate` if another tag already has the name; `merge`
+    /// combines the two instead.
+    pub fn rename(&self, tag: &mut Tag, name: &str) -> Result<(), JobSearchError> {
+        tag.name = name.trim().to_string();
+        tag.update_db(self.conn())
+    }
+
+    /// Moves every record tagged with `from` over to `into` and deletes
+    /// `from`.
+    pub fn merge(&self, from: &mut Tag, into: &Tag) -> Result<(), JobSearchError> {
+        Tag::merge(self.conn(), from, into)
+    }
+
+    /// Every visible tag with the number of visible records of each type
+    /// carrying it, sorted by name.
+    pub fn counts(&self) -> Result<Vec<TagCount>, JobSearchError> {
+        Tag::counts(self.conn())
+    }
+}
+
+impl<'a, T: Taggable> Table<'a, T> {
+    /// Tags the record with `name`, adding the tag if it is new. Fails with
+    /// a `ForeignKeyViolation` if there is no record with the id.
+    pub fn tag(&self, id: i32, name: &str) -> Result<Tag, JobSearchError> {
+        add_tag::<T>(self.conn(), id, name)
+    }
+
+    /// Takes the tag `name` off the record, returning whether it was there.
+    /// The tag itself is kept.
+    pub fn untag(&self, id: i32, name: &str) -> Result<bool, JobSearchError> {
+        remove_tag::<T>(self.conn(), id, name)
+    }
+
+    /// The tags on the record, sorted by name.
+    pub fn tags(&self, id: i32) -> Result<Vec<Tag>, JobSearchError> {
+        get_tags::<T>(self.conn(), id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_in_memory_db;
+    use crate::filter::{Filter, TagMatch};
+    use crate::interview::Interview;
+    use crate::interview_type::InterviewType;
+
+    fn add_company(conn: &Connection, name: &str) -> i32 {
+        let mut company = Company::new(name.to_string(), None, None, None);
+        company.add_to_db(conn).unwrap();
+        company.id.unwrap()
+    }
+
+    fn names(tags: &[Tag]) -> Vec<&str> {
+        tags.iter().map(|tag| tag.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tag_and_untag() {
+        let conn = create_in_memory_db().unwrap();
+        let google = add_company(&conn, "google");
+
+        let remote = add_tag::<Company>(&conn, google, "remote-only").unwrap();
+        add_tag::<Company>(&conn, google, " Fintech ").unwrap();
+        // Names are matched ignoring case, so this is the same tag
+        let again = add_tag::<Company>(&conn, google, "Remote-Only").unwrap();
+
+        assert_eq!(again.id, remote.id);
+        assert_eq!(Tag::count(&conn).unwrap(), 2);
+        assert_eq!(
+            names(&get_tags::<Company>(&conn, google).unwrap()),
+            vec!["Fintech", "remote-only"]
+        );
+
+        assert!(remove_tag::<Company>(&conn, google, "fintech").unwrap());
+        assert!(!remove_tag::<Company>(&conn, google, "fintech").unwrap());
+        assert_eq!(get_tags::<Company>(&conn, google).unwrap().len(), 1);
+        assert_eq!(Tag::count(&conn).unwrap(), 2);
+
+        assert!(matches!(
+            add_tag::<Contact>(&conn, 42, "referral"),
+            Err(JobSearchError::ForeignKeyViolation(_))
+        ));
+        // The savepoint keeps the tag from being added on its own
+        assert!(Tag::get_by_name(&conn, "referral").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rename_and_merge() {
+        let conn = create_in_memory_db().unwrap();
+        let google = add_company(&conn, "google");
+        let stripe = add_company(&conn, "stripe");
+
+        add_tag::<Company>(&conn, google, "fin-tech").unwrap();
+        add_tag::<Company>(&conn, google, "fintech").unwrap();
+        let mut typo = add_tag::<Company>(&conn, stripe, "fin-tech").unwrap();
+        let fintech = Tag::get_by_name(&conn, "fintech").unwrap().unwrap();
+
+        typo.name = "FINTECH".to_string();
+        assert!(matches!(
+            typo.update_db(&conn),
+            Err(JobSearchError::Duplicate { .. })
+        ));
+
+        Tag::merge(&conn, &mut typo, &fintech).unwrap();
+
+        assert_eq!(typo.id, None);
+        assert_eq!(Tag::count(&conn).unwrap(), 1);
+        assert_eq!(
+            names(&get_tags::<Company>(&conn, google).unwrap()),
+            vec!["fintech"]
+        );
+        assert_eq!(
+            names(&get_tags::<Company>(&conn, stripe).unwrap()),
+            vec!["fintech"]
+        );
+
+        let mut fintech = fintech;
+        assert!(matches!(
+            Tag::merge(
+                &conn,
+                &mut fintech,
+                &Tag::get_by_name(&conn, "fintech").unwrap().unwrap()
+            ),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_counts() {
+        let conn = create_in_memory_db().unwrap();
+        let google = add_company(&conn, "google");
+        let mut stripe = Company::new("stripe".to_string(), None, None, None);
+        stripe.add_to_db(&conn).unwrap();
+
+        let mut application = Application::new(google, None, None);
+        application.add_to_db(&conn).unwrap();
+
+        add_tag::<Company>(&conn, google, "dream-job").unwrap();
+        add_tag::<Company>(&conn, stripe.id.unwrap(), "dream-job").unwrap();
+        add_tag::<Application>(&conn, application.id.unwrap(), "dream-job").unwrap();
+        add_tag::<Application>(&conn, application.id.unwrap(), "referral").unwrap();
+        stripe.archive(&conn).unwrap();
+
+        let counts = Tag::counts(&conn).unwrap();
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].tag.name, "dream-job");
+        assert_eq!(counts[0].companies, 1);
+        assert_eq!(counts[0].applications, 1);
+        assert_eq!(counts[0].total(), 2);
+        assert_eq!(counts[1].tag.name, "referral");
+        assert_eq!(counts[1].total(), 1);
+    }
+
+    #[test]
+    fn test_filter() {
+        let conn = create_in_memory_db().unwrap();
+        let google = add_company(&conn, "google");
+        let stripe = add_company(&conn, "stripe");
+        add_company(&conn, "mozilla");
+
+        add_tag::<Company>(&conn, google, "remote-only").unwrap();
+        add_tag::<Company>(&conn, google, "dream-job").unwrap();
+        add_tag::<Company>(&conn, stripe, "fintech").unwrap();
+        add_tag::<Company>(&conn, stripe, "remote-only").unwrap();
+
+        let count = |tags: &[&str], tag_match| {
+            Company::count_matching(&conn, &Filter::new().tagged(tags, tag_match)).unwrap()
+        };
+
+        assert_eq!(count(&["REMOTE-ONLY"], TagMatch::Any), 2);
+        assert_eq!(count(&["dream-job", "fintech"], TagMatch::Any), 2);
+        assert_eq!(count(&["remote-only", "fintech"], TagMatch::All), 1);
+        assert_eq!(count(&["unknown"], TagMatch::Any), 0);
+
+        // Interviews go by the tags of their company
+        let mut phone = InterviewType::new("phone".to_string());
+        phone.add_to_db(&conn).unwrap();
+        Interview::new(phone.id.unwrap(), stripe, None)
+            .add_to_db(&conn)
+            .unwrap();
+
+        let filter = Filter::new().tagged(&["fintech"], TagMatch::Any);
+        assert_eq!(Interview::count_matching(&conn, &filter).unwrap(), 1);
+        assert!(matches!(
+            Tag::list(&conn, &filter),
+            Err(JobSearchError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::Row;
+
+use crate::errors::JobSearchError;
+
+// Timestamps without an offset are in UTC, which is what SQLite's
+// CURRENT_TIMESTAMP writes
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+const OFFSET_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M:%S%.f%z"];
+
+/// Parses a timestamp as written by SQLite, by this library or by earlier
+/// versions of it.
+///
+/// Accepts RFC 3339 (`2020-05-14T21:16:39+02:00`), `YYYY-MM-DD HH:MM:SS`
+/// with optional fractional seconds, with or without a `T` or an offset, and
+/// plain dates. Values without an offset are taken to be in UTC.
+pub fn parse(value: &str) -> Result<DateTime<Utc>, JobSearchError> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    for format in OFFSET_FORMATS {
+        if let Ok(time) = DateTime::parse_from_str(&value.replacen('T', " ", 1), format) {
+            return Ok(time.with_timezone(&Utc));
+        }
+    }
+
+    for format in NAIVE_FORMATS {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(Utc.from_utc_datetime(&time));
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(time) = date.and_hms_opt(0, 0, 0) {
+            return Ok(Utc.from_utc_datetime(&time));
+        }
+    }
+
+    Err(JobSearchError::InvalidInput(format!(
+        "{:?} is not a timestamp",
+        value
+    )))
+}
+
+/// Converts a wall clock time in `tz` to UTC.
+///
+/// When clocks go back the same wall clock time happens twice, and the
+/// earlier one is used. Times skipped when clocks go forward do not exist
+/// and fail with `InvalidInput`.
+pub fn from_local<Tz: TimeZone>(
+    local: NaiveDateTime,
+    tz: &Tz,
+) -> Result<DateTime<Utc>, JobSearchError> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Ok(time.with_timezone(&Utc)),
+        LocalResult::None => Err(JobSearchError::InvalidInput(format!(
+            "{} does not exist in the time zone",
+            local
+        ))),
+    }
+}
+
+/// The same instant as seen in `tz`, using the offset `tz` has on that
+/// date.
+pub fn to_timezone<Tz: TimeZone>(time: &DateTime<Utc>, tz: &Tz) -> DateTime<Tz> {
+    time.with_timezone(tz)
+}
+
+/// Formats `time` as seen in `tz`, with the `chrono::format::strftime`
+/// syntax.
+pub fn format_in<Tz: TimeZone>(time: &DateTime<Utc>, tz: &Tz, format: &str) -> String
+where
+    Tz::Offset: Display,
+{
+    to_timezone(time, tz).format(format).to_string()
+}
+
+/// Formats `time` like SQLite's CURRENT_TIMESTAMP, so timestamps written by
+/// this library sort together with the ones the database writes itself.
+pub(crate) fn to_sql(time: &Option<DateTime<Utc>>) -> Option<String> {
+    time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+// Reads a column with `parse`, instead of the stricter parsing of rusqlite
+struct Timestamp(DateTime<Utc>);
+
+impl FromSql for Timestamp {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(seconds) => Utc
+                .timestamp_opt(seconds, 0)
+                .single()
+                .map(Timestamp)
+                .ok_or(FromSqlError::OutOfRange(seconds)),
+            value => parse(value.as_str()?)
+                .map(Timestamp)
+                .map_err(|err| FromSqlError::Other(Box::new(err))),
+        }
+    }
+}
+
+/// Reads the timestamp in column `idx`, which may be NULL.
+pub(crate) fn get(row: &Row, idx: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    Ok(row
+        .get::<_, Option<Timestamp>>(idx)?
+        .map(|Timestamp(time)| time))
+}
+
+/// Reads the timestamp in column `idx`, which may not be NULL.
+pub(crate) fn get_required(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let Timestamp(time) = row.get(idx)?;
+
+    Ok(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+    use rusqlite::{Connection, NO_PARAMS};
+
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let test_cases = vec![
+            ("2020-05-14 21:16:39", "2020-05-14T21:16:39Z"),
+            ("2020-05-14T21:16:39", "2020-05-14T21:16:39Z"),
+            ("2020-05-14 21:16:39.250", "2020-05-14T21:16:39.250Z"),
+            ("2020-05-14 21:16", "2020-05-14T21:16:00Z"),
+            ("2020-05-14T23:16:39+02:00", "2020-05-14T21:16:39Z"),
+            ("2020-05-14 23:16:39+02:00", "2020-05-14T21:16:39Z"),
+            ("2020-05-14 23:16:39+0200", "2020-05-14T21:16:39Z"),
+            ("2020-05-14T21:16:39.123456Z", "2020-05-14T21:16:39.123456Z"),
+            ("2020-05-14", "2020-05-14T00:00:00Z"),
+        ];
+
+        for (value, expected) in test_cases {
+            assert_eq!(parse(value).unwrap(), utc(expected), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let test_cases = vec!["", "2020-13-14 21:16:39", "14/05/2020", "not a date"];
+
+        for value in test_cases {
+            assert!(
+                matches!(parse(value), Err(JobSearchError::InvalidInput(_))),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_local() {
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let local = NaiveDateTime::parse_from_str("2020-05-08 22:30", "%Y-%m-%d %H:%M").unwrap();
+
+        let time = from_local(local, &tz).unwrap();
+
+        assert_eq!(time, utc("2020-05-09T03:30:00Z"));
+        assert_eq!(to_timezone(&time, &tz).naive_local(), local);
+        assert_eq!(
+            format_in(&time, &tz, "%Y-%m-%d %H:%M %z"),
+            "2020-05-08 22:30 -0500"
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let (stored, missing, seconds) = conn
+            .query_row(
+                "SELECT CURRENT_TIMESTAMP, NULL, 1589490999",
+                NO_PARAMS,
+                |row| Ok((get_required(row, 0)?, get(row, 1)?, get_required(row, 2)?)),
+            )
+            .unwrap();
+
+        // CURRENT_TIMESTAMP is in UTC, so it is close to now whatever the
+        // local time zone is
+        assert!((Utc::now() - stored).num_minutes().abs() < 1);
+        assert_eq!(missing, None);
+        assert_eq!(seconds, utc("2020-05-14T21:16:39Z"));
+
+        let invalid = conn.query_row("SELECT 'yesterday'", NO_PARAMS, |row| get(row, 0));
+        assert!(invalid.is_err());
+    }
+}
@@ -1,59 +1,42 @@
-use chrono::prelude::*;
-use chrono::{DateTime, Local};
-use std::fs;
-
-#[allow(dead_code)]
-pub fn convert_option_string_to_option_date(
-    string_time: Option<String>,
-) -> Option<DateTime<Local>> {
-    match string_time {
-        Some(time) => match parse_time(time.as_str()) {
-            Ok(date) => Some(date),
-            Err(_err) => {
-                //log error
-                None
-            }
-        },
-        None => None,
-    }
-}
+use rusqlite::{Connection, NO_PARAMS};
 
-#[allow(dead_code)]
-fn parse_time(date: &str) -> Result<DateTime<Local>, std::num::ParseIntError> {
-    //"2020-05-14 21:16:39"
+use crate::errors::JobSearchError;
 
-    let date_time_parts: Vec<&str> = date.split(' ').collect();
-    let date_parts: Vec<u32> = date_time_parts[0]
-        .split('-')
-        .map(|part| part.parse::<u32>().unwrap())
-        .collect();
+/// Runs `f` inside a savepoint, so either all of its changes are kept or,
+/// if it fails, none of them. Unlike a transaction this works on a shared
+/// connection and can be nested.
+pub(crate) fn with_savepoint<T, F>(conn: &Connection, f: F) -> Result<T, JobSearchError>
+where
+    F: FnOnce() -> Result<T, JobSearchError>,
+{
+    conn.execute("SAVEPOINT job_search", NO_PARAMS)?;
 
-    let time_parts: Vec<u32> = date_time_parts[1]
-        .split(':')
-        .map(|part| part.parse::<u32>().unwrap())
-        .collect();
+    match f() {
+        Ok(value) => {
+            conn.execute("RELEASE job_search", NO_PARAMS)?;
 
-    let result = Local
-        .ymd(date_parts[0] as i32, date_parts[1], date_parts[2])
-        .and_hms(time_parts[0], time_parts[1], time_parts[2]);
-
-    println!("{}", result.format("%Y-%m-%d %H:%M:%S"));
+            Ok(value)
+        }
+        Err(err) => {
+            conn.execute_batch("ROLLBACK TO job_search; RELEASE job_search")?;
 
-    Ok(result)
+            Err(err)
+        }
+    }
 }
 
-#[allow(dead_code)]
-fn file_exist(path: &str) -> bool {
-    let metadata = if let Ok(data) = fs::metadata(path) {
-        Some(data)
-    } else {
-        None
-    };
-
-    match metadata {
-        Some(data) => data.is_file(),
-        None => false,
-    }
+/// Runs `f` inside a savepoint that is always rolled back, to see what it
+/// would do without keeping any of its changes.
+pub(crate) fn with_rollback<T, F>(conn: &Connection, f: F) -> Result<T, JobSearchError>
+where
+    F: FnOnce() -> Result<T, JobSearchError>,
+{
+    conn.execute("SAVEPOINT job_search_dry_run", NO_PARAMS)?;
+
+    let result = f();
+    conn.execute_batch("ROLLBACK TO job_search_dry_run; RELEASE job_search_dry_run")?;
+
+    result
 }
 
 #[cfg(test)]
@@ -61,22 +44,42 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_file_exist() {
-        let test_cases = vec![("", false), ("src", false), ("Cargo.toml", true)];
+    fn test_with_savepoint_rolls_back() {
+        let conn = crate::create_in_memory_db().unwrap();
 
-        for (path, expected) in test_cases {
-            let result = file_exist(path);
+        let result: Result<(), JobSearchError> = with_savepoint(&conn, || {
+            conn.execute("INSERT INTO companies (name) VALUES ('google')", NO_PARAMS)?;
+            conn.execute("INSERT INTO companies (name) VALUES ('google')", NO_PARAMS)?;
 
-            assert_eq!(result, expected);
-        }
-    }
+            Ok(())
+        });
 
-    #[test]
-    fn test_parse_time() {
-        let string_time = "2020-05-14 21:16:39";
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM companies", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .unwrap();
 
-        let result = parse_time(string_time).unwrap();
+        assert!(result.is_err());
+        assert_eq!(count, 0);
+    }
 
-        assert_eq!(result.year(), 2020);
+    #[test]
+    fn test_with_rollback() {
+        let conn = crate::create_in_memory_db().unwrap();
+
+        let inserted = with_rollback(&conn, || {
+            Ok(conn.execute("INSERT INTO companies (name) VALUES ('google')", NO_PARAMS)?)
+        })
+        .unwrap();
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM companies", NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .unwrap();
+
+        assert_eq!(inserted, 1);
+        assert_eq!(count, 0);
     }
 }